@@ -0,0 +1,57 @@
+use raytracer::camera::Camera;
+use raytracer::color::Color;
+use raytracer::hit::list::HittableList;
+use raytracer::hit::sphere::Sphere;
+use raytracer::materials::{Dielectric, Lambertian, Metal};
+use raytracer::sampling::AdaptiveSampling;
+use raytracer::vec3::{Point3, Vec3};
+
+fn build_world() -> HittableList {
+    let mut world = HittableList::new();
+
+    let ground_material = Lambertian::new(Color::new(0.8, 0.8, 0.0));
+    world.add(Sphere::new(
+        Point3::new(0., -100.5, -1.),
+        100.,
+        ground_material.into(),
+    ));
+
+    let center_material = Lambertian::new(Color::new(0.1, 0.2, 0.5));
+    world.add(Sphere::new(
+        Point3::new(0., 0., -1.2),
+        0.5,
+        center_material.into(),
+    ));
+
+    let glass = Dielectric::new(1.5);
+    world.add(Sphere::new(Point3::new(-1., 0., -1.), 0.5, glass.into()));
+
+    let metal = Metal::new(Color::new(0.8, 0.6, 0.2), 0.3);
+    world.add(Sphere::new(Point3::new(1., 0., -1.), 0.5, metal.into()));
+    world
+}
+
+fn main() -> std::io::Result<()> {
+    env_logger::init();
+    let world = build_world();
+
+    let camera = Camera::new(
+        16. / 9.,
+        400,
+        0,
+        50,
+        90.,
+        Point3::zero(),
+        Point3::new(0., 0., -1.),
+        Vec3::new(0., 1., 0.),
+        0.,
+        1.,
+    );
+
+    // Spend between 16 and 1024 samples on each pixel, aiming for a 2% relative error.
+    let sampling = AdaptiveSampling::new(16, 1024, 0.02);
+    let buffer = camera.render_adaptive(&world, &sampling);
+
+    buffer.to_image().save_ppm("adaptive.ppm")?;
+    buffer.heatmap().save_ppm("adaptive_heatmap.ppm")
+}
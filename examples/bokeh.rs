@@ -0,0 +1,81 @@
+use raytracer::aperture::Aperture;
+use raytracer::camera::Camera;
+use raytracer::color::Color;
+use raytracer::hit::list::HittableList;
+use raytracer::hit::sphere::Sphere;
+use raytracer::image::Image;
+use raytracer::materials::{DiffuseLight, Lambertian};
+use raytracer::progressive::ProgressiveSettings;
+use raytracer::vec3::{Point3, Vec3};
+use std::env;
+
+// A sphere in focus in front of a wall of small far away lights, which blur into the shape of
+// the aperture.
+fn build_world() -> HittableList {
+    let mut world = HittableList::new();
+
+    for x in -6..=6 {
+        for y in -3..=3 {
+            let hue = (x + 6) as f64 / 12.;
+            let color = Color::new(1. - hue, 0.6, hue) * 4.;
+            let center = Point3::new(x as f64 * 1.5, y as f64 * 1.5 + 1., -20.);
+            world.add(Sphere::new(center, 0.08, DiffuseLight::new(color).into()));
+        }
+    }
+
+    let white = Lambertian::new(Color::new(0.8, 0.8, 0.8));
+    world.add_named(
+        "subject",
+        Sphere::new(Point3::new(0., 0., -2.), 0.5, white.into()),
+    );
+    let light = DiffuseLight::new(Color::new(6., 6., 6.));
+    world.add(Sphere::new(Point3::new(2., 3., 0.), 1., light.into()));
+    world
+}
+
+fn render(world: &HittableList, aperture: Aperture, path: &str) -> std::io::Result<()> {
+    let camera = Camera::new(
+        16. / 9.,
+        480,
+        0,
+        20,
+        40.,
+        Point3::new(0., 0., 0.5),
+        Point3::new(0., 0., -2.),
+        Vec3::new(0., 1., 0.),
+        3.,
+        2.5,
+    );
+    // Focus on the front of the white sphere.
+    let focus_distance = camera
+        .focus_distance_to_object(world, "subject")
+        .expect("the subject is in the world");
+    let camera = camera
+        .with_focus_distance(focus_distance)
+        .expect("the subject is in front of the camera")
+        .with_background(Color::new(0.01, 0.01, 0.02))
+        .with_aperture(aperture);
+    let settings = ProgressiveSettings::new(128);
+    camera
+        .render_progressive(world, &settings)
+        .to_image()
+        .save_ppm(path)
+}
+
+fn main() -> std::io::Result<()> {
+    env_logger::init();
+    let world = build_world();
+
+    render(&world, Aperture::polygon(6, 15.), "bokeh_hexagon.ppm")?;
+    render(
+        &world,
+        Aperture::default().with_squeeze(2.).with_vignetting(0.8),
+        "bokeh_anamorphic.ppm",
+    )?;
+    // An optional aperture mask, such as a white star on black.
+    if let Some(path) = env::args().nth(1) {
+        let mask = Image::load_ppm(path)?;
+        render(&world, Aperture::mask(&mask), "bokeh_mask.ppm")?;
+    }
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use raytracer::animation::{CameraTracks, FrameSequence};
+use raytracer::camera_settings::{CameraError, CameraSettings};
+use raytracer::color::Color;
+use raytracer::film::Filter;
+use raytracer::hit::cuboid::Cuboid;
+use raytracer::hit::list::HittableList;
+use raytracer::hit::plane::Plane;
+use raytracer::hit::sphere::Sphere;
+use raytracer::materials::{Lambertian, Metal};
+use raytracer::texture::Checker;
+use raytracer::vec3::{Point3, Vec3};
+use raytracer::video::{GifPalette, GifWriter};
+use std::fs::File;
+use std::io::BufWriter;
+
+const FRAMES: u32 = 24;
+
+// A sphere in the middle of an avenue of pillars on a checkerboard.
+fn build_world() -> HittableList {
+    let mut world = HittableList::new();
+
+    let checker = Checker::new(Color::new(0.2, 0.2, 0.25), Color::new(0.8, 0.8, 0.8), 1.);
+    world.add(Plane::new(
+        Point3::zero(),
+        Vec3::new(0., 1., 0.),
+        Lambertian::new(checker).into(),
+    ));
+    world.add(Sphere::new(
+        Point3::new(0., 1., 0.),
+        1.,
+        Lambertian::new(Color::new(0.8, 0.2, 0.1)).into(),
+    ));
+    let stone = Lambertian::new(Color::new(0.7, 0.65, 0.5));
+    for row in -4..=4 {
+        for x in [-3., 3.] {
+            let z = row as f64 * 4.;
+            world.add(Cuboid::new(
+                &Point3::new(x - 0.4, 0., z - 0.4),
+                &Point3::new(x + 0.4, 3., z + 0.4),
+                stone.clone().into(),
+            ));
+        }
+    }
+    let mirror = Metal::new(Color::new(0.8, 0.8, 0.9), 0.05);
+    world.add(Sphere::new(Point3::new(0., 0.6, -8.), 0.6, mirror.into()));
+    world
+}
+
+fn render_gif(
+    settings: &CameraSettings,
+    tracks: &CameraTracks,
+    path: &str,
+) -> Result<(), CameraError> {
+    log::info!("Rendering {}", path);
+    let mut gif = GifWriter::new(BufWriter::new(File::create(path)?), 12)
+        .with_palette(GifPalette::FirstFrame);
+    FrameSequence::new(0..=FRAMES - 1, path)
+        .with_filter(Filter::tent())
+        .render_video(
+            |frame| {
+                Ok((
+                    tracks.settings_at(settings, frame as f64).build()?,
+                    build_world(),
+                ))
+            },
+            &mut gif,
+        )
+}
+
+fn main() -> Result<(), CameraError> {
+    env_logger::init();
+    let settings = CameraSettings::new()
+        .with_image_size(240, 16. / 9.)
+        .with_samples(8, 8)
+        .with_view(
+            Point3::new(0., 1.5, 8.),
+            Point3::new(0., 1., 0.),
+            Vec3::new(0., 1., 0.),
+        )
+        .with_vfov(30.)
+        .with_background(Color::new(0.6, 0.7, 0.9));
+
+    let orbit = CameraTracks::orbit(&settings, FRAMES, 20.);
+    render_gif(&settings, &orbit, "orbit.gif")?;
+
+    let dolly = CameraTracks::dolly_zoom(&settings, FRAMES - 1, 75.)?;
+    render_gif(&settings, &dolly, "dolly_zoom.gif")?;
+
+    let waypoints = [
+        Point3::new(0., 1.5, 16.),
+        Point3::new(-1.6, 1.3, 5.),
+        Point3::new(-1.6, 1.8, -3.),
+        Point3::new(0., 4., -14.),
+    ];
+    let flight = CameraTracks::fly_through(&settings, &waypoints, FRAMES - 1, 3.)?;
+    render_gif(&settings, &flight, "fly_through.gif")
+}
@@ -0,0 +1,94 @@
+use raytracer::camera::Camera;
+use raytracer::color::Color;
+use raytracer::hit::cuboid::Cuboid;
+use raytracer::hit::instance::Instance;
+use raytracer::hit::list::HittableList;
+use raytracer::hit::quad::Quad;
+use raytracer::materials::{DiffuseLight, Lambertian};
+use raytracer::matrix::Transform;
+use raytracer::vec3::{Point3, Vec3};
+
+fn build_world() -> HittableList {
+    let mut world = HittableList::new();
+
+    let red = Lambertian::new(Color::new(0.65, 0.05, 0.05));
+    let white = Lambertian::new(Color::new(0.73, 0.73, 0.73));
+    let green = Lambertian::new(Color::new(0.12, 0.45, 0.15));
+    let light = DiffuseLight::new(Color::new(15., 15., 15.));
+
+    world.add(Quad::new(
+        Point3::new(555., 0., 0.),
+        Vec3::new(0., 555., 0.),
+        Vec3::new(0., 0., 555.),
+        green.into(),
+    ));
+    world.add(Quad::new(
+        Point3::zero(),
+        Vec3::new(0., 555., 0.),
+        Vec3::new(0., 0., 555.),
+        red.into(),
+    ));
+    world.add(Quad::new(
+        Point3::new(343., 554., 332.),
+        Vec3::new(-130., 0., 0.),
+        Vec3::new(0., 0., -105.),
+        light.into(),
+    ));
+    world.add(Quad::new(
+        Point3::zero(),
+        Vec3::new(555., 0., 0.),
+        Vec3::new(0., 0., 555.),
+        white.clone().into(),
+    ));
+    world.add(Quad::new(
+        Point3::new(555., 555., 555.),
+        Vec3::new(-555., 0., 0.),
+        Vec3::new(0., 0., -555.),
+        white.clone().into(),
+    ));
+    world.add(Quad::new(
+        Point3::new(0., 0., 555.),
+        Vec3::new(555., 0., 0.),
+        Vec3::new(0., 555., 0.),
+        white.clone().into(),
+    ));
+
+    let tall_box = Cuboid::new(
+        &Point3::zero(),
+        &Point3::new(165., 330., 165.),
+        white.clone().into(),
+    );
+    let transform = Transform::rotation(&Vec3::new(0., 1., 0.), 15.)
+        .then(&Transform::translation(&Vec3::new(265., 0., 295.)));
+    world.add(Instance::of(tall_box, transform));
+
+    let short_box = Cuboid::new(
+        &Point3::zero(),
+        &Point3::new(165., 165., 165.),
+        white.into(),
+    );
+    let transform = Transform::rotation(&Vec3::new(0., 1., 0.), -18.)
+        .then(&Transform::translation(&Vec3::new(130., 0., 65.)));
+    world.add(Instance::of(short_box, transform));
+    world
+}
+
+fn main() {
+    env_logger::init();
+    let world = build_world();
+
+    let camera = Camera::new(
+        1.,
+        600,
+        200,
+        50,
+        40.,
+        Point3::new(278., 278., -800.),
+        Point3::new(278., 278., 0.),
+        Vec3::new(0., 1., 0.),
+        0.,
+        10.,
+    )
+    .with_background(Color::zero());
+    camera.render(&world)
+}
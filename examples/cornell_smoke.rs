@@ -0,0 +1,118 @@
+use raytracer::camera::Camera;
+use raytracer::color::Color;
+use raytracer::hit::cuboid::Cuboid;
+use raytracer::hit::instance::Instance;
+use raytracer::hit::list::HittableList;
+use raytracer::hit::medium::ConstantMedium;
+use raytracer::hit::quad::Quad;
+use raytracer::hit::sphere::Sphere;
+use raytracer::materials::{Dielectric, DiffuseLight, HenyeyGreenstein, Isotropic, Lambertian};
+use raytracer::matrix::Transform;
+use raytracer::vec3::{Point3, Vec3};
+
+fn build_world() -> HittableList {
+    let mut world = HittableList::new();
+
+    let red = Lambertian::new(Color::new(0.65, 0.05, 0.05));
+    let white = Lambertian::new(Color::new(0.73, 0.73, 0.73));
+    let green = Lambertian::new(Color::new(0.12, 0.45, 0.15));
+    let light = DiffuseLight::new(Color::new(15., 15., 15.));
+
+    world.add(Quad::new(
+        Point3::new(555., 0., 0.),
+        Vec3::new(0., 555., 0.),
+        Vec3::new(0., 0., 555.),
+        green.into(),
+    ));
+    world.add(Quad::new(
+        Point3::zero(),
+        Vec3::new(0., 555., 0.),
+        Vec3::new(0., 0., 555.),
+        red.into(),
+    ));
+    world.add(Quad::new(
+        Point3::new(343., 554., 332.),
+        Vec3::new(-130., 0., 0.),
+        Vec3::new(0., 0., -105.),
+        light.into(),
+    ));
+    world.add(Quad::new(
+        Point3::zero(),
+        Vec3::new(555., 0., 0.),
+        Vec3::new(0., 0., 555.),
+        white.clone().into(),
+    ));
+    world.add(Quad::new(
+        Point3::new(555., 555., 555.),
+        Vec3::new(-555., 0., 0.),
+        Vec3::new(0., 0., -555.),
+        white.clone().into(),
+    ));
+    world.add(Quad::new(
+        Point3::new(0., 0., 555.),
+        Vec3::new(555., 0., 0.),
+        Vec3::new(0., 555., 0.),
+        white.clone().into(),
+    ));
+
+    // Dark smoke filling the tall box, and bright fog scattering mostly forward in the short one.
+    let tall_box = Cuboid::new(
+        &Point3::zero(),
+        &Point3::new(165., 330., 165.),
+        white.clone().into(),
+    );
+    let transform = Transform::rotation(&Vec3::new(0., 1., 0.), 15.)
+        .then(&Transform::translation(&Vec3::new(265., 0., 295.)));
+    let smoke = Isotropic::new(Color::zero());
+    world.add(ConstantMedium::new(
+        Instance::of(tall_box, transform),
+        0.01,
+        smoke.into(),
+    ));
+
+    let short_box = Cuboid::new(
+        &Point3::zero(),
+        &Point3::new(165., 165., 165.),
+        white.into(),
+    );
+    let transform = Transform::rotation(&Vec3::new(0., 1., 0.), -18.)
+        .then(&Transform::translation(&Vec3::new(130., 0., 65.)));
+    let fog = HenyeyGreenstein::new(Color::one(), 0.6);
+    world.add(ConstantMedium::new(
+        Instance::of(short_box, transform),
+        0.01,
+        fog.into(),
+    ));
+
+    // A glass ball with a dense, milky medium inside, which looks like subsurface scattering.
+    let center = Point3::new(190., 240., 150.);
+    let glass = Dielectric::new(1.5);
+    world.add(Sphere::new(center.clone(), 70., glass.into()));
+    let milk = Isotropic::new(Color::new(0.9, 0.6, 0.4));
+    world.add(ConstantMedium::new(
+        Sphere::new(center, 69.9, Dielectric::new(1.5).into()),
+        0.04,
+        milk.into(),
+    ));
+    world
+}
+
+fn main() {
+    env_logger::init();
+    let world = build_world();
+
+    let camera = Camera::new(
+        1.,
+        600,
+        200,
+        50,
+        40.,
+        Point3::new(278., 278., -800.),
+        Point3::new(278., 278., 0.),
+        Vec3::new(0., 1., 0.),
+        0.,
+        10.,
+    )
+    .with_background(Color::zero());
+    camera.render(&world)
+}
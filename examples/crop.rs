@@ -0,0 +1,60 @@
+use raytracer::camera::Camera;
+use raytracer::color::Color;
+use raytracer::film::{Filter, Region};
+use raytracer::hit::list::HittableList;
+use raytracer::hit::plane::Plane;
+use raytracer::hit::sphere::Sphere;
+use raytracer::materials::{Dielectric, DiffuseLight, Lambertian};
+use raytracer::texture::Checker;
+use raytracer::vec3::{Point3, Vec3};
+
+// A glass ball on a checkerboard under a light, whose caustic stays noisy long after
+// the rest of the image has converged.
+fn build_world() -> HittableList {
+    let mut world = HittableList::new();
+
+    let checker = Checker::new(Color::new(0.2, 0.3, 0.1), Color::new(0.9, 0.9, 0.9), 0.5);
+    world.add(Plane::new(
+        Point3::zero(),
+        Vec3::new(0., 1., 0.),
+        Lambertian::new(checker).into(),
+    ));
+    world.add(Sphere::new(
+        Point3::new(0., 1., -1.),
+        1.,
+        Dielectric::new(1.5).into(),
+    ));
+    let light = DiffuseLight::new(Color::new(8., 8., 8.));
+    world.add(Sphere::new(Point3::new(-2., 5., -3.), 1., light.into()));
+    world
+}
+
+fn camera(samples_per_pixel: i32) -> Camera {
+    Camera::new(
+        16. / 9.,
+        400,
+        samples_per_pixel,
+        20,
+        40.,
+        Point3::new(0., 2.5, 6.),
+        Point3::new(0., 0.8, -1.),
+        Vec3::new(0., 1., 0.),
+        0.,
+        7.,
+    )
+    .with_background(Color::new(0.2, 0.25, 0.35))
+}
+
+fn main() -> std::io::Result<()> {
+    env_logger::init();
+    let world = build_world();
+
+    // A quick preview of the whole frame, then the ball and its caustic again with many more
+    // samples, pasted over the preview.
+    let filter = Filter::gaussian();
+    let mut image = camera(16).render_filtered(&world, filter).to_image();
+    let region = Region::new(130, 40, 170, 150);
+    let patch = camera(512).render_region(&world, region, filter).to_image();
+    image.paste(&patch, region.x, region.y);
+    image.save_ppm("crop.ppm")
+}
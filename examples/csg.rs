@@ -0,0 +1,87 @@
+use raytracer::camera::Camera;
+use raytracer::color::Color;
+use raytracer::hit::csg::Csg;
+use raytracer::hit::cuboid::Cuboid;
+use raytracer::hit::instance::Instance;
+use raytracer::hit::list::HittableList;
+use raytracer::hit::plane::Plane;
+use raytracer::hit::sphere::Sphere;
+use raytracer::materials::{Dielectric, Lambertian, Metal};
+use raytracer::matrix::Transform;
+use raytracer::vec3::{Point3, Vec3};
+
+fn build_world() -> HittableList {
+    let mut world = HittableList::new();
+
+    let ground = Lambertian::new(Color::new(0.5, 0.5, 0.5));
+    world.add(Plane::new(
+        Point3::zero(),
+        Vec3::new(0., 1., 0.),
+        ground.into(),
+    ));
+
+    // A biconvex lens: the overlap of two large glass spheres.
+    let glass = Dielectric::new(1.5);
+    let lens = Csg::intersection(
+        Sphere::new(Point3::new(-2., 1.2, -1.8), 2., glass.clone().into()),
+        Sphere::new(Point3::new(-2., 1.2, 1.8), 2., glass.into()),
+    );
+    world.add(lens);
+
+    // A rounded die: a cube intersected with a sphere, with a dimple carved out of its top.
+    let ivory = Lambertian::new(Color::new(0.9, 0.85, 0.7));
+    let red = Lambertian::new(Color::new(0.7, 0.1, 0.1));
+    let rounded = Csg::intersection(
+        Cuboid::new(
+            &Point3::new(-0.8, 0., -0.8),
+            &Point3::new(0.8, 1.6, 0.8),
+            ivory.clone().into(),
+        ),
+        Sphere::new(Point3::new(0., 0.8, 0.), 1.1, ivory.into()),
+    );
+    let die = Csg::difference(
+        rounded,
+        Sphere::new(Point3::new(0., 1.75, 0.), 0.3, red.into()),
+    );
+    let transform = Transform::rotation(&Vec3::new(0., 1., 0.), 30.);
+    world.add(Instance::of(die, transform));
+
+    // A machined part: a metal block with a spherical pocket and a rotated slot.
+    let steel = Metal::new(Color::new(0.8, 0.8, 0.85), 0.15);
+    let block = Cuboid::new(
+        &Point3::new(1.4, 0., -0.8),
+        &Point3::new(3.4, 1., 0.8),
+        steel.clone().into(),
+    );
+    let pocket = Sphere::new(Point3::new(2.4, 1., 0.), 0.6, steel.clone().into());
+    let slot = Instance::of(
+        Cuboid::new(
+            &Point3::new(-0.15, -1., -1.),
+            &Point3::new(0.15, 1., 1.),
+            steel.into(),
+        ),
+        Transform::rotation(&Vec3::new(0., 1., 0.), 45.)
+            .then(&Transform::translation(&Vec3::new(2.4, 1., 0.))),
+    );
+    world.add(Csg::difference(Csg::difference(block, pocket), slot));
+    world
+}
+
+fn main() {
+    env_logger::init();
+    let world = build_world();
+
+    let camera = Camera::new(
+        16. / 9.,
+        600,
+        100,
+        50,
+        35.,
+        Point3::new(0., 4., 8.),
+        Point3::new(0., 0.6, 0.),
+        Vec3::new(0., 1., 0.),
+        0.,
+        10.,
+    );
+    camera.render(&world)
+}
@@ -0,0 +1,57 @@
+use raytracer::camera::Camera;
+use raytracer::color::Color;
+use raytracer::film::Filter;
+use raytracer::hit::list::HittableList;
+use raytracer::hit::plane::Plane;
+use raytracer::hit::sphere::Sphere;
+use raytracer::materials::{DiffuseLight, Lambertian};
+use raytracer::texture::Checker;
+use raytracer::vec3::{Point3, Vec3};
+
+// A checkerboard running off to the horizon, where its squares get smaller than the pixels,
+// and a bright light whose edge shows the ringing of the sharper filters.
+fn build_world() -> HittableList {
+    let mut world = HittableList::new();
+
+    let checker = Checker::new(Color::new(0.05, 0.05, 0.05), Color::new(0.9, 0.9, 0.9), 0.5);
+    world.add(Plane::new(
+        Point3::zero(),
+        Vec3::new(0., 1., 0.),
+        Lambertian::new(checker).into(),
+    ));
+    let light = DiffuseLight::new(Color::new(8., 8., 8.));
+    world.add(Sphere::new(Point3::new(0., 1.5, -6.), 1., light.into()));
+    world
+}
+
+fn main() -> std::io::Result<()> {
+    env_logger::init();
+    let world = build_world();
+
+    let camera = Camera::new(
+        16. / 9.,
+        320,
+        16,
+        10,
+        50.,
+        Point3::new(0., 1., 2.),
+        Point3::new(0., 0.8, -6.),
+        Vec3::new(0., 1., 0.),
+        0.,
+        8.,
+    );
+    for (filter, path) in [
+        (Filter::default(), "filter_box.ppm"),
+        (Filter::tent(), "filter_tent.ppm"),
+        (Filter::gaussian(), "filter_gaussian.ppm"),
+        (Filter::mitchell(), "filter_mitchell.ppm"),
+        (Filter::lanczos(), "filter_lanczos.ppm"),
+    ] {
+        log::info!("Rendering with {:?}", filter);
+        camera
+            .render_filtered(&world, filter)
+            .to_image()
+            .save_ppm(path)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use raytracer::aabb::Aabb;
+use raytracer::camera::Camera;
+use raytracer::color::Color;
+use raytracer::hit::list::HittableList;
+use raytracer::hit::plane::Plane;
+use raytracer::hit::volume::VoxelVolume;
+use raytracer::materials::{HenyeyGreenstein, Isotropic, Lambertian};
+use raytracer::vec3::{Point3, Vec3};
+use raytracer::voxel::VoxelGrid;
+use std::env;
+
+const SIZE: usize = 64;
+
+/// A rising plume, widening and cooling with height, with some swirls to break it up.
+fn plume() -> VoxelGrid {
+    let mut density = Vec::with_capacity(SIZE * SIZE * SIZE);
+    let mut temperature = Vec::with_capacity(SIZE * SIZE * SIZE);
+    for k in 0..SIZE {
+        for j in 0..SIZE {
+            for i in 0..SIZE {
+                let [x, y, z] = [i, j, k].map(|n| (n as f64 + 0.5) / SIZE as f64);
+                let sway = 0.08 * (y * 9.).sin();
+                let dx = x - 0.5 - sway;
+                let dz = z - 0.5;
+                let radius = 0.15 + 0.3 * y;
+                let swirl = 0.75 + 0.25 * (x * 23. + y * 17.).sin() * (z * 19. - y * 11.).cos();
+                let core = (1. - (dx * dx + dz * dz).sqrt() / radius).max(0.);
+                density.push((core * swirl * (1. - y)) as f32);
+                temperature.push((2600. * core * (-2.5 * y).exp()) as f32);
+            }
+        }
+    }
+    VoxelGrid::new([SIZE, SIZE, SIZE], density).with_temperature(temperature)
+}
+
+fn build_world(grid: VoxelGrid) -> HittableList {
+    let mut world = HittableList::new();
+
+    let ground = Lambertian::new(Color::new(0.3, 0.3, 0.3));
+    world.add(Plane::new(
+        Point3::zero(),
+        Vec3::new(0., 1., 0.),
+        ground.into(),
+    ));
+
+    let embers = Isotropic::new(Color::new(0.3, 0.3, 0.3));
+    let fire = VoxelVolume::new(
+        grid.clone(),
+        Aabb::new(&Point3::new(-1., 0., -1.), &Point3::new(1., 2.5, 1.)),
+        6.,
+        embers.into(),
+    )
+    .with_emission(0.4);
+    world.add(fire);
+
+    // The same grid without its temperature, as a cold smoke lit by the fire.
+    let smoke = HenyeyGreenstein::new(Color::new(0.8, 0.8, 0.8), 0.3);
+    world.add(VoxelVolume::new(
+        grid,
+        Aabb::new(&Point3::new(1.2, 0., -1.5), &Point3::new(3.2, 2.5, 0.5)),
+        12.,
+        smoke.into(),
+    ));
+    world
+}
+
+fn main() {
+    env_logger::init();
+    // A grid can be loaded from a file, otherwise a procedural plume is used.
+    let grid = match env::args().nth(1) {
+        Some(path) => VoxelGrid::load(&path).unwrap_or_else(|error| {
+            eprintln!("Failed to load {}: {}", path, error);
+            std::process::exit(1)
+        }),
+        None => plume(),
+    };
+    let world = build_world(grid);
+
+    let camera = Camera::new(
+        16. / 9.,
+        600,
+        200,
+        50,
+        40.,
+        Point3::new(0.5, 1.8, 6.),
+        Point3::new(0.8, 1.1, 0.),
+        Vec3::new(0., 1., 0.),
+        0.,
+        10.,
+    )
+    .with_background(Color::new(0.08, 0.09, 0.12));
+    camera.render(&world)
+}
@@ -0,0 +1,88 @@
+use raytracer::camera::Camera;
+use raytracer::color::Color;
+use raytracer::hit::instance::Instance;
+use raytracer::hit::list::HittableList;
+use raytracer::hit::plane::Plane;
+use raytracer::hit::sdf::SdfObject;
+use raytracer::materials::Lambertian;
+use raytracer::matrix::{Quaternion, Transform};
+use raytracer::sdf::fractals::{Mandelbulb, MengerSponge, QuaternionJulia};
+use raytracer::texture::{Checker, ColorRamp};
+use raytracer::vec3::{Point3, Vec3};
+
+fn place(scale: f64, position: Vec3) -> Transform {
+    Transform::scaling(&Vec3::new(scale, scale, scale)).then(&Transform::translation(&position))
+}
+
+fn build_world() -> HittableList {
+    let mut world = HittableList::new();
+
+    let ground = Checker::new(Color::new(0.2, 0.2, 0.22), Color::new(0.6, 0.6, 0.6), 0.5);
+    world.add(Plane::new(
+        Point3::zero(),
+        Vec3::new(0., 1., 0.),
+        Lambertian::new(ground).into(),
+    ));
+
+    // Fractals take many small steps near their surface, hence the generous step counts.
+    let fire = ColorRamp::new(vec![
+        Color::new(0.3, 0.02, 0.02),
+        Color::new(0.8, 0.3, 0.05),
+        Color::new(0.95, 0.8, 0.3),
+        Color::new(0.95, 0.95, 0.9),
+    ]);
+    let bulb = SdfObject::new(Mandelbulb::new(8., 10), Lambertian::new(fire).into())
+        .with_max_steps(400)
+        .with_epsilon(5e-4);
+    world.add(Instance::of(bulb, place(1., Vec3::new(0., 1.1, 0.))));
+
+    let levels = ColorRamp::new(vec![
+        Color::new(0.8, 0.8, 0.8),
+        Color::new(0.2, 0.5, 0.8),
+        Color::new(0.1, 0.2, 0.5),
+        Color::new(0.05, 0.05, 0.2),
+    ])
+    .along_v();
+    let sponge = SdfObject::new(MengerSponge::new(3), Lambertian::new(levels).into());
+    world.add(Instance::of(
+        sponge,
+        Transform::rotation(&Vec3::new(0., 1., 0.), 30.)
+            .then(&place(0.8, Vec3::new(-2.5, 0.8, 0.))),
+    ));
+
+    let ocean = ColorRamp::new(vec![
+        Color::new(0.05, 0.3, 0.3),
+        Color::new(0.2, 0.7, 0.6),
+        Color::new(0.9, 0.95, 0.8),
+    ]);
+    let c = Quaternion {
+        w: -0.2,
+        x: 0.6,
+        y: 0.2,
+        z: 0.2,
+    };
+    let julia = SdfObject::new(QuaternionJulia::new(c, 12), Lambertian::new(ocean).into())
+        .with_max_steps(400)
+        .with_epsilon(5e-4);
+    world.add(Instance::of(julia, place(0.9, Vec3::new(2.5, 1.2, 0.))));
+    world
+}
+
+fn main() {
+    env_logger::init();
+    let world = build_world();
+
+    let camera = Camera::new(
+        16. / 9.,
+        600,
+        100,
+        50,
+        40.,
+        Point3::new(0., 3., 7.),
+        Point3::new(0., 1., 0.),
+        Vec3::new(0., 1., 0.),
+        0.,
+        10.,
+    );
+    camera.render(&world)
+}
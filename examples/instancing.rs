@@ -0,0 +1,74 @@
+use raytracer::camera::Camera;
+use raytracer::color::Color;
+use raytracer::hit::bvh::Bvh;
+use raytracer::hit::hittable::Hittable;
+use raytracer::hit::instance::Instance;
+use raytracer::hit::list::HittableList;
+use raytracer::hit::sphere::Sphere;
+use raytracer::materials::{Lambertian, Metal};
+use raytracer::matrix::Transform;
+use raytracer::random::{random, random_range};
+use raytracer::vec3::{Point3, Vec3};
+use std::rc::Rc;
+
+// A small "molecule" of spheres, stored once and placed all over the scene.
+fn build_molecule() -> Bvh {
+    let mut molecule = HittableList::new();
+    let core = Metal::new(Color::new(0.8, 0.8, 0.9), 0.1);
+    molecule.add(Sphere::new(Point3::zero(), 0.5, core.into()));
+    for axis in [
+        Vec3::new(1., 0., 0.),
+        Vec3::new(-1., 0., 0.),
+        Vec3::new(0., 1., 0.),
+        Vec3::new(0., 0., 1.),
+        Vec3::new(0., 0., -1.),
+    ] {
+        let atom = Lambertian::new(Color::new(0.8, 0.2, 0.1));
+        molecule.add(Sphere::new(axis * 0.7, 0.3, atom.into()));
+    }
+    molecule.into_bvh()
+}
+
+fn build_world() -> HittableList {
+    let molecule: Rc<dyn Hittable> = Rc::new(build_molecule());
+
+    let mut instances = HittableList::new();
+    for _ in 0..2000 {
+        let scale = random_range(0.1..0.3);
+        let axis = Vec3::random_range(&(-1.0..1.0));
+        let position = Point3::new(random_range(-12.0..12.0), scale, random_range(-12.0..4.0));
+        let transform = Transform::scaling(&Vec3::new(scale, scale, scale))
+            .then(&Transform::rotation(&axis, 360. * random()))
+            .then(&Transform::translation(&position));
+        instances.add(Instance::new(molecule.clone(), transform));
+    }
+
+    let mut world = HittableList::new();
+    let ground_material = Lambertian::new(Color::new(0.5, 0.5, 0.5));
+    world.add(Sphere::new(
+        Point3::new(0., -1000., 0.),
+        1000.,
+        ground_material.into(),
+    ));
+    world.add(instances.into_bvh());
+    world
+}
+
+fn main() {
+    env_logger::init();
+    let world = build_world();
+
+    let camera = Camera::new(
+        16. / 9.,
+        400,
+        50,
+        20,
+        30.,
+        Point3::new(0., 4., 10.),
+        Point3::new(0., 0., -2.),
+        Vec3::new(0., 1., 0.),
+        0.,
+        10.,
+    );
+    camera.render(&world)
+}
@@ -0,0 +1,74 @@
+use raytracer::camera::Camera;
+use raytracer::color::Color;
+use raytracer::hit::list::HittableList;
+use raytracer::hit::quad::Quad;
+use raytracer::hit::sphere::Sphere;
+use raytracer::lens::{LensError, LensSystem};
+use raytracer::materials::{Lambertian, Metal};
+use raytracer::progressive::ProgressiveSettings;
+use raytracer::texture::Checker;
+use raytracer::vec3::{Point3, Vec3};
+use std::env;
+
+// A checkered wall, whose straight lines show the distortion of the lens, behind spheres at
+// different distances.
+fn build_world() -> HittableList {
+    let mut world = HittableList::new();
+
+    let checker = Checker::new(Color::new(0.1, 0.1, 0.12), Color::new(0.8, 0.8, 0.8), 0.25);
+    world.add(Quad::new(
+        Point3::new(-6., -4., -6.),
+        Vec3::new(12., 0., 0.),
+        Vec3::new(0., 8., 0.),
+        Lambertian::new(checker).into(),
+    ));
+
+    for (k, z) in [-0.5, -2., -4.].into_iter().enumerate() {
+        let x = k as f64 - 1.;
+        let material = if k == 1 {
+            Metal::new(Color::new(0.8, 0.8, 0.85), 0.).into()
+        } else {
+            Lambertian::new(Color::new(0.7, 0.3, 0.2)).into()
+        };
+        world.add(Sphere::new(Point3::new(x, 0., z), 0.4, material));
+    }
+    world
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    env_logger::init();
+    let world = build_world();
+
+    // A 50 mm double Gauss at f/2.8 unless another prescription is given, focused on the
+    // middle sphere 4 m away.
+    let lens = match env::args().nth(1) {
+        Some(path) => LensSystem::load(path)?,
+        None => LensSystem::parse(include_str!("lenses/double_gauss_50mm.txt"))?,
+    };
+    let lens = lens.with_stop_diameter(12.5).focused_at(4.)?;
+    log::info!(
+        "Focal length {:.1} mm, film {:.2} mm behind the lens",
+        lens.focal_length().ok_or(LensError::CannotFocus)?,
+        lens.film_distance()
+    );
+
+    let camera = Camera::new(
+        3. / 2.,
+        600,
+        0,
+        50,
+        40.,
+        Point3::new(0., 0., 2.),
+        Point3::new(0., 0., -2.),
+        Vec3::new(0., 1., 0.),
+        0.,
+        4.,
+    )
+    .with_lens(lens, 36.);
+    let settings = ProgressiveSettings::new(128);
+    camera
+        .render_progressive(&world, &settings)
+        .to_image()
+        .save_ppm("lens.ppm")?;
+    Ok(())
+}
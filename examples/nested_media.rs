@@ -0,0 +1,80 @@
+use raytracer::camera::Camera;
+use raytracer::color::Color;
+use raytracer::hit::boundary::MediumBoundary;
+use raytracer::hit::cuboid::Cuboid;
+use raytracer::hit::list::HittableList;
+use raytracer::hit::plane::Plane;
+use raytracer::hit::sphere::Sphere;
+use raytracer::materials::{Dielectric, HenyeyGreenstein, Isotropic, Lambertian, Metal};
+use raytracer::medium::Medium;
+use raytracer::vec3::{Point3, Vec3};
+use std::rc::Rc;
+
+fn build_world(haze: Rc<Medium>) -> HittableList {
+    let mut world = HittableList::new();
+
+    let ground = Lambertian::new(Color::new(0.5, 0.5, 0.5));
+    world.add(Plane::new(
+        Point3::zero(),
+        Vec3::new(0., 1., 0.),
+        ground.into(),
+    ));
+
+    // A glass ball of red liquid, with a white ball floating inside it.
+    let liquid = Rc::new(Medium::absorbing(Color::new(0.2, 1.5, 1.5)));
+    world.add(MediumBoundary::new(
+        Sphere::new(Point3::new(-1.3, 1., 0.), 1., Dielectric::new(1.33).into()),
+        liquid,
+    ));
+    let white = Lambertian::new(Color::new(0.9, 0.9, 0.9));
+    world.add(Sphere::new(Point3::new(-1.3, 0.8, 0.3), 0.35, white.into()));
+
+    // A bank of fog without a visible surface, around a metal ball.
+    let fog = Rc::new(Medium::new(
+        Color::zero(),
+        0.5,
+        HenyeyGreenstein::new(Color::new(0.9, 0.9, 0.9), 0.5).into(),
+    ));
+    world.add(MediumBoundary::interface(
+        Cuboid::new(
+            &Point3::new(0.4, 0., -1.2),
+            &Point3::new(2.8, 1.4, 1.2),
+            Isotropic::new(Color::one()).into(),
+        ),
+        fog,
+    ));
+    let steel = Metal::new(Color::new(0.8, 0.8, 0.85), 0.05);
+    world.add(Sphere::new(Point3::new(1.6, 0.6, 0.), 0.6, steel.into()));
+
+    // A light haze around the whole scene, the camera included.
+    world.add(MediumBoundary::interface(
+        Sphere::new(Point3::zero(), 50., Isotropic::new(Color::one()).into()),
+        haze,
+    ));
+    world
+}
+
+fn main() {
+    env_logger::init();
+    let haze = Rc::new(Medium::new(
+        Color::new(0.002, 0.002, 0.001),
+        0.006,
+        Isotropic::new(Color::new(0.8, 0.85, 1.)).into(),
+    ));
+    let world = build_world(haze.clone());
+
+    let camera = Camera::new(
+        16. / 9.,
+        600,
+        200,
+        50,
+        35.,
+        Point3::new(0., 2.5, 7.),
+        Point3::new(0., 0.7, 0.),
+        Vec3::new(0., 1., 0.),
+        0.,
+        10.,
+    )
+    .with_medium(haze);
+    camera.render(&world)
+}
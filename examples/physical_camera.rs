@@ -0,0 +1,65 @@
+use raytracer::camera_settings::{CameraError, CameraSettings};
+use raytracer::color::Color;
+use raytracer::hit::list::HittableList;
+use raytracer::hit::sphere::Sphere;
+use raytracer::materials::{Lambertian, Metal};
+use raytracer::physical::PhysicalCamera;
+use raytracer::progressive::ProgressiveSettings;
+use raytracer::vec3::{Point3, Vec3};
+
+// An overcast sky about 10 exposure values above the reference of the physical camera, where
+// scene radiance renders unchanged.
+const DAYLIGHT: f64 = 1000.;
+
+// A row of spheres in meters, 1.5 to 13 m away from the camera, on a large ground sphere.
+fn build_world() -> HittableList {
+    let mut world = HittableList::new();
+
+    let ground = Lambertian::new(Color::new(0.5, 0.55, 0.4));
+    world.add(Sphere::new(
+        Point3::new(0., -1000., 0.),
+        1000.,
+        ground.into(),
+    ));
+
+    for (k, z) in [-1.5, -2.5, -4., -6., -9., -13.].into_iter().enumerate() {
+        let x = 0.6 - k as f64 * 0.3;
+        let material = if k % 2 == 1 {
+            Metal::new(Color::new(0.8, 0.8, 0.85), 0.05).into()
+        } else {
+            Lambertian::new(Color::new(0.7, 0.3, 0.2)).into()
+        };
+        world.add(Sphere::new(Point3::new(x, 0.3, z), 0.3, material));
+    }
+    world
+}
+
+fn render(world: &HittableList, physical: &PhysicalCamera, path: &str) -> Result<(), CameraError> {
+    let settings = CameraSettings::new()
+        .with_image_size(480, 3. / 2.)
+        .with_view(
+            Point3::new(0., 0.8, 0.),
+            Point3::new(0., 0.3, -4.),
+            Vec3::new(0., 1., 0.),
+        )
+        .with_background(Color::new(0.6, 0.75, 1.) * DAYLIGHT);
+    // Focused on the third sphere.
+    let camera = physical.settings(&settings, 4.).build()?;
+    camera
+        .render_progressive(world, &ProgressiveSettings::new(128))
+        .to_image()
+        .save_ppm(path)?;
+    Ok(())
+}
+
+fn main() -> Result<(), CameraError> {
+    env_logger::init();
+    let world = build_world();
+
+    // Both exposures let the same light in: f/1.8 at 1/500 s keeps only the sphere 4 m away
+    // sharp, f/8 at 1/25 s gets most of the row in focus.
+    let wide_open = PhysicalCamera::new(50., 1.8).with_exposure(1. / 500., 100.);
+    render(&world, &wide_open, "physical_f1.8.ppm")?;
+    let stopped_down = PhysicalCamera::new(50., 8.).with_exposure(1. / 25., 100.);
+    render(&world, &stopped_down, "physical_f8.ppm")
+}
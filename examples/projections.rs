@@ -0,0 +1,125 @@
+use raytracer::camera::Camera;
+use raytracer::color::Color;
+use raytracer::hit::cuboid::Cuboid;
+use raytracer::hit::list::HittableList;
+use raytracer::hit::plane::Plane;
+use raytracer::hit::sphere::Sphere;
+use raytracer::materials::{Lambertian, Metal};
+use raytracer::progressive::ProgressiveSettings;
+use raytracer::projection::{CubeFace, FisheyeMapping, Projection};
+use raytracer::vec3::{Point3, Vec3};
+use std::f64::consts::PI;
+
+// A ring of colored spheres around the origin, with a tower of boxes in front.
+fn build_world() -> HittableList {
+    let mut world = HittableList::new();
+
+    let ground = Lambertian::new(Color::new(0.5, 0.5, 0.5));
+    world.add(Plane::new(
+        Point3::zero(),
+        Vec3::new(0., 1., 0.),
+        ground.into(),
+    ));
+
+    let count = 12;
+    for k in 0..count {
+        let angle = 2. * PI * k as f64 / count as f64;
+        let center = Point3::new(4. * angle.sin(), 0.6, -4. * angle.cos());
+        let hue = k as f64 / count as f64;
+        let albedo = Color::new(
+            0.5 + 0.4 * (2. * PI * hue).cos(),
+            0.5 + 0.4 * (2. * PI * (hue + 1. / 3.)).cos(),
+            0.5 + 0.4 * (2. * PI * (hue + 2. / 3.)).cos(),
+        );
+        world.add(Sphere::new(center, 0.6, Lambertian::new(albedo).into()));
+    }
+
+    let steel = Metal::new(Color::new(0.8, 0.8, 0.85), 0.1);
+    for level in 0..3 {
+        let size = 1. - 0.25 * level as f64;
+        let bottom = level as f64;
+        world.add(Cuboid::new(
+            &Point3::new(-size / 2., bottom, -2. - size / 2.),
+            &Point3::new(size / 2., bottom + 1., -2. + size / 2.),
+            steel.clone().into(),
+        ));
+    }
+    world
+}
+
+fn render(
+    world: &HittableList,
+    aspect_ratio: f64,
+    look_from: Point3,
+    look_at: Point3,
+    projection: Projection,
+    path: &str,
+) -> std::io::Result<()> {
+    let camera = Camera::new(
+        aspect_ratio,
+        400,
+        0,
+        50,
+        90.,
+        look_from,
+        look_at,
+        Vec3::new(0., 1., 0.),
+        0.,
+        1.,
+    )
+    .with_projection(projection);
+    let settings = ProgressiveSettings::new(64);
+    camera
+        .render_progressive(world, &settings)
+        .to_image()
+        .save_ppm(path)
+}
+
+fn main() -> std::io::Result<()> {
+    env_logger::init();
+    let world = build_world();
+    let eye = Point3::new(0., 1., 0.);
+    let ahead = Point3::new(0., 1., -1.);
+
+    render(
+        &world,
+        2.,
+        eye.clone(),
+        ahead.clone(),
+        Projection::Equirectangular,
+        "equirectangular.ppm",
+    )?;
+    render(
+        &world,
+        1.,
+        eye.clone(),
+        ahead.clone(),
+        Projection::Fisheye {
+            fov: 180.,
+            mapping: FisheyeMapping::Equisolid,
+        },
+        "fisheye.ppm",
+    )?;
+    render(
+        &world,
+        1.,
+        Point3::new(0., 1.5, 10.),
+        Point3::new(0., 1.5, 0.),
+        Projection::Orthographic { height: 4. },
+        "elevation.ppm",
+    )?;
+    for (face, name) in CubeFace::ALL
+        .into_iter()
+        .zip(["front", "back", "left", "right", "up", "down"])
+    {
+        render(
+            &world,
+            1.,
+            eye.clone(),
+            ahead.clone(),
+            Projection::CubemapFace(face),
+            &format!("cube_{}.ppm", name),
+        )?;
+    }
+    Ok(())
+}
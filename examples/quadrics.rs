@@ -0,0 +1,73 @@
+use raytracer::camera::Camera;
+use raytracer::color::Color;
+use raytracer::hit::instance::Instance;
+use raytracer::hit::list::HittableList;
+use raytracer::hit::plane::Plane;
+use raytracer::hit::quadric::Quadric;
+use raytracer::hit::torus::Torus;
+use raytracer::materials::{Dielectric, Lambertian, Metal};
+use raytracer::matrix::Transform;
+use raytracer::vec3::{Point3, Vec3};
+
+fn at(x: f64, y: f64, z: f64) -> Transform {
+    Transform::translation(&Vec3::new(x, y, z))
+}
+
+fn build_world() -> HittableList {
+    let mut world = HittableList::new();
+
+    let ground = Lambertian::new(Color::new(0.5, 0.5, 0.5));
+    world.add(Plane::new(
+        Point3::zero(),
+        Vec3::new(0., 1., 0.),
+        ground.into(),
+    ));
+
+    let red = Lambertian::new(Color::new(0.7, 0.15, 0.1));
+    world.add(Instance::of(
+        Quadric::cylinder(0.6, 1.5, true, red.into()),
+        at(-3., 0., 0.),
+    ));
+
+    let gold = Metal::new(Color::new(0.8, 0.6, 0.2), 0.05);
+    world.add(Instance::of(
+        Quadric::cone(0.7, 1.6, true, gold.into()),
+        at(-1.5, 0., -1.),
+    ));
+
+    let glass = Dielectric::new(1.5);
+    world.add(Instance::of(
+        Quadric::paraboloid(0.7, 1.4, true, glass.into()),
+        at(0., 0., 0.),
+    ));
+
+    let blue = Lambertian::new(Color::new(0.1, 0.3, 0.7));
+    world.add(Instance::of(
+        Quadric::hyperboloid(0.3, 0.7, 1.8, false, blue.into()),
+        at(1.5, 0.9, -1.),
+    ));
+
+    let steel = Metal::new(Color::new(0.8, 0.8, 0.85), 0.2);
+    let transform = Transform::rotation(&Vec3::new(1., 0., 0.), 60.).then(&at(3., 0.9, 0.));
+    world.add(Instance::of(Torus::new(0.6, 0.25, steel.into()), transform));
+    world
+}
+
+fn main() {
+    env_logger::init();
+    let world = build_world();
+
+    let camera = Camera::new(
+        16. / 9.,
+        600,
+        100,
+        50,
+        35.,
+        Point3::new(0., 3., 8.),
+        Point3::new(0., 0.6, -0.5),
+        Vec3::new(0., 1., 0.),
+        0.,
+        10.,
+    );
+    camera.render(&world)
+}
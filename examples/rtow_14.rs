@@ -1,6 +1,7 @@
-use raytracer::camera::Camera;
+use raytracer::camera_settings::CameraSettings;
 use raytracer::color::Color;
 use raytracer::hit::list::HittableList;
+use raytracer::hit::plane::Plane;
 use raytracer::hit::sphere::Sphere;
 use raytracer::materials::{Dielectric, Lambertian, Metal};
 use raytracer::random::random;
@@ -10,9 +11,9 @@ fn build_world() -> HittableList {
     let mut world = HittableList::new();
 
     let ground_material = Lambertian::new(Color::new(0.5, 0.5, 0.5));
-    world.add(Sphere::new(
-        Point3::new(0., -1000., 0.),
-        1000.0,
+    world.add(Plane::new(
+        Point3::zero(),
+        Vec3::new(0., 1., 0.),
         ground_material.into(),
     ));
 
@@ -57,29 +58,17 @@ fn main() {
     let world = build_world();
 
     // Render
-    let aspect_ratio = 16. / 9.;
-    let image_width = 1200;
-    let samples_per_pixel = 500;
-    let max_depth = 50;
-
-    let vfov = 20.;
-    let look_from = Point3::new(13., 2., 3.);
-    let look_at = Point3::zero();
-    let vup = Vec3::new(0., 1., 0.);
-
-    let defocus_angle = 0.6;
-    let focus_distance = 10.;
-    let camera = Camera::new(
-        aspect_ratio,
-        image_width,
-        samples_per_pixel,
-        max_depth,
-        vfov,
-        look_from,
-        look_at,
-        vup,
-        defocus_angle,
-        focus_distance,
-    );
+    let camera = CameraSettings::new()
+        .with_image_size(1200, 16. / 9.)
+        .with_samples(500, 50)
+        .with_vfov(20.)
+        .with_view(
+            Point3::new(13., 2., 3.),
+            Point3::zero(),
+            Vec3::new(0., 1., 0.),
+        )
+        .with_defocus(0.6, 10.)
+        .build()
+        .expect("valid camera settings");
     camera.render(&world)
 }
@@ -0,0 +1,94 @@
+use raytracer::aabb::Aabb;
+use raytracer::camera::Camera;
+use raytracer::color::Color;
+use raytracer::hit::list::HittableList;
+use raytracer::hit::plane::Plane;
+use raytracer::hit::sdf::SdfObject;
+use raytracer::hit::sphere::Sphere;
+use raytracer::materials::{Dielectric, Lambertian, Metal};
+use raytracer::sdf::shapes::{Capsule, Cuboid, Cylinder, Sphere as SdfSphere, Torus};
+use raytracer::sdf::{Sdf, SdfFn};
+use raytracer::vec3::{Point3, Vec3};
+
+fn build_world() -> HittableList {
+    let mut world = HittableList::new();
+
+    let ground = Lambertian::new(Color::new(0.5, 0.5, 0.5));
+    world.add(Plane::new(
+        Point3::zero(),
+        Vec3::new(0., 1., 0.),
+        ground.into(),
+    ));
+
+    // A twisted column with rounded edges.
+    let copper = Metal::new(Color::new(0.8, 0.5, 0.3), 0.2);
+    let column = Cuboid::new(Point3::new(0., 1., 0.), Vec3::new(0.35, 1., 0.35))
+        .round(0.05)
+        .twist(60.)
+        .translate(Vec3::new(-2.2, 0., 0.));
+    world.add(SdfObject::new(column, copper.into()).with_step_scale(0.6));
+
+    // A blob of three spheres and a capsule melted together.
+    let clay = Lambertian::new(Color::new(0.7, 0.25, 0.2));
+    let blob = SdfSphere::new(Point3::new(-0.4, 0.6, 0.), 0.55)
+        .smooth_union(SdfSphere::new(Point3::new(0.3, 0.5, 0.2), 0.45), 0.4)
+        .smooth_union(SdfSphere::new(Point3::new(0., 1.1, -0.2), 0.35), 0.4)
+        .smooth_union(
+            Capsule::new(Point3::new(-0.6, 0.2, 0.5), Point3::new(0.6, 0.2, 0.5), 0.2),
+            0.3,
+        );
+    world.add(SdfObject::new(blob, clay.into()).with_step_scale(0.8));
+
+    // A ring with a cylindrical notch cut out of it.
+    let gold = Metal::new(Color::new(0.9, 0.75, 0.35), 0.05);
+    let ring = Torus::new(Point3::new(0., 0., 0.), 0.6, 0.18)
+        .smooth_subtract(Cylinder::new(Point3::new(0.6, 0., 0.), 0.25, 0.5), 0.05)
+        .translate(Vec3::new(2.2, 0.18, 0.3));
+    world.add(SdfObject::new(ring, gold.into()));
+
+    // A row of small spheres repeated along X, behind everything else.
+    let blue = Lambertian::new(Color::new(0.2, 0.3, 0.7));
+    let row = SdfSphere::new(Point3::zero(), 0.2)
+        .repeat_limited(Vec3::new(0.6, 0., 0.), [6, 0, 0])
+        .translate(Vec3::new(0., 0.2, -2.));
+    world.add(SdfObject::new(row, blue.into()));
+
+    // A custom distance function: a gyroid clipped to a sphere. The gyroid's distance is only a
+    // rough estimate, hence the small steps.
+    let gyroid = SdfFn::new(
+        Aabb::new(&Point3::new(-1., -1., -1.), &Point3::new(1., 1., 1.)),
+        |p: &Point3| {
+            let q = p * 6.;
+            let field = q.x.sin() * q.y.cos() + q.y.sin() * q.z.cos() + q.z.sin() * q.x.cos();
+            field.abs() / 8. - 0.03
+        },
+    )
+    .intersection(SdfSphere::new(Point3::zero(), 0.7))
+    .translate(Vec3::new(1.2, 0.7, -1.2));
+    let green = Lambertian::new(Color::new(0.3, 0.6, 0.3));
+    world.add(SdfObject::new(gyroid, green.into()).with_step_scale(0.5));
+
+    // Analytic objects mix freely with distance fields.
+    let glass = Dielectric::new(1.5);
+    world.add(Sphere::new(Point3::new(-1., 0.35, 1.2), 0.35, glass.into()));
+    world
+}
+
+fn main() {
+    env_logger::init();
+    let world = build_world();
+
+    let camera = Camera::new(
+        16. / 9.,
+        600,
+        100,
+        50,
+        35.,
+        Point3::new(0., 3., 7.),
+        Point3::new(0., 0.6, 0.),
+        Vec3::new(0., 1., 0.),
+        0.,
+        10.,
+    );
+    camera.render(&world)
+}
@@ -0,0 +1,83 @@
+use raytracer::camera::Camera;
+use raytracer::color::Color;
+use raytracer::hit::list::HittableList;
+use raytracer::hit::plane::Plane;
+use raytracer::hit::sphere::Sphere;
+use raytracer::materials::{Dielectric, Lambertian, Metal};
+use raytracer::progressive::ProgressiveSettings;
+use raytracer::projection::Projection;
+use raytracer::stereo::{Stereo, StereoLayout};
+use raytracer::vec3::{Point3, Vec3};
+use std::f64::consts::PI;
+
+// Spheres at increasing distances all around the viewer, so that the depth is easy to judge.
+fn build_world() -> HittableList {
+    let mut world = HittableList::new();
+
+    let ground = Lambertian::new(Color::new(0.5, 0.5, 0.5));
+    world.add(Plane::new(
+        Point3::zero(),
+        Vec3::new(0., 1., 0.),
+        ground.into(),
+    ));
+
+    let count = 16;
+    for k in 0..count {
+        let angle = 2. * PI * k as f64 / count as f64;
+        let distance = 2. + 0.5 * k as f64;
+        let center = Point3::new(distance * angle.sin(), 0.5, -distance * angle.cos());
+        let material = match k % 3 {
+            0 => Lambertian::new(Color::new(0.7, 0.2, 0.2)).into(),
+            1 => Metal::new(Color::new(0.8, 0.8, 0.8), 0.05).into(),
+            _ => Dielectric::new(1.5).into(),
+        };
+        world.add(Sphere::new(center, 0.5, material));
+    }
+    world
+}
+
+fn main() -> std::io::Result<()> {
+    env_logger::init();
+    let world = build_world();
+    let settings = ProgressiveSettings::new(64);
+    let look_from = Point3::new(0., 1.2, 0.);
+    let look_at = Point3::new(0., 0.8, -1.);
+
+    // A side by side pair converging on the nearest sphere.
+    let camera = Camera::new(
+        4. / 3.,
+        400,
+        0,
+        50,
+        60.,
+        look_from.clone(),
+        look_at,
+        Vec3::new(0., 1., 0.),
+        0.,
+        2.,
+    );
+    let stereo = Stereo::new(0.064).with_convergence(2.);
+    camera
+        .render_stereo(&world, &settings, &stereo)
+        .save_ppm("stereo.ppm")?;
+
+    // An omnidirectional stereo panorama for VR headsets, left eye on top, looking level so
+    // that the horizon is straight.
+    let camera = Camera::new(
+        2.,
+        800,
+        0,
+        50,
+        90.,
+        look_from,
+        Point3::new(0., 1.2, -1.),
+        Vec3::new(0., 1., 0.),
+        0.,
+        1.,
+    )
+    .with_projection(Projection::Equirectangular);
+    let stereo = Stereo::new(0.064).with_layout(StereoLayout::OverUnder);
+    camera
+        .render_stereo(&world, &settings, &stereo)
+        .save_ppm("stereo_360.ppm")
+}
@@ -0,0 +1,112 @@
+use raytracer::animation::{CameraTracks, FrameSequence, Track, TransformTracks};
+use raytracer::camera_settings::{CameraError, CameraSettings};
+use raytracer::color::Color;
+use raytracer::film::Filter;
+use raytracer::hit::cuboid::Cuboid;
+use raytracer::hit::instance::Instance;
+use raytracer::hit::list::HittableList;
+use raytracer::hit::plane::Plane;
+use raytracer::hit::sphere::Sphere;
+use raytracer::hit::torus::Torus;
+use raytracer::materials::{DiffuseLight, Lambertian, Metal};
+use raytracer::matrix::Quaternion;
+use raytracer::vec3::{Point3, Vec3};
+use raytracer::video::{GifPalette, GifWriter, Y4mWriter};
+use std::env;
+use std::fs::File;
+use std::io::{self, BufWriter};
+
+const FRAMES: u32 = 48;
+
+// A torus spinning on a pedestal while the camera zooms in, and the pedestal changes color.
+fn build_world(frame: f64, product: &TransformTracks, pedestal: &Track<Color>) -> HittableList {
+    let mut world = HittableList::new();
+
+    let ground = Lambertian::new(Color::new(0.4, 0.4, 0.45));
+    world.add(Plane::new(
+        Point3::zero(),
+        Vec3::new(0., 1., 0.),
+        ground.into(),
+    ));
+    world.add(Cuboid::new(
+        &Point3::new(-1., 0., -1.),
+        &Point3::new(1., 0.5, 1.),
+        Lambertian::new(pedestal.value_at(frame)).into(),
+    ));
+    let gold = Metal::new(Color::new(0.9, 0.7, 0.3), 0.15);
+    world.add(Instance::of(
+        Torus::new(0.8, 0.3, gold.into()),
+        product.transform_at(frame),
+    ));
+    let light = DiffuseLight::new(Color::new(6., 6., 6.));
+    world.add(Sphere::new(Point3::new(3., 6., 4.), 1.5, light.into()));
+    world
+}
+
+fn main() -> Result<(), CameraError> {
+    env_logger::init();
+    // An optional output, `gif` or `y4m` instead of numbered images, and an optional range of
+    // frames to render, such as `10 20` to render frames 10 to 20 again.
+    let args: Vec<String> = env::args().skip(1).collect();
+    let output = args.first().cloned().unwrap_or_default();
+    let numbers: Vec<u32> = args.iter().filter_map(|arg| arg.parse().ok()).collect();
+    let frames = match numbers[..] {
+        [first, last] => first..=last,
+        _ => 0..=FRAMES - 1,
+    };
+
+    let last = FRAMES as f64;
+    let settings = CameraSettings::new()
+        .with_image_size(320, 16. / 9.)
+        .with_samples(16, 10)
+        .with_view(
+            Point3::new(0., 3., 6.),
+            Point3::new(0., 1.2, 0.),
+            Vec3::new(0., 1., 0.),
+        )
+        .with_background(Color::new(0.6, 0.7, 0.9));
+    let camera = CameraTracks::from_settings(&settings).with_vfov(Track::catmull_rom(vec![
+        (0., 40.),
+        (last / 2., 25.),
+        (last, 40.),
+    ])?);
+
+    // One full turn of the tilted torus, rising and coming back down.
+    let tilt = Quaternion::from_axis_angle(&Vec3::new(1., 0., 0.), 60.);
+    let y = Vec3::new(0., 1., 0.);
+    let spin = (0..=4)
+        .map(|quarter| {
+            let turn = Quaternion::from_axis_angle(&y, 90. * quarter as f64);
+            (last * quarter as f64 / 4., &turn * &tilt)
+        })
+        .collect();
+    let product = TransformTracks::new()
+        .with_rotation(Track::linear(spin)?)
+        .with_translation(Track::catmull_rom(vec![
+            (0., Vec3::new(0., 0.8, 0.)),
+            (last / 2., Vec3::new(0., 1.6, 0.)),
+            (last, Vec3::new(0., 0.8, 0.)),
+        ])?);
+    let pedestal = Track::linear(vec![
+        (0., Color::new(0.7, 0.1, 0.1)),
+        (last, Color::new(0.1, 0.2, 0.7)),
+    ])?;
+
+    let sequence =
+        FrameSequence::new(frames, "turntable/frame_####.ppm").with_filter(Filter::gaussian());
+    let scene = |frame| {
+        let frame = frame as f64;
+        let camera = camera.settings_at(&settings, frame).build()?;
+        Ok((camera, build_world(frame, &product, &pedestal)))
+    };
+    match output.as_str() {
+        "gif" => {
+            // The colors hardly change as the torus turns, so one palette avoids shimmering.
+            let file = BufWriter::new(File::create("turntable.gif")?);
+            let mut gif = GifWriter::new(file, 24).with_palette(GifPalette::FirstFrame);
+            sequence.render_video(scene, &mut gif)
+        }
+        "y4m" => sequence.render_video(scene, &mut Y4mWriter::new(io::stdout().lock(), 24)),
+        _ => sequence.render(scene),
+    }
+}
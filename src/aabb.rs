@@ -0,0 +1,162 @@
+use crate::matrix::Transform;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+use std::ops::Range;
+
+/// Axis-aligned bounding box.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Point3,
+    pub max: Point3,
+}
+
+impl Aabb {
+    /// The box spanned by two opposite corners, given in any order.
+    pub fn new(a: &Point3, b: &Point3) -> Self {
+        Self {
+            min: Point3::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z)),
+            max: Point3::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z)),
+        }
+    }
+
+    /// A box containing nothing, the identity of `union`.
+    pub fn empty() -> Self {
+        Self {
+            min: Point3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            max: Point3::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+        }
+    }
+
+    /// A box containing everything, for unbounded objects.
+    pub fn infinite() -> Self {
+        Self {
+            min: Point3::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+            max: Point3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+        }
+    }
+
+    pub fn union(&self, other: &Aabb) -> Self {
+        Self {
+            min: Point3::new(
+                self.min.x.min(other.min.x),
+                self.min.y.min(other.min.y),
+                self.min.z.min(other.min.z),
+            ),
+            max: Point3::new(
+                self.max.x.max(other.max.x),
+                self.max.y.max(other.max.y),
+                self.max.z.max(other.max.z),
+            ),
+        }
+    }
+
+    /// The box of the space inside both boxes, empty if they don't overlap.
+    pub fn intersection(&self, other: &Aabb) -> Self {
+        Self {
+            min: Point3::new(
+                self.min.x.max(other.min.x),
+                self.min.y.max(other.min.y),
+                self.min.z.max(other.min.z),
+            ),
+            max: Point3::new(
+                self.max.x.min(other.max.x),
+                self.max.y.min(other.max.y),
+                self.max.z.min(other.max.z),
+            ),
+        }
+    }
+
+    /// Grows the box by `delta` to every side.
+    pub fn expanded(&self, delta: f64) -> Self {
+        let extent = Vec3::new(delta, delta, delta);
+        Self {
+            min: &self.min - &extent,
+            max: &self.max + &extent,
+        }
+    }
+
+    /// Grows the box so that it is at least `delta` thick along every axis, as flat boxes of
+    /// planar objects are easily missed due to rounding errors.
+    pub fn padded(&self, delta: f64) -> Self {
+        let mut padded = self.clone();
+        for axis in 0..3 {
+            let missing = delta - (self.max[axis] - self.min[axis]);
+            if missing > 0. {
+                padded.min[axis] -= missing / 2.;
+                padded.max[axis] += missing / 2.;
+            }
+        }
+        padded
+    }
+
+    pub fn is_empty(&self) -> bool {
+        (0..3).any(|axis| self.min[axis] > self.max[axis])
+    }
+
+    pub fn corners(&self) -> [Point3; 8] {
+        std::array::from_fn(|corner| {
+            let pick = |bit: usize, axis: usize| {
+                if corner & bit == 0 {
+                    self.min[axis]
+                } else {
+                    self.max[axis]
+                }
+            };
+            Point3::new(pick(1, 0), pick(2, 1), pick(4, 2))
+        })
+    }
+
+    /// The box containing the transformed corners, which contains the whole transformed box
+    /// since transforms are affine.
+    pub fn transformed(&self, transform: &Transform) -> Self {
+        if self.is_empty() {
+            return self.clone();
+        }
+        if (0..3).any(|axis| self.min[axis].is_infinite() || self.max[axis].is_infinite()) {
+            return Self::infinite();
+        }
+        self.corners().iter().fold(Self::empty(), |bounds, corner| {
+            let point = transform.transform_point(corner);
+            bounds.union(&Self::new(&point, &point))
+        })
+    }
+
+    pub fn center(&self) -> Point3 {
+        (&self.min + &self.max) * 0.5
+    }
+
+    /// Index of the axis along which the box is the longest.
+    pub fn longest_axis(&self) -> usize {
+        let size = &self.max - &self.min;
+        if size.x > size.y && size.x > size.z {
+            0
+        } else if size.y > size.z {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// Slab test: whether the ray passes through the box within `time_range`.
+    pub fn hit(&self, ray: &Ray, time_range: &Range<f64>) -> bool {
+        self.clip(ray, time_range).is_some()
+    }
+
+    /// The part of `time_range` during which the ray is inside the box, if any.
+    pub fn clip(&self, ray: &Ray, time_range: &Range<f64>) -> Option<Range<f64>> {
+        let mut start = time_range.start;
+        let mut end = time_range.end;
+        for axis in 0..3 {
+            let inverse_direction = 1. / ray.direction[axis];
+            let t0 = (self.min[axis] - ray.origin[axis]) * inverse_direction;
+            let t1 = (self.max[axis] - ray.origin[axis]) * inverse_direction;
+            let (t0, t1) = if t0 < t1 { (t0, t1) } else { (t1, t0) };
+            start = start.max(t0);
+            end = end.min(t1);
+            if end <= start {
+                return None;
+            }
+        }
+        Some(start..end)
+    }
+}
@@ -0,0 +1,723 @@
+//! Keyframed animation of the camera, of object transforms and of any other scene parameter,
+//! and rendering of numbered frame sequences.
+//!
+//! Tracks are keyed by frame number, fractional frames falling between frames. The scene is
+//! built again for every frame by a closure given the frame number, which samples the tracks
+//! for the camera, the transforms of the objects and the parameters of their materials.
+//! `CameraTracks` also has presets for the usual camera moves: orbits, dolly zooms and
+//! flights through waypoints.
+
+use crate::camera::Camera;
+use crate::camera_settings::{CameraError, CameraSettings};
+use crate::film::Filter;
+use crate::hit::list::HittableList;
+use crate::image::Image;
+use crate::matrix::{Quaternion, Transform};
+use crate::vec3::{Point3, Vec3};
+use crate::video::FrameWriter;
+use std::f64::consts::PI;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum AnimationError {
+    /// A track was given no keys.
+    NoKeys,
+    /// A camera flight was given fewer than two different waypoints.
+    TooFewWaypoints,
+}
+
+impl Display for AnimationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoKeys => write!(f, "animation tracks need a key"),
+            Self::TooFewWaypoints => write!(f, "flights need two different waypoints"),
+        }
+    }
+}
+
+impl std::error::Error for AnimationError {}
+
+/// Values that can be interpolated, or extrapolated for `t` outside of 0 to 1. Every
+/// interpolation of a track is built out of this alone, so curves of rotations stay rotations.
+pub trait Lerp: Clone {
+    fn lerp(&self, other: &Self, t: f64) -> Self;
+}
+
+impl Lerp for f64 {
+    fn lerp(&self, other: &Self, t: f64) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Lerp for Vec3 {
+    fn lerp(&self, other: &Self, t: f64) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Lerp for Quaternion {
+    fn lerp(&self, other: &Self, t: f64) -> Self {
+        self.slerp(other, t)
+    }
+}
+
+/// How a track goes from a key to the next one.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Interpolation<T> {
+    /// Keeps the value of the key until the next key.
+    Step,
+    /// Straight to the next value.
+    Linear,
+    /// A Catmull-Rom spline through the keys around, which keeps the speed continuous across
+    /// keys without having to place any control point.
+    CatmullRom,
+    /// A cubic Bézier curve leaving the key towards the first control point, and arriving at
+    /// the next key from the second one.
+    Bezier(T, T),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Key<T> {
+    pub frame: f64,
+    pub value: T,
+    pub interpolation: Interpolation<T>, // How the track goes on to the next key
+}
+
+impl<T> Key<T> {
+    pub fn new(frame: f64, value: T, interpolation: Interpolation<T>) -> Self {
+        Self {
+            frame,
+            value,
+            interpolation,
+        }
+    }
+}
+
+fn keyed<T: Clone>(keys: Vec<(f64, T)>, interpolation: Interpolation<T>) -> Vec<Key<T>> {
+    keys.into_iter()
+        .map(|(frame, value)| Key::new(frame, value, interpolation.clone()))
+        .collect()
+}
+
+/// A value changing over the frames, through keys sorted by frame. Before the first key and
+/// after the last one, the track holds their values.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Track<T> {
+    keys: Vec<Key<T>>,
+}
+
+impl<T: Lerp> Track<T> {
+    /// A track keeping the same value on every frame.
+    pub fn constant(value: T) -> Self {
+        Self::sorted(vec![Key::new(0., value, Interpolation::Step)])
+    }
+
+    /// A track through `keys` in any order, which must not be empty.
+    pub fn new(keys: Vec<Key<T>>) -> Result<Self, AnimationError> {
+        if keys.is_empty() {
+            return Err(AnimationError::NoKeys);
+        }
+        Ok(Self::sorted(keys))
+    }
+
+    /// A track going straight from key to key, keys being frame and value pairs.
+    pub fn linear(keys: Vec<(f64, T)>) -> Result<Self, AnimationError> {
+        Self::new(keyed(keys, Interpolation::Linear))
+    }
+
+    /// A smooth track through all the keys, keys being frame and value pairs.
+    pub fn catmull_rom(keys: Vec<(f64, T)>) -> Result<Self, AnimationError> {
+        Self::new(keyed(keys, Interpolation::CatmullRom))
+    }
+
+    // A track through keys known not to be empty.
+    fn sorted(mut keys: Vec<Key<T>>) -> Self {
+        keys.sort_by(|l, r| l.frame.total_cmp(&r.frame));
+        Self { keys }
+    }
+
+    /// Adds a key, replacing the key on the same frame if there is one.
+    pub fn with_key(mut self, frame: f64, value: T, interpolation: Interpolation<T>) -> Self {
+        self.keys.retain(|key| key.frame != frame);
+        self.keys.push(Key::new(frame, value, interpolation));
+        Self::sorted(self.keys)
+    }
+
+    pub fn keys(&self) -> &[Key<T>] {
+        &self.keys
+    }
+
+    /// First and last keyed frames.
+    pub fn frames(&self) -> RangeInclusive<f64> {
+        self.keys[0].frame..=self.keys[self.keys.len() - 1].frame
+    }
+
+    pub fn value_at(&self, frame: f64) -> T {
+        let next = self.keys.partition_point(|key| key.frame <= frame);
+        if next == 0 {
+            return self.keys[0].value.clone();
+        }
+        if next == self.keys.len() {
+            return self.keys[next - 1].value.clone();
+        }
+        let (from, to) = (&self.keys[next - 1], &self.keys[next]);
+        let t = (frame - from.frame) / (to.frame - from.frame);
+        match &from.interpolation {
+            Interpolation::Step => from.value.clone(),
+            Interpolation::Linear => from.value.lerp(&to.value, t),
+            Interpolation::Bezier(control1, control2) => {
+                // De Casteljau's construction.
+                let a = from.value.lerp(control1, t);
+                let b = control1.lerp(control2, t);
+                let c = control2.lerp(&to.value, t);
+                a.lerp(&b, t).lerp(&b.lerp(&c, t), t)
+            }
+            Interpolation::CatmullRom => self.catmull_rom_at(next - 1, frame),
+        }
+    }
+
+    // The Catmull-Rom spline between the key `index` and the next one, evaluated with the
+    // construction of Barry and Goldman so that it only needs `lerp`. Using the frames of the
+    // keys as knots keeps the speed even when keys are unevenly spaced. The first and last
+    // keys are mirrored to stand in for the missing neighbors, as are neighbors on the same
+    // frame as the key next to them, which would make a knot interval empty.
+    fn catmull_rom_at(&self, index: usize, frame: f64) -> T {
+        let (k1, k2) = (&self.keys[index], &self.keys[index + 1]);
+        let (t1, t2) = (k1.frame, k2.frame);
+        let (t0, p0) = match index.checked_sub(1).map(|i| &self.keys[i]) {
+            Some(key) if key.frame < t1 => (key.frame, key.value.clone()),
+            _ => (2. * t1 - t2, k1.value.lerp(&k2.value, -1.)),
+        };
+        let (t3, p3) = match self.keys.get(index + 2) {
+            Some(key) if key.frame > t2 => (key.frame, key.value.clone()),
+            _ => (2. * t2 - t1, k2.value.lerp(&k1.value, -1.)),
+        };
+        let (p1, p2) = (&k1.value, &k2.value);
+        let a1 = p0.lerp(p1, (frame - t0) / (t1 - t0));
+        let a2 = p1.lerp(p2, (frame - t1) / (t2 - t1));
+        let a3 = p2.lerp(&p3, (frame - t2) / (t3 - t2));
+        let b1 = a1.lerp(&a2, (frame - t0) / (t2 - t0));
+        let b2 = a2.lerp(&a3, (frame - t1) / (t3 - t1));
+        b1.lerp(&b2, (frame - t1) / (t2 - t1))
+    }
+}
+
+/// Tracks moving the camera, zooming it and focusing it. Everything else about the camera
+/// comes from the settings it is applied to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CameraTracks {
+    pub look_from: Track<Point3>,   // Point camera is looking from
+    pub look_at: Track<Point3>,     // Point camera is looking at
+    pub vfov: Track<f64>,           // Vertical view angle (field of view) in degrees
+    pub focus_distance: Track<f64>, // Distance from look_from to the plane of perfect focus
+}
+
+impl CameraTracks {
+    /// A camera staying where the `settings` put it, to key from there.
+    pub fn from_settings(settings: &CameraSettings) -> Self {
+        Self {
+            look_from: Track::constant(settings.look_from.clone()),
+            look_at: Track::constant(settings.look_at.clone()),
+            vfov: Track::constant(settings.vfov),
+            focus_distance: Track::constant(settings.focus_distance),
+        }
+    }
+
+    /// A turntable: the camera circles once around the `look_at` of the `settings` over
+    /// `frames` frames, counterclockwise seen from above, at its distance from the target and
+    /// `elevation` degrees above the plane perpendicular to `vup`. It starts on the side of
+    /// `look_from` and is back there at frame `frames`, so rendering the frames 0 to
+    /// `frames - 1` loops seamlessly.
+    pub fn orbit(settings: &CameraSettings, frames: u32, elevation: f64) -> Self {
+        let up = settings.vup.unit();
+        let offset = &settings.look_from - &settings.look_at;
+        let level = &offset - &up * offset.dot(&up);
+        let front = if level.is_near_zero() {
+            up.orthonormal_basis().0
+        } else {
+            level.unit()
+        };
+        let side = up.cross(&front);
+        let radius = offset.length();
+        let (height, reach) = (
+            radius * elevation.to_radians().sin(),
+            radius * elevation.to_radians().cos(),
+        );
+
+        let keys = (0..=frames)
+            .map(|frame| {
+                let angle = 2. * PI * frame as f64 / frames.max(1) as f64;
+                let around = &front * angle.cos() + &side * angle.sin();
+                let position = &settings.look_at + around * reach + &up * height;
+                (frame as f64, position)
+            })
+            .collect();
+        Self::from_settings(settings)
+            .with_look_from(Track::sorted(keyed(keys, Interpolation::Linear)))
+    }
+
+    /// The dolly zoom of Vertigo: over `frames` frames, the field of view goes from the one of
+    /// the `settings` to `vfov`, while the camera moves along its line of sight to keep the
+    /// size of everything at the distance of `look_at` the same. Only the perspective around
+    /// the target changes, the background seeming to stretch away or rush in. The focus
+    /// distance follows the distance to the target, so the target stays as sharp as it was.
+    pub fn dolly_zoom(
+        settings: &CameraSettings,
+        frames: u32,
+        vfov: f64,
+    ) -> Result<Self, CameraError> {
+        for angle in [settings.vfov, vfov] {
+            if !(angle > 0. && angle < 180.) {
+                return Err(CameraError::InvalidFieldOfView);
+            }
+        }
+        let backwards = (&settings.look_from - &settings.look_at).unit();
+        // The height of the view at the target is 2 distance tan(vfov / 2).
+        let start = (&settings.look_from - &settings.look_at).length();
+        let half_height = start * (settings.vfov / 2.).to_radians().tan();
+
+        let (mut positions, mut angles, mut focus) = (Vec::new(), Vec::new(), Vec::new());
+        for frame in 0..=frames {
+            let t = frame as f64 / frames.max(1) as f64;
+            let angle = settings.vfov.lerp(&vfov, t);
+            let distance = half_height / (angle / 2.).to_radians().tan();
+            positions.push((frame as f64, &settings.look_at + &backwards * distance));
+            angles.push((frame as f64, angle));
+            focus.push((frame as f64, settings.focus_distance * distance / start));
+        }
+        Ok(Self::from_settings(settings)
+            .with_look_from(Track::sorted(keyed(positions, Interpolation::Linear)))
+            .with_vfov(Track::sorted(keyed(angles, Interpolation::Linear)))
+            .with_focus_distance(Track::sorted(keyed(focus, Interpolation::Linear))))
+    }
+
+    /// A smooth flight through the `waypoints` over `frames` frames, looking at where the
+    /// camera will be `look_ahead` frames later, with the field of view of the `settings`. The
+    /// waypoints are spaced out in time by their distance, to fly at a roughly steady speed.
+    /// At the end of the flight, the camera keeps looking the way it was going.
+    pub fn fly_through(
+        settings: &CameraSettings,
+        waypoints: &[Point3],
+        frames: u32,
+        look_ahead: f64,
+    ) -> Result<Self, CameraError> {
+        // A waypoint repeated would make an empty leg, with no direction to fly in.
+        let mut waypoints = waypoints.to_vec();
+        waypoints.dedup();
+        if waypoints.len() < 2 {
+            return Err(AnimationError::TooFewWaypoints.into());
+        }
+        let legs: Vec<f64> = waypoints
+            .windows(2)
+            .map(|pair| (&pair[1] - &pair[0]).length())
+            .collect();
+        let total: f64 = legs.iter().sum();
+        let mut traveled = 0.;
+        let mut keys = vec![(0., waypoints[0].clone())];
+        for (k, leg) in legs.iter().enumerate() {
+            traveled += leg / total;
+            keys.push((traveled * frames as f64, waypoints[k + 1].clone()));
+        }
+        let path = Track::sorted(keyed(keys, Interpolation::CatmullRom));
+
+        let end = frames as f64;
+        let look_ahead = look_ahead.max(1e-3);
+        let targets = (0..=frames)
+            .map(|frame| {
+                let ahead = frame as f64 + look_ahead;
+                let target = if ahead <= end {
+                    path.value_at(ahead)
+                } else {
+                    // Past the end, along the last stretch of the path.
+                    let last = path.value_at(end);
+                    let before = path.value_at(end - look_ahead);
+                    before.lerp(&last, (ahead - (end - look_ahead)) / look_ahead)
+                };
+                (frame as f64, target)
+            })
+            .collect();
+        Ok(Self::from_settings(settings)
+            .with_look_from(path)
+            .with_look_at(Track::sorted(keyed(targets, Interpolation::Linear))))
+    }
+
+    pub fn with_look_from(mut self, look_from: Track<Point3>) -> Self {
+        self.look_from = look_from;
+        self
+    }
+
+    pub fn with_look_at(mut self, look_at: Track<Point3>) -> Self {
+        self.look_at = look_at;
+        self
+    }
+
+    pub fn with_vfov(mut self, vfov: Track<f64>) -> Self {
+        self.vfov = vfov;
+        self
+    }
+
+    pub fn with_focus_distance(mut self, focus_distance: Track<f64>) -> Self {
+        self.focus_distance = focus_distance;
+        self
+    }
+
+    /// The `settings` with the view, field of view and focus of the camera at `frame`.
+    pub fn settings_at(&self, settings: &CameraSettings, frame: f64) -> CameraSettings {
+        settings
+            .clone()
+            .with_view(
+                self.look_from.value_at(frame),
+                self.look_at.value_at(frame),
+                settings.vup.clone(),
+            )
+            .with_vfov(self.vfov.value_at(frame))
+            .with_defocus(settings.defocus_angle, self.focus_distance.value_at(frame))
+    }
+}
+
+/// Tracks placing an object, applied in the order scale, rotation and translation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransformTracks {
+    pub scale: Track<Vec3>,
+    pub rotation: Track<Quaternion>,
+    pub translation: Track<Vec3>,
+}
+
+impl Default for TransformTracks {
+    fn default() -> Self {
+        Self {
+            scale: Track::constant(Vec3::one()),
+            rotation: Track::constant(Quaternion::identity()),
+            translation: Track::constant(Vec3::zero()),
+        }
+    }
+}
+
+impl TransformTracks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Scale factors along every axis, none of which should ever be zero.
+    pub fn with_scale(mut self, scale: Track<Vec3>) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_rotation(mut self, rotation: Track<Quaternion>) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_translation(mut self, translation: Track<Vec3>) -> Self {
+        self.translation = translation;
+        self
+    }
+
+    /// The transform at `frame`, to place the object with an `Instance`.
+    pub fn transform_at(&self, frame: f64) -> Transform {
+        Transform::scaling(&self.scale.value_at(frame))
+            .then(&Transform::from_quaternion(&self.rotation.value_at(frame)))
+            .then(&Transform::translation(&self.translation.value_at(frame)))
+    }
+}
+
+/// A range of frames to render, and where to save them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrameSequence {
+    pub frames: RangeInclusive<u32>, // Frame numbers to render
+    pub path: String,                // Image path, with a run of `#` for the frame number
+    pub filter: Filter,              // Reconstruction filter of every frame
+}
+
+impl FrameSequence {
+    /// Frames to be saved to `path`, where a run of `#` is replaced by the frame number
+    /// padded with zeros to its length, such as `frames/turntable_####.ppm`. Without any `#`,
+    /// the frame number goes right before the extension.
+    pub fn new(frames: RangeInclusive<u32>, path: &str) -> Self {
+        Self {
+            frames,
+            path: path.to_string(),
+            filter: Filter::default(),
+        }
+    }
+
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn frame_path(&self, frame: u32) -> PathBuf {
+        let Some(start) = self.path.find('#') else {
+            let path = PathBuf::from(&self.path);
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+            let name = match path.extension() {
+                Some(extension) => format!("{}_{:04}.{}", stem, frame, extension.to_string_lossy()),
+                None => format!("{}_{:04}", stem, frame),
+            };
+            return path.with_file_name(name);
+        };
+        let digits = self.path[start..].chars().take_while(|&c| c == '#').count();
+        PathBuf::from(format!(
+            "{}{:0digits$}{}",
+            &self.path[..start],
+            frame,
+            &self.path[start + digits..],
+        ))
+    }
+
+    /// Renders every frame of the sequence with the camera and the world that `scene` builds
+    /// for its frame number, and saves it. Frames are rendered with `Camera::render_filtered`,
+    /// at the `samples_per_pixel` of their camera. Missing directories of the path are created.
+    pub fn render<F>(&self, scene: F) -> Result<(), CameraError>
+    where
+        F: FnMut(u32) -> Result<(Camera, HittableList), CameraError>,
+    {
+        self.render_frames(scene, |frame, image| {
+            let path = self.frame_path(frame);
+            if let Some(directory) = path
+                .parent()
+                .filter(|parent| !parent.as_os_str().is_empty())
+            {
+                fs::create_dir_all(directory)?;
+            }
+            image.save_ppm(&path)
+        })
+    }
+
+    /// Renders every frame like `render`, but writes them one after the other to `video`
+    /// instead of saving them, ignoring the path of the sequence.
+    pub fn render_video<F, V>(&self, scene: F, video: &mut V) -> Result<(), CameraError>
+    where
+        F: FnMut(u32) -> Result<(Camera, HittableList), CameraError>,
+        V: FrameWriter,
+    {
+        self.render_frames(scene, |_, image| video.write_frame(&image))?;
+        Ok(video.finish()?)
+    }
+
+    fn render_frames<F, O>(&self, mut scene: F, mut output: O) -> Result<(), CameraError>
+    where
+        F: FnMut(u32) -> Result<(Camera, HittableList), CameraError>,
+        O: FnMut(u32, Image) -> io::Result<()>,
+    {
+        let count = self.frames.clone().count();
+        for (rendered, frame) in self.frames.clone().enumerate() {
+            log::info!("Frame {} ({} of {})", frame, rendered + 1, count);
+            let (camera, world) = scene(frame)?;
+            output(
+                frame,
+                camera.render_filtered(&world, self.filter).to_image(),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn linear_tracks_hold_their_ends() {
+        let track = Track::linear(vec![(10., 2.), (0., 0.), (20., 0.)]).unwrap();
+        assert_near(track.value_at(-5.), 0.);
+        assert_near(track.value_at(5.), 1.);
+        assert_near(track.value_at(15.), 1.);
+        assert_near(track.value_at(30.), 0.);
+        assert_eq!(track.frames(), 0.0..=20.0);
+
+        let track = track.with_key(10., 4., Interpolation::Step);
+        assert_eq!(track.keys().len(), 3);
+        assert_near(track.value_at(19.), 4.);
+    }
+
+    #[test]
+    fn catmull_rom_tracks_go_through_their_keys() {
+        let track = Track::catmull_rom(vec![(0., 0.), (1., 1.), (3., 0.), (4., 2.)]).unwrap();
+        for key in track.keys() {
+            assert_near(track.value_at(key.frame), key.value);
+        }
+        // Points on a line stay on it, at an even speed.
+        let line = Track::catmull_rom(vec![(0., 0.), (1., 1.), (2., 2.), (3., 3.)]).unwrap();
+        assert_near(line.value_at(1.5), 1.5);
+        assert_near(line.value_at(0.25), 0.25);
+        // The speed is continuous across keys.
+        let speed =
+            |frame: f64| (track.value_at(frame + 1e-6) - track.value_at(frame - 1e-6)) / 2e-6;
+        assert!((speed(1. - 1e-3) - speed(1. + 1e-3)).abs() < 1e-2);
+    }
+
+    #[test]
+    fn bezier_tracks_follow_their_control_points() {
+        let track = Track::new(vec![
+            Key::new(0., 0., Interpolation::Bezier(1., 1.)),
+            Key::new(2., 0., Interpolation::Linear),
+        ])
+        .unwrap();
+        assert_near(track.value_at(1.), 0.75);
+        assert_near(track.value_at(2.), 0.);
+    }
+
+    #[test]
+    fn transform_tracks_scale_rotate_then_translate() {
+        let y = Vec3::new(0., 1., 0.);
+        let tracks = TransformTracks::new()
+            .with_scale(Track::constant(Vec3::new(2., 2., 2.)))
+            .with_rotation(
+                Track::linear(vec![
+                    (0., Quaternion::identity()),
+                    (10., Quaternion::from_axis_angle(&y, 180.)),
+                ])
+                .unwrap(),
+            )
+            .with_translation(Track::constant(Vec3::new(0., 0., -5.)));
+        let point = tracks
+            .transform_at(5.)
+            .transform_point(&Point3::new(1., 0., 0.));
+        assert!((point - Point3::new(0., 0., -7.)).length() < 1e-9);
+    }
+
+    #[test]
+    fn camera_tracks_change_the_view() {
+        let settings = CameraSettings::new();
+        let tracks = CameraTracks::from_settings(&settings)
+            .with_vfov(Track::linear(vec![(0., 90.), (10., 30.)]).unwrap());
+        let moved = tracks.settings_at(&settings, 5.);
+        assert_near(moved.vfov, 60.);
+        assert_eq!(moved.look_from, settings.look_from);
+    }
+
+    #[test]
+    fn frame_paths_are_numbered() {
+        let sequence = FrameSequence::new(0..=9, "frames/orbit_###.ppm");
+        assert_eq!(
+            sequence.frame_path(7),
+            PathBuf::from("frames/orbit_007.ppm")
+        );
+        let sequence = FrameSequence::new(0..=9, "frames/orbit.ppm");
+        assert_eq!(
+            sequence.frame_path(12),
+            PathBuf::from("frames/orbit_0012.ppm")
+        );
+    }
+
+    #[test]
+    fn orbits_circle_the_target_and_loop() {
+        let settings = CameraSettings::new().with_view(
+            Point3::new(0., 0., 4.),
+            Point3::new(0., 1., 0.),
+            Vec3::new(0., 1., 0.),
+        );
+        let orbit = CameraTracks::orbit(&settings, 40, 30.);
+        let distance = (&settings.look_from - &settings.look_at).length();
+        for frame in 0..40 {
+            let offset = orbit.look_from.value_at(frame as f64) - &settings.look_at;
+            assert_near(offset.length(), distance);
+            assert_near(offset.y, distance / 2.);
+        }
+        let (start, end) = (orbit.look_from.value_at(0.), orbit.look_from.value_at(40.));
+        assert!((start - end).length() < 1e-9);
+        // A quarter turn later, the camera is to the right of where it started.
+        let quarter = orbit.look_from.value_at(10.) - &settings.look_at;
+        assert!(quarter.x > 0. && quarter.z.abs() < 1e-9);
+    }
+
+    #[test]
+    fn dolly_zooms_keep_the_target_size() {
+        let settings = CameraSettings::new().with_vfov(60.);
+        let dolly = CameraTracks::dolly_zoom(&settings, 10, 20.).unwrap();
+        let view_height = |frame: f64| {
+            let distance = (dolly.look_from.value_at(frame) - &settings.look_at).length();
+            2. * distance * (dolly.vfov.value_at(frame) / 2.).to_radians().tan()
+        };
+        for frame in 0..=10 {
+            assert_near(view_height(frame as f64), view_height(0.));
+        }
+        assert_near(dolly.vfov.value_at(10.), 20.);
+        assert_eq!(dolly.look_at.value_at(5.), settings.look_at);
+
+        // Focused on the target, the camera stays focused on it.
+        let settings = settings.with_defocus(1., 1.);
+        let dolly = CameraTracks::dolly_zoom(&settings, 10, 20.).unwrap();
+        for frame in [0., 3.5, 10.] {
+            let moved = dolly.settings_at(&settings, frame);
+            assert_near(
+                moved.focus_distance,
+                (&moved.look_from - &moved.look_at).length(),
+            );
+        }
+        assert!(matches!(
+            CameraTracks::dolly_zoom(&settings, 10, 180.),
+            Err(CameraError::InvalidFieldOfView)
+        ));
+    }
+
+    #[test]
+    fn flights_go_through_the_waypoints_looking_ahead() {
+        let waypoints = [
+            Point3::zero(),
+            Point3::new(0., 0., -10.),
+            Point3::new(10., 0., -10.),
+        ];
+        let flight = CameraTracks::fly_through(&CameraSettings::new(), &waypoints, 20, 2.).unwrap();
+        assert_eq!(flight.look_from.value_at(0.), waypoints[0]);
+        assert!((flight.look_from.value_at(10.) - &waypoints[1]).length() < 1e-9);
+        assert_eq!(flight.look_from.value_at(20.), waypoints[2]);
+        assert_eq!(flight.look_at.value_at(4.), flight.look_from.value_at(6.));
+        // At the end, still looking along the last leg.
+        let direction = flight.look_at.value_at(20.) - flight.look_from.value_at(20.);
+        assert!(direction.x > 0. && direction.z.abs() < 0.1 * direction.x);
+    }
+
+    #[test]
+    fn flights_skip_repeated_waypoints() {
+        let waypoints = [
+            Point3::zero(),
+            Point3::new(0., 0., -10.),
+            Point3::new(0., 0., -10.),
+            Point3::new(10., 0., -10.),
+        ];
+        let flight = CameraTracks::fly_through(&CameraSettings::new(), &waypoints, 20, 2.).unwrap();
+        for frame in 0..=40 {
+            let position = flight.look_from.value_at(frame as f64 / 2.);
+            assert!(position.length().is_finite());
+        }
+        assert!((flight.look_from.value_at(10.) - &waypoints[1]).length() < 1e-9);
+    }
+
+    #[test]
+    fn catmull_rom_tracks_handle_keys_on_the_same_frame() {
+        let track = Track::catmull_rom(vec![(0., 0.), (5., 1.), (5., 2.), (10., 3.)]).unwrap();
+        for frame in 0..=10 {
+            assert!(track.value_at(frame as f64).is_finite());
+        }
+        assert_near(track.value_at(5.), 2.);
+    }
+
+    #[test]
+    fn tracks_and_flights_refuse_missing_keys() {
+        assert!(matches!(
+            Track::<f64>::linear(vec![]),
+            Err(AnimationError::NoKeys)
+        ));
+        let waypoints = [Point3::one(), Point3::one()];
+        assert!(matches!(
+            CameraTracks::fly_through(&CameraSettings::new(), &waypoints, 20, 2.),
+            Err(CameraError::Animation(AnimationError::TooFewWaypoints))
+        ));
+    }
+}
@@ -0,0 +1,278 @@
+//! The shape of the lens aperture, which out of focus highlights take: round, polygonal from
+//! the blades of the diaphragm, or any shape cut in a mask.
+
+use crate::checkpoint::Fingerprint;
+use crate::color::luminance;
+use crate::image::Image;
+use crate::random::{random, random_range};
+use crate::vec3::Vec3;
+use std::f64::consts::PI;
+use std::rc::Rc;
+
+// Least number of cells along each axis of the grid sampling a mask. Pixels of smaller masks
+// are split, so that the cells left out on the round edge of the lens are thin.
+const MASK_RESOLUTION: usize = 256;
+
+#[derive(Clone, Debug)]
+pub enum ApertureShape {
+    Circle,
+    /// A regular polygon with a corner for every diaphragm blade, turned by `rotation` degrees
+    /// counterclockwise from a corner pointing right.
+    Polygon {
+        blades: u32,
+        rotation: f64,
+    },
+    /// The brightness of an image stretched over the square around the aperture sets how much
+    /// light goes through every point, white being fully open. The lens is still round, so the
+    /// corners of the image are cut off.
+    Mask(Rc<ApertureMask>),
+}
+
+/// An aperture mask prepared for sampling: how much light goes through a grid of cells over
+/// the mask, accumulated so that a cell can be picked with a binary search. Only cells entirely
+/// within the lens are kept. Like the other shapes, the mask changes where light goes through
+/// the lens and not how much of it.
+#[derive(Debug)]
+pub struct ApertureMask {
+    width: usize,      // Cells in a row of the grid
+    rows: Vec<f64>,    // Light through every row down to this one
+    columns: Vec<f64>, // Light through every cell of the row up to this one, row after row
+}
+
+impl ApertureMask {
+    pub fn new(image: &Image) -> Self {
+        let subdivision = MASK_RESOLUTION.div_ceil(image.width().min(image.height()).max(1));
+        let (width, height) = (image.width() * subdivision, image.height() * subdivision);
+        let mut rows = Vec::with_capacity(height);
+        let mut columns = Vec::with_capacity(width * height);
+        let mut total = 0.;
+        for j in 0..height {
+            let mut row = 0.;
+            for i in 0..width {
+                // The corner of the cell farthest from the center of the lens.
+                let far = |k: usize, cells: usize| {
+                    let edge = if 2 * k < cells { k } else { k + 1 };
+                    edge as f64 / cells as f64 * 2. - 1.
+                };
+                let (x, y) = (far(i, width), far(j, height));
+                if x * x + y * y <= 1. {
+                    let pixel = image.get(i / subdivision, j / subdivision);
+                    row += luminance(pixel).max(0.);
+                }
+                columns.push(row);
+            }
+            total += row;
+            rows.push(total);
+        }
+        Self {
+            width,
+            rows,
+            columns,
+        }
+    }
+
+    // A point of the unit disk, distributed like the light going through the mask. Masks
+    // letting no light through have no points.
+    fn sample(&self) -> Option<Vec3> {
+        let total = *self.rows.last()?;
+        if total <= 0. {
+            return None;
+        }
+        let light = random_range(0.0..total);
+        let j = self.rows.partition_point(|&through| through <= light);
+        let row = &self.columns[j * self.width..(j + 1) * self.width];
+        let light = random_range(0.0..row[self.width - 1]);
+        let i = row.partition_point(|&through| through <= light);
+
+        let height = self.rows.len() as f64;
+        let x = (i as f64 + random()) / self.width as f64 * 2. - 1.;
+        let y = 1. - (j as f64 + random()) / height * 2.;
+        Some(Vec3::new(x, y, 0.))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Aperture {
+    pub shape: ApertureShape,
+    pub squeeze: f64,    // How many times taller than wide the aperture is
+    pub vignetting: f64, // How far the lens barrel clips the aperture towards the image corners
+}
+
+impl Default for Aperture {
+    fn default() -> Self {
+        Self::new(ApertureShape::Circle)
+    }
+}
+
+impl Aperture {
+    pub fn new(shape: ApertureShape) -> Self {
+        Self {
+            shape,
+            squeeze: 1.,
+            vignetting: 0.,
+        }
+    }
+
+    pub fn polygon(blades: u32, rotation: f64) -> Self {
+        Self::new(ApertureShape::Polygon {
+            blades: blades.max(3),
+            rotation,
+        })
+    }
+
+    pub fn mask(mask: &Image) -> Self {
+        Self::new(ApertureShape::Mask(Rc::new(ApertureMask::new(mask))))
+    }
+
+    /// Makes out of focus highlights ovals `squeeze` times taller than wide, like anamorphic
+    /// lenses do.
+    pub fn with_squeeze(mut self, squeeze: f64) -> Self {
+        self.squeeze = squeeze;
+        self
+    }
+
+    /// Clips the aperture seen from off-center parts of the image, by a second disk moved
+    /// `vignetting` times the distance from the image center, 1 being the corners. Highlights
+    /// near the edges turn into cat's eyes, and the corners of the image darken.
+    pub fn with_vignetting(mut self, vignetting: f64) -> Self {
+        self.vignetting = vignetting;
+        self
+    }
+
+    /// A random point of the aperture in the unit disk, seen from the point `film` of the
+    /// image, from -1 to 1 towards the corners. Points blocked by vignetting, or by a mask that
+    /// let nothing through, are `None`.
+    pub fn sample(&self, film: (f64, f64)) -> Option<Vec3> {
+        let point = match &self.shape {
+            ApertureShape::Circle => Vec3::random_in_unit_disk(),
+            ApertureShape::Polygon { blades, rotation } => {
+                sample_polygon(*blades, rotation.to_radians())
+            }
+            ApertureShape::Mask(mask) => mask.sample()?,
+        };
+        let point = Vec3::new(point.x / self.squeeze, point.y, 0.);
+
+        let barrel = Vec3::new(film.0, film.1, 0.) * self.vignetting;
+        if (&point - barrel).length_squared() > 1. {
+            return None;
+        }
+        Some(point)
+    }
+
+    /// Masks only add the size of their grid and the light going through it, not the whole
+    /// table built from the image.
+    pub fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        match &self.shape {
+            ApertureShape::Circle => fingerprint.add_bytes(b"circle"),
+            ApertureShape::Polygon { blades, rotation } => {
+                fingerprint.add_bytes(b"polygon");
+                fingerprint.add_u64(*blades as u64);
+                fingerprint.add_f64(*rotation);
+            }
+            ApertureShape::Mask(mask) => {
+                fingerprint.add_bytes(b"mask");
+                fingerprint.add_u64(mask.width as u64);
+                fingerprint.add_u64(mask.rows.len() as u64);
+                fingerprint.add_f64(mask.rows.last().copied().unwrap_or_default());
+            }
+        }
+        fingerprint.add_f64(self.squeeze);
+        fingerprint.add_f64(self.vignetting);
+    }
+}
+
+// A uniformly distributed point in a regular polygon inscribed in the unit circle, in one of
+// its triangles around the center. Fewer than three blades make a triangle, as they would
+// leave no area to sample.
+fn sample_polygon(blades: u32, rotation: f64) -> Vec3 {
+    let blades = blades.max(3);
+    let triangle = (random() * blades as f64).floor().min(blades as f64 - 1.);
+    let corner = |k: f64| {
+        let angle = rotation + 2. * PI * k / blades as f64;
+        Vec3::new(angle.cos(), angle.sin(), 0.)
+    };
+    let (mut a, mut b) = (random(), random());
+    if a + b > 1. {
+        (a, b) = (1. - a, 1. - b);
+    }
+    corner(triangle) * a + corner(triangle + 1.) * b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::random::seed;
+
+    #[test]
+    fn polygon_samples_stay_inside_the_blades() {
+        // A square with corners on the axes, whose sides are at a distance of 1/sqrt(2).
+        let aperture = Aperture::polygon(4, 0.);
+        for _ in 0..1000 {
+            let point = aperture.sample((0., 0.)).unwrap();
+            assert!(point.x.abs() + point.y.abs() <= 1. + 1e-12);
+        }
+    }
+
+    #[test]
+    fn squeeze_narrows_the_aperture() {
+        let aperture = Aperture::default().with_squeeze(2.);
+        for _ in 0..1000 {
+            let point = aperture.sample((0., 0.)).unwrap();
+            assert!(point.x.abs() <= 0.5);
+        }
+    }
+
+    #[test]
+    fn vignetting_clips_the_aperture_in_the_corners() {
+        seed(1);
+        let aperture = Aperture::default().with_vignetting(1.);
+        let passed = (0..1000)
+            .filter_map(|_| aperture.sample((1., 0.)))
+            .inspect(|point| assert!(point.x >= 0.))
+            .count();
+        // The two unit disks one radius apart overlap over 39% of their area.
+        assert!((300..500).contains(&passed), "{} passed", passed);
+    }
+
+    #[test]
+    fn mask_only_lets_light_through_where_it_is_bright() {
+        seed(1);
+        let mut mask = Image::new(2, 2);
+        mask.set(1, 0, Color::one());
+        let aperture = Aperture::mask(&mask);
+        for _ in 0..100 {
+            let point = aperture.sample((0., 0.)).unwrap();
+            assert!(point.x >= 0. && point.y >= 0.);
+        }
+        assert!(Aperture::mask(&Image::new(2, 2)).sample((0., 0.)).is_none());
+    }
+
+    #[test]
+    fn mask_samples_follow_its_brightness() {
+        seed(1);
+        let mut mask = Image::new(2, 1);
+        mask.set(0, 0, Color::one() * 0.25);
+        mask.set(1, 0, Color::one());
+        let aperture = Aperture::mask(&mask);
+        let samples = 10000;
+        let right = (0..samples)
+            .map(|_| aperture.sample((0., 0.)).unwrap())
+            .inspect(|point| assert!(point.length_squared() <= 1. + 1e-12))
+            .filter(|point| point.x > 0.)
+            .count();
+        assert!((right as f64 / samples as f64 - 0.8).abs() < 0.02);
+    }
+
+    #[test]
+    fn polygons_have_at_least_three_blades() {
+        let aperture = Aperture::new(ApertureShape::Polygon {
+            blades: 0,
+            rotation: 0.,
+        });
+        for _ in 0..100 {
+            let point = aperture.sample((0., 0.)).unwrap();
+            assert!(point.length().is_finite());
+        }
+    }
+}
@@ -1,34 +1,66 @@
+use crate::aperture::Aperture;
 use crate::color::{write_color, Color};
-use crate::hit::hittable::Hittable;
+use crate::hit::hittable::{HitRecord, Hittable};
 use crate::ray::Ray;
 use crate::vec3::{Point3, Vec3};
 
+use crate::camera_settings::CameraError;
+use crate::checkpoint::{self, Checkpoint, CheckpointError, CheckpointSettings, Fingerprint};
+use crate::film::{Film, Filter, Region};
 use crate::hit::list::HittableList;
-use crate::hit::sphere::Sphere;
-use crate::random::sample_square;
+use crate::image::Image;
+use crate::lens::{LensCamera, LensSystem};
+use crate::medium::{Medium, MediumStack};
+use crate::progressive::ProgressiveSettings;
+use crate::projection::Projection;
+use crate::random::{self, random, random_range, sample_square};
+use crate::sampling::{AdaptiveSampling, SampleBuffer};
+use crate::stereo::{self, Eye, Stereo};
 use log;
+use std::ops::Range;
+use std::rc::Rc;
+use std::sync::atomic::Ordering;
+use std::time::Instant;
 
+#[derive(PartialEq)]
+enum PassesOutcome {
+    Finished,  // Every pixel reached the sample cap
+    OutOfTime, // The time limit ran out
+    Stopped,   // The caller asked to stop
+}
+
+#[derive(Clone)]
 pub struct Camera {
-    aspect_ratio: f64,      // Ratio of image width over height
-    image_width: i32,       // Rendered image width in pixels
-    image_height: i32,      // Rendered image height
-    center: Point3,         // Camera center
-    pixel00_loc: Point3,    // Location of pixel 0, 0
-    pixel_delta_u: Vec3,    // Offset to pixel to the right
-    pixel_delta_v: Vec3,    // Offset to pixel below
-    samples_per_pixel: i32, // Count of random samples for each pixel
-    max_depth: i32,         // Maximum number of ray bounces into scene
-    vfov: f64,              // Vertical view angle (field of view)
-    look_from: Point3,      // Point camera is looking from
-    look_at: Point3,        // Point camera is looking at
-    vup: Vec3,              // Camera-relative "up" direction
-    defocus_angle: f64,     // Variation angle of rays through each pixel
-    focus_distance: f64,    // Distance from camera look_from point to plane of perfect focus
-    defocus_disk_u: Vec3,   // Defocus disk horizontal radius
-    defocus_disk_v: Vec3,   // Defocus disk vertical radius
+    image_width: i32,           // Rendered image width in pixels
+    image_height: i32,          // Rendered image height
+    center: Point3,             // Camera center
+    pixel00_loc: Point3,        // Location of pixel 0, 0
+    pixel_delta_u: Vec3,        // Offset to pixel to the right
+    pixel_delta_v: Vec3,        // Offset to pixel below
+    samples_per_pixel: i32,     // Count of random samples for each pixel
+    max_depth: i32,             // Maximum number of ray bounces into scene
+    defocus_angle: f64,         // Variation angle of rays through each pixel
+    focus_distance: f64,        // Distance from camera look_from point to plane of perfect focus
+    defocus_disk_u: Vec3,       // Defocus disk horizontal radius
+    defocus_disk_v: Vec3,       // Defocus disk vertical radius
+    aperture: Aperture,         // Shape of the defocus disk
+    u: Vec3,                    // Camera frame unit vector pointing right
+    v: Vec3,                    // Camera frame unit vector pointing up
+    w: Vec3,                    // Camera frame unit vector pointing opposite the view direction
+    projection: Projection,     // Mapping from image positions to rays
+    lens: Option<LensCamera>,   // Lens system traced instead of the thin lens
+    eye_offset: f64,            // Offset of the eye to the right of the center, for stereo
+    convergence: f64,           // Distance at which the eyes of a stereo pair converge
+    exposure: f64,              // Factor scaling the light reaching the camera
+    shutter: Range<f64>,        // Shutter time interval during which rays are sampled
+    background: Option<Color>,  // Color of rays that hit nothing, a sky gradient if not set
+    medium: Option<Rc<Medium>>, // Medium the camera is in, such as the fog filling a room
 }
 
 impl Camera {
+    /// A thin lens perspective camera. `CameraSettings` builds the same camera from named
+    /// values, and validates them first.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         aspect_ratio: f64,
         image_width: i32,
@@ -71,11 +103,10 @@ impl Camera {
 
         // Calculate the camera defocus disk basis vectors.
         let defocus_radius = (defocus_angle / 2.).to_radians().tan() * focus_distance;
-        let defocus_disk_u = u * defocus_radius;
-        let defocus_disk_v = v * defocus_radius;
+        let defocus_disk_u = &u * defocus_radius;
+        let defocus_disk_v = &v * defocus_radius;
 
         Self {
-            aspect_ratio,
             image_width,
             image_height,
             center,
@@ -84,15 +115,177 @@ impl Camera {
             pixel_delta_v,
             samples_per_pixel,
             max_depth,
-            vfov,
-            look_from,
-            look_at,
-            vup,
             defocus_angle,
             focus_distance,
             defocus_disk_u,
             defocus_disk_v,
+            aperture: Aperture::default(),
+            u,
+            v,
+            w,
+            projection: Projection::Perspective,
+            lens: None,
+            eye_offset: 0.,
+            convergence: f64::INFINITY,
+            exposure: 1.,
+            shutter: 0.0..0.0,
+            background: None,
+            medium: None,
+        }
+    }
+
+    /// Moves the plane of perfect focus to `focus_distance`, keeping the field of view and the
+    /// defocus angle. A lens system is refocused with `LensSystem::focused_at` instead, the
+    /// distance being from its front element.
+    pub fn with_focus_distance(mut self, focus_distance: f64) -> Result<Self, CameraError> {
+        if focus_distance.is_nan() || focus_distance <= 0. {
+            return Err(CameraError::NonPositiveFocusDistance);
         }
+        if let Some(lens) = &self.lens {
+            self.lens = Some(lens.refocused(focus_distance)?);
+            self.focus_distance = focus_distance;
+            return Ok(self);
+        }
+
+        // The viewport lies in the focus plane, and the defocus disk grows with its distance.
+        let ratio = focus_distance / self.focus_distance;
+        self.pixel00_loc = &self.center + (&self.pixel00_loc - &self.center) * ratio;
+        self.pixel_delta_u *= ratio;
+        self.pixel_delta_v *= ratio;
+        self.defocus_disk_u *= ratio;
+        self.defocus_disk_v *= ratio;
+        self.focus_distance = focus_distance;
+        Ok(self)
+    }
+
+    /// Distance to the first surface seen through the center of the pixel i, j, along the
+    /// view direction, to focus on it with `with_focus_distance`. `None` if the pixel sees no
+    /// surface.
+    pub fn focus_distance_at_pixel(&self, world: &HittableList, i: i32, j: i32) -> Option<f64> {
+        let ray = self.pixel_center_ray(i, j)?;
+        let record = world.hit(&ray, &(0.001..f64::INFINITY))?;
+        Some(self.view_depth(&record.point))
+    }
+
+    /// Distance to the object added to `world` as `name`, along the view direction, to focus
+    /// on it with `with_focus_distance`. It is measured to the surface seen towards the center
+    /// of the object's bounding box, or to that center if the surface isn't hit there. `None`
+    /// if there is no such object, or it is unbounded.
+    pub fn focus_distance_to_object(&self, world: &HittableList, name: &str) -> Option<f64> {
+        let object = world.get(name)?;
+        let target = object.bounding_box().center();
+        if ![target.x, target.y, target.z].iter().all(|c| c.is_finite()) {
+            return None;
+        }
+        let ray = Ray::new_at(
+            self.center.clone(),
+            &target - &self.center,
+            self.shutter.start,
+        );
+        let point = object
+            .hit(&ray, &(0.001..f64::INFINITY))
+            .map_or(target, |record| record.point);
+        Some(self.view_depth(&point))
+    }
+
+    // Distance from the camera center to a point, along the view direction.
+    fn view_depth(&self, point: &Point3) -> f64 {
+        (point - &self.center).dot(&-&self.w)
+    }
+
+    // The ray through the center of the pixel i, j from the center of the lens.
+    fn pixel_center_ray(&self, i: i32, j: i32) -> Option<Ray> {
+        if self.projection == Projection::Perspective {
+            let pixel_center =
+                &self.pixel00_loc + &self.pixel_delta_u * i as f64 + &self.pixel_delta_v * j as f64;
+            let direction = pixel_center - &self.center;
+            return Some(Ray::new_at(
+                self.center.clone(),
+                direction,
+                self.shutter.start,
+            ));
+        }
+        let s = (i as f64 + 0.5) / self.image_width as f64;
+        let t = (j as f64 + 0.5) / self.image_height as f64;
+        let aspect_ratio = self.image_width as f64 / self.image_height as f64;
+        let (origin, direction) = self.projection.camera_ray(s, t, aspect_ratio)?;
+        Some(Ray::new_at(
+            &self.center + self.to_world(&origin),
+            self.to_world(&direction),
+            self.shutter.start,
+        ))
+    }
+
+    /// Keeps the shutter open from `open` to `close`, so that rays are spread over that time
+    /// interval and moving objects are motion blurred. Object motion is defined over the shutter
+    /// times 0 to 1, so `with_shutter(0., 1.)` captures all of it.
+    pub fn with_shutter(mut self, open: f64, close: f64) -> Self {
+        self.shutter = open.min(close)..open.max(close);
+        self
+    }
+
+    /// Scales the light reaching the camera, like the exposure settings of a real camera. See
+    /// `PhysicalCamera` to derive it from the f-number, shutter time and ISO.
+    pub fn with_exposure(mut self, exposure: f64) -> Self {
+        self.exposure = exposure;
+        self
+    }
+
+    /// Gives the defocus disk the shape of `aperture`, which out of focus highlights take.
+    pub fn with_aperture(mut self, aperture: Aperture) -> Self {
+        self.aperture = aperture;
+        self
+    }
+
+    /// Traces rays through the elements of `lens` onto a film `sensor_width` millimeters wide,
+    /// instead of using the thin lens. The film height follows the image aspect ratio, and the
+    /// field of view, defocus and projection settings are ignored: they follow from the lens,
+    /// which should be focused with `LensSystem::focused_at`.
+    pub fn with_lens(mut self, lens: LensSystem, sensor_width: f64) -> Self {
+        let sensor_height = sensor_width * self.image_height as f64 / self.image_width as f64;
+        self.lens = Some(LensCamera::new(lens, sensor_width, sensor_height));
+        self
+    }
+
+    /// Maps image positions to rays with `projection` instead of the thin lens perspective.
+    /// The defocus settings only apply to the perspective projection, and the vertical field of
+    /// view is ignored by the others.
+    pub fn with_projection(mut self, projection: Projection) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    /// The camera of one eye of a stereo pair. With the perspective projection the eyes keep
+    /// parallel view directions and their images are shifted to converge, so that vertical
+    /// lines stay vertical in both.
+    pub fn eye(&self, eye: Eye, stereo: &Stereo) -> Self {
+        let mut camera = self.clone();
+        camera.eye_offset = stereo.eye_offset(eye);
+        camera.convergence = stereo.convergence;
+        if self.projection == Projection::Perspective {
+            let shift = &self.u * camera.eye_offset;
+            camera.center = &self.center + &shift;
+            camera.pixel00_loc =
+                &self.pixel00_loc + shift * (1. - self.focus_distance / stereo.convergence);
+        }
+        camera
+    }
+
+    /// Renders both eyes progressively like `render_progressive` and combines them into one
+    /// image following `stereo.layout`.
+    pub fn render_stereo(
+        &self,
+        world: &HittableList,
+        settings: &ProgressiveSettings,
+        stereo: &Stereo,
+    ) -> Image {
+        let [left, right] = [Eye::Left, Eye::Right].map(|eye| {
+            log::info!("Rendering the {:?} eye", eye);
+            self.eye(eye, stereo)
+                .render_progressive(world, settings)
+                .to_image()
+        });
+        stereo.combine(&left, &right)
     }
 
     pub fn render(&self, world: &HittableList) {
@@ -103,8 +296,7 @@ impl Camera {
             for i in 0..self.image_width {
                 let mut pixel_color = Color::zero();
                 for _ in 0..self.samples_per_pixel {
-                    let ray = self.get_ray(i, j);
-                    pixel_color += &Self::ray_color(&ray, &world, self.max_depth);
+                    pixel_color += &self.sample_pixel(world, i, j);
                 }
                 pixel_color /= self.samples_per_pixel as f64;
                 write_color(&pixel_color);
@@ -114,44 +306,463 @@ impl Camera {
         log::info!("Done!")
     }
 
-    // Construct a camera ray originating from the defocus disk and directed at a randomly
-    // sampled point around the pixel location i, j.
-    fn get_ray(&self, i: i32, j: i32) -> Ray {
+    /// Renders `samples_per_pixel` samples in every pixel, and reconstructs the image from
+    /// them with `filter` instead of averaging the samples of every pixel on their own.
+    pub fn render_filtered(&self, world: &HittableList, filter: Filter) -> Film {
+        let (width, height) = (self.image_width as usize, self.image_height as usize);
+        self.render_region(world, Region::full(width, height), filter)
+    }
+
+    /// Renders only the `region` of the image, such as a crop window rendered again with more
+    /// samples to fix a noisy part, with the camera of the full frame. Pixels along the border
+    /// of the region also get the samples taken outside it that fall within the filter radius,
+    /// so that the result pasted into the full image with `Image::paste` blends in seamlessly.
+    pub fn render_region(&self, world: &HittableList, region: Region, filter: Filter) -> Film {
+        let (width, height) = (self.image_width as usize, self.image_height as usize);
+        let mut film = Film::for_region(width, height, region, filter);
+        let sampled = film.sample_region();
+
+        for j in sampled.y..sampled.y + sampled.height {
+            log::info!("Scanlines remaining: {}", sampled.y + sampled.height - j);
+            for i in sampled.x..sampled.x + sampled.width {
+                for _ in 0..self.samples_per_pixel {
+                    let offset = sample_square();
+                    let x = i as f64 + 0.5 + offset.x;
+                    let y = j as f64 + 0.5 + offset.y;
+                    film.add_sample(x, y, &self.sample_at(world, x, y));
+                }
+            }
+        }
+
+        log::info!("Done!");
+        film
+    }
+
+    /// Renders the image spending samples only where they are needed: every pixel gets
+    /// `sampling.min_samples`, and then the pixels whose estimated error is still above the
+    /// noise target get more samples in batches, up to `sampling.max_samples`.
+    ///
+    /// The `samples_per_pixel` of the camera is ignored in this mode.
+    pub fn render_adaptive(
+        &self,
+        world: &HittableList,
+        sampling: &AdaptiveSampling,
+    ) -> SampleBuffer {
+        let mut buffer = SampleBuffer::new(self.image_width as usize, self.image_height as usize);
+
+        for j in 0..self.image_height {
+            log::info!("Scanlines remaining: {}", self.image_height - j);
+            for i in 0..self.image_width {
+                let stats = buffer.get_mut(i as usize, j as usize);
+                for _ in 0..sampling.min_samples {
+                    stats.add(&self.sample_pixel(world, i, j));
+                }
+            }
+        }
+
+        let mut pass = 1;
+        loop {
+            let mut active_pixels = 0;
+            for j in 0..self.image_height {
+                for i in 0..self.image_width {
+                    let stats = buffer.get_mut(i as usize, j as usize);
+                    if sampling.is_converged(stats) {
+                        continue;
+                    }
+                    active_pixels += 1;
+                    let batch = sampling
+                        .batch_samples
+                        .min(sampling.max_samples - stats.count());
+                    for _ in 0..batch {
+                        stats.add(&self.sample_pixel(world, i, j));
+                    }
+                }
+            }
+            if active_pixels == 0 {
+                break;
+            }
+            log::info!(
+                "Adaptive pass {}: {} pixels above the noise target",
+                pass,
+                active_pixels
+            );
+            pass += 1;
+        }
+
+        log::info!(
+            "Done! Average samples per pixel: {:.1}",
+            buffer.total_samples() as f64 / (buffer.width() * buffer.height()) as f64
+        );
+        buffer
+    }
+
+    /// Renders the image in passes of increasing sample count until `settings.max_samples` per
+    /// pixel are reached or the time limit runs out, whichever comes first.
+    ///
+    /// The `samples_per_pixel` of the camera is ignored in this mode.
+    pub fn render_progressive(
+        &self,
+        world: &HittableList,
+        settings: &ProgressiveSettings,
+    ) -> SampleBuffer {
+        let mut buffer = SampleBuffer::new(self.image_width as usize, self.image_height as usize);
+        self.continue_progressive(world, settings, &mut buffer);
+        buffer
+    }
+
+    /// Keeps adding progressive passes to an existing accumulation `buffer`. Returns `true` if
+    /// every pixel reached `settings.max_samples` and `false` if the time limit ran out first.
+    pub fn continue_progressive(
+        &self,
+        world: &HittableList,
+        settings: &ProgressiveSettings,
+        buffer: &mut SampleBuffer,
+    ) -> bool {
+        self.progressive_passes(world, settings, buffer, |_| false) == PassesOutcome::Finished
+    }
+
+    /// Renders progressively like `render_progressive`, but survives being killed: the
+    /// accumulated samples and the random generator state are saved to `checkpoint.path` every
+    /// `checkpoint.interval`, on Ctrl-C, and when rendering stops.
+    ///
+    /// If the checkpoint file already exists, rendering resumes from it, so running again with
+    /// a larger `settings.max_samples` adds samples to a finished render. A checkpoint saved for
+    /// a different scene or camera is refused with `CheckpointError::SceneChanged`.
+    pub fn render_resumable(
+        &self,
+        world: &HittableList,
+        settings: &ProgressiveSettings,
+        checkpoint: &CheckpointSettings,
+    ) -> Result<SampleBuffer, CheckpointError> {
+        let fingerprint = self.scene_fingerprint(world, checkpoint.scene_version);
+        let mut buffer = if checkpoint.path.exists() {
+            let saved = Checkpoint::load(&checkpoint.path)?;
+            if saved.fingerprint != fingerprint {
+                return Err(CheckpointError::SceneChanged);
+            }
+            log::info!(
+                "Resuming from {} at {} samples per pixel",
+                checkpoint.path.display(),
+                saved.buffer.min_samples()
+            );
+            random::set_rng_state(&saved.rng);
+            saved.buffer
+        } else {
+            SampleBuffer::new(self.image_width as usize, self.image_height as usize)
+        };
+
+        let interrupted = checkpoint::install_interrupt_handler();
+        let mut save_error = None;
+        let mut save = |buffer: &SampleBuffer| {
+            let saved = Checkpoint {
+                fingerprint,
+                rng: random::rng_state(),
+                buffer: buffer.clone(),
+            };
+            match saved.save(&checkpoint.path) {
+                Ok(()) => log::info!("Saved checkpoint to {}", checkpoint.path.display()),
+                Err(error) => save_error = Some(error),
+            }
+        };
+
+        let mut last_checkpoint = Instant::now();
+        let outcome = self.progressive_passes(world, settings, &mut buffer, |buffer| {
+            if interrupted.load(Ordering::SeqCst) {
+                return true;
+            }
+            if last_checkpoint.elapsed() >= checkpoint.interval {
+                save(buffer);
+                last_checkpoint = Instant::now();
+            }
+            false
+        });
+        save(&buffer);
+
+        if let Some(error) = save_error {
+            return Err(error.into());
+        }
+        match outcome {
+            PassesOutcome::Stopped => Err(CheckpointError::Interrupted),
+            _ => Ok(buffer),
+        }
+    }
+
+    // Runs progressive passes over `buffer`, calling `after_scanline` after every rendered
+    // scanline; rendering stops as soon as it returns `true`.
+    fn progressive_passes<F: FnMut(&SampleBuffer) -> bool>(
+        &self,
+        world: &HittableList,
+        settings: &ProgressiveSettings,
+        buffer: &mut SampleBuffer,
+        mut after_scanline: F,
+    ) -> PassesOutcome {
+        let start = Instant::now();
+        let mut last_snapshot = start;
+        let out_of_time = || {
+            settings
+                .time_limit
+                .is_some_and(|time_limit| start.elapsed() >= time_limit)
+        };
+
+        // Without any pixel there is nothing to render, and no sample count would ever be
+        // reached.
+        if buffer.pixels().is_empty() {
+            return PassesOutcome::Finished;
+        }
+
+        loop {
+            let reached = buffer.min_samples();
+            if reached >= settings.max_samples {
+                log::info!("Done! Reached {} samples per pixel", reached);
+                settings.write_snapshot(buffer);
+                return PassesOutcome::Finished;
+            }
+
+            let target = settings.next_pass_samples(reached);
+            log::info!("Progressive pass: {} samples per pixel", target);
+            for j in 0..self.image_height {
+                if out_of_time() {
+                    log::info!(
+                        "Time limit reached after {:.1?} at {} samples per pixel",
+                        start.elapsed(),
+                        buffer.min_samples()
+                    );
+                    settings.write_snapshot(buffer);
+                    return PassesOutcome::OutOfTime;
+                }
+                for i in 0..self.image_width {
+                    let stats = buffer.get_mut(i as usize, j as usize);
+                    while stats.count() < target {
+                        stats.add(&self.sample_pixel(world, i, j));
+                    }
+                }
+                if last_snapshot.elapsed() >= settings.snapshot_interval {
+                    settings.write_snapshot(buffer);
+                    last_snapshot = Instant::now();
+                }
+                if after_scanline(buffer) {
+                    log::info!(
+                        "Rendering stopped at {} samples per pixel",
+                        buffer.min_samples()
+                    );
+                    settings.write_snapshot(buffer);
+                    return PassesOutcome::Stopped;
+                }
+            }
+        }
+    }
+
+    // Identifies the camera and the scene, so that a checkpoint is never resumed against
+    // anything else.
+    fn scene_fingerprint(&self, world: &HittableList, scene_version: u64) -> u64 {
+        let mut fingerprint = Fingerprint::new();
+        fingerprint.add_u64(scene_version);
+        fingerprint.add_bytes(&self.image_width.to_le_bytes());
+        fingerprint.add_bytes(&self.image_height.to_le_bytes());
+        fingerprint.add_bytes(&self.max_depth.to_le_bytes());
+        fingerprint.add_f64(self.shutter.start);
+        fingerprint.add_f64(self.shutter.end);
+        fingerprint.add_f64(self.exposure);
+        fingerprint.add_f64(self.eye_offset);
+        fingerprint.add_f64(self.convergence);
+        for vec in [
+            &self.center,
+            &self.pixel00_loc,
+            &self.pixel_delta_u,
+            &self.pixel_delta_v,
+            &self.defocus_disk_u,
+            &self.defocus_disk_v,
+        ] {
+            fingerprint.add_f64(vec.x);
+            fingerprint.add_f64(vec.y);
+            fingerprint.add_f64(vec.z);
+        }
+        self.projection.fingerprint(&mut fingerprint);
+        self.aperture.fingerprint(&mut fingerprint);
+        // Whether the optional parts are there comes first, so that they can't be mistaken
+        // for one another.
+        fingerprint.add_bytes(&[
+            self.lens.is_some() as u8,
+            self.background.is_some() as u8,
+            self.medium.is_some() as u8,
+        ]);
+        if let Some(lens) = &self.lens {
+            lens.fingerprint(&mut fingerprint);
+        }
+        if let Some(background) = &self.background {
+            fingerprint.add_vec3(background);
+        }
+        if let Some(medium) = &self.medium {
+            medium.fingerprint(&mut fingerprint);
+        }
+        world.fingerprint(&mut fingerprint);
+        fingerprint.finish()
+    }
+
+    // Trace a single randomly sampled ray through the pixel i, j and return its color. Pixels
+    // outside the area covered by the projection are black.
+    fn sample_pixel(&self, world: &HittableList, i: i32, j: i32) -> Color {
         let offset = sample_square();
-        let pixel_sample = &self.pixel00_loc
-            + &self.pixel_delta_u * (i as f64 + offset.x)
-            + &self.pixel_delta_v * (j as f64 + offset.y);
+        self.sample_at(world, i as f64 + 0.5 + offset.x, j as f64 + 0.5 + offset.y)
+    }
+
+    // Trace a ray through the image position x, y, in pixels from the top left corner of the
+    // image, and return its color.
+    fn sample_at(&self, world: &HittableList, x: f64, y: f64) -> Color {
+        let Some((ray, weight)) = self.get_ray(x, y) else {
+            return Color::zero();
+        };
+        let media = MediumStack::new(self.medium.clone());
+        self.ray_color(&ray, world, self.max_depth, &media) * (self.exposure * weight)
+    }
+
+    /// Places the camera inside a medium. It must be the medium of a `MediumBoundary` around
+    /// the camera, so that rays leaving it leave the medium, or one filling the whole scene.
+    pub fn with_medium(mut self, medium: Rc<Medium>) -> Self {
+        self.medium = Some(medium);
+        self
+    }
+
+    /// Sets a uniform color for rays escaping the scene instead of the default sky gradient,
+    /// usually black for scenes lit only by their light sources.
+    pub fn with_background(mut self, background: Color) -> Self {
+        self.background = Some(background);
+        self
+    }
+
+    // Construct a camera ray through the image position x, y, in pixels from the top left
+    // corner of the image, and the weight of the light it brings back. Perspective rays
+    // originate from the defocus disk and are directed at the focus plane.
+    fn get_ray(&self, x: f64, y: f64) -> Option<(Ray, f64)> {
+        let shutter_time = if self.shutter.is_empty() {
+            self.shutter.start
+        } else {
+            random_range(self.shutter.clone())
+        };
+
+        let s = x / self.image_width as f64;
+        let t = y / self.image_height as f64;
+        let aspect_ratio = self.image_width as f64 / self.image_height as f64;
+        if let Some(lens) = &self.lens {
+            let (origin, direction, weight) = lens.sample_ray(s, t, (random(), random()))?;
+            let ray_origin = &self.center + self.to_world(&origin);
+            let ray = Ray::new_at(ray_origin, self.to_world(&direction), shutter_time);
+            return Some((ray, weight));
+        }
+        if self.projection != Projection::Perspective {
+            let (origin, direction) = self.projection.camera_ray(s, t, aspect_ratio)?;
+            let (origin, direction) = stereo::eye_ray(
+                &self.projection,
+                origin,
+                direction,
+                self.eye_offset,
+                self.convergence,
+            );
+            let ray_origin = &self.center + self.to_world(&origin);
+            let ray = Ray::new_at(ray_origin, self.to_world(&direction), shutter_time);
+            return Some((ray, 1.));
+        }
+
+        let pixel_sample =
+            &self.pixel00_loc + &self.pixel_delta_u * (x - 0.5) + &self.pixel_delta_v * (y - 0.5);
 
         let ray_origin = if self.defocus_angle <= 0. {
             self.center.clone()
         } else {
-            self.defocus_disk_sample()
+            // Image position from the center, 1 at the corners.
+            let corner = (aspect_ratio * aspect_ratio + 1.).sqrt();
+            let film = (
+                (2. * s - 1.) * aspect_ratio / corner,
+                (1. - 2. * t) / corner,
+            );
+            self.defocus_disk_sample(film)?
         };
         let ray_direction = pixel_sample - &ray_origin;
-        Ray::new(ray_origin, ray_direction)
+        Some((Ray::new_at(ray_origin, ray_direction, shutter_time), 1.))
+    }
+
+    // Converts a vector from the camera frame to world coordinates.
+    fn to_world(&self, vec: &Vec3) -> Vec3 {
+        &self.u * vec.x + &self.v * vec.y + &self.w * vec.z
     }
 
-    // Returns a random point in the camera defocus disk.
-    fn defocus_disk_sample(&self) -> Point3 {
-        let p = Vec3::random_in_unit_disk();
-        &self.center + &self.defocus_disk_u * p.x + &self.defocus_disk_v * p.y
+    // Returns a random point in the camera defocus disk, seen from the image position `film`,
+    // if the aperture lets light through there.
+    fn defocus_disk_sample(&self, film: (f64, f64)) -> Option<Point3> {
+        let p = self.aperture.sample(film)?;
+        Some(&self.center + &self.defocus_disk_u * p.x + &self.defocus_disk_v * p.y)
     }
 
-    fn ray_color(ray: &Ray, world: &HittableList, depth: i32) -> Color {
+    fn ray_color(&self, ray: &Ray, world: &HittableList, depth: i32, media: &MediumStack) -> Color {
         if depth <= 0 {
             return Color::zero(); // Black
         }
 
         let all_time = 0.001..f64::INFINITY;
-        if let Some(record) = world.hit(ray, &all_time) {
-            let material = record.material.clone();
-            return if let Some(scatter) = material.scatter(ray, record) {
-                Self::ray_color(&scatter.ray, world, depth - 1) * scatter.attenuation
-            } else {
-                Color::zero()
-            };
+        let record = world.hit(ray, &all_time);
+        let Some(medium) = media.current() else {
+            return self.surface_color(ray, record, world, depth, media);
+        };
+
+        // Inside a medium, light may scatter before reaching the next surface, and is absorbed
+        // along the way.
+        let speed = ray.direction.length();
+        let surface_distance = record
+            .as_ref()
+            .map_or(f64::INFINITY, |record| record.time * speed);
+        if let Some(distance) = medium.sample_scattering(surface_distance) {
+            let phase_function = medium.phase_function().clone();
+            let record = HitRecord::in_medium(ray, distance / speed, phase_function);
+            return self.surface_color(ray, Some(record), world, depth, media)
+                * medium.transmittance(distance);
         }
+        self.surface_color(ray, record, world, depth, media)
+            * medium.transmittance(surface_distance)
+    }
+
+    // Color of the light coming from a surface hit, or from the background if nothing was hit.
+    fn surface_color(
+        &self,
+        ray: &Ray,
+        record: Option<HitRecord>,
+        world: &HittableList,
+        depth: i32,
+        media: &MediumStack,
+    ) -> Color {
+        let Some(record) = record else {
+            return self.background_color(ray);
+        };
+        let material = record.material.clone();
+        let emitted = material.emitted(&record);
+        let boundary = record.medium.clone();
+        let outside = record.outside;
+        let normal = record.normal.clone();
+        let Some(scatter) = material.scatter(ray, record) else {
+            return emitted;
+        };
 
+        // Light going through the surface of a medium enters or leaves it. The normal faces the
+        // incoming ray, so it went through if it continues on the other side.
+        let crossed = scatter.ray.direction.dot(&normal) < 0.;
+        let color = match boundary {
+            Some(medium) if crossed => {
+                let mut media = media.clone();
+                if outside {
+                    media.enter(medium);
+                } else {
+                    media.leave(&medium);
+                }
+                self.ray_color(&scatter.ray, world, depth - 1, &media)
+            }
+            _ => self.ray_color(&scatter.ray, world, depth - 1, media),
+        };
+        emitted + color * scatter.attenuation
+    }
+
+    fn background_color(&self, ray: &Ray) -> Color {
+        if let Some(background) = &self.background {
+            return background.clone();
+        }
         let unit_direction = ray.direction.unit();
         let a = (unit_direction.y + 1.) * 0.5;
         let white = Color::new(1., 1., 1.);
@@ -159,3 +770,124 @@ impl Camera {
         white * (1. - a) + blue * a
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hit::sphere::Sphere;
+    use crate::lens::LensError;
+    use crate::materials::Lambertian;
+
+    fn camera() -> Camera {
+        Camera::new(
+            1.,
+            11,
+            1,
+            1,
+            40.,
+            Point3::zero(),
+            Point3::new(0., 0., -1.),
+            Vec3::new(0., 1., 0.),
+            2.,
+            10.,
+        )
+    }
+
+    fn world() -> HittableList {
+        let mut world = HittableList::new();
+        let material = Lambertian::new(Color::one());
+        world.add(Sphere::new(
+            Point3::new(0., 0., -5.),
+            1.,
+            material.clone().into(),
+        ));
+        world.add_named(
+            "far",
+            Sphere::new(Point3::new(3., 0., -12.), 1., material.into()),
+        );
+        world
+    }
+
+    #[test]
+    fn autofocus_on_the_surface_seen_through_a_pixel() {
+        let camera = camera();
+        let world = world();
+        let distance = camera.focus_distance_at_pixel(&world, 5, 5).unwrap();
+        assert!((distance - 4.).abs() < 1e-9);
+        assert!(camera.focus_distance_at_pixel(&world, 0, 0).is_none());
+    }
+
+    #[test]
+    fn autofocus_on_a_named_object() {
+        let camera = camera();
+        let world = world();
+        let distance = camera.focus_distance_to_object(&world, "far").unwrap();
+        // The sphere surface towards the camera, along the view direction.
+        let expected = 12. * (1. - 1. / 153f64.sqrt());
+        assert!((distance - expected).abs() < 1e-9, "{}", distance);
+        assert!(camera.focus_distance_to_object(&world, "near").is_none());
+    }
+
+    #[test]
+    fn refocusing_keeps_the_field_of_view() {
+        let camera = camera();
+        let refocused = camera.clone().with_focus_distance(5.).unwrap();
+        let before = camera.pixel_center_ray(2, 7).unwrap().direction.unit();
+        let after = refocused.pixel_center_ray(2, 7).unwrap().direction.unit();
+        assert!((before - after).length() < 1e-12);
+        assert!(
+            (refocused.defocus_disk_u.length() * 2. - camera.defocus_disk_u.length()).abs() < 1e-12
+        );
+    }
+
+    #[test]
+    fn progressive_rendering_of_empty_buffers_finishes() {
+        let mut buffer = SampleBuffer::new(0, 0);
+        assert!(camera().continue_progressive(&world(), &ProgressiveSettings::new(4), &mut buffer));
+    }
+
+    #[test]
+    fn fingerprint_covers_objects_out_of_view() {
+        let camera = camera();
+        let behind = |color: Color| {
+            let mut world = world();
+            world.add(Sphere::new(
+                Point3::new(0., 0., 5.),
+                1.,
+                Lambertian::new(color).into(),
+            ));
+            world
+        };
+        assert_eq!(
+            camera.scene_fingerprint(&behind(Color::one()), 0),
+            camera.scene_fingerprint(&behind(Color::one()), 0)
+        );
+        assert_ne!(
+            camera.scene_fingerprint(&behind(Color::one()), 0),
+            camera.scene_fingerprint(&behind(Color::new(1., 0., 0.)), 0)
+        );
+        assert_ne!(
+            camera.scene_fingerprint(&behind(Color::one()), 0),
+            camera.scene_fingerprint(&behind(Color::one()), 1)
+        );
+    }
+
+    #[test]
+    fn focus_distances_must_be_positive() {
+        assert!(matches!(
+            camera().with_focus_distance(0.),
+            Err(CameraError::NonPositiveFocusDistance)
+        ));
+        assert!(matches!(
+            camera().with_focus_distance(f64::NAN),
+            Err(CameraError::NonPositiveFocusDistance)
+        ));
+
+        // A biconvex lens that can't focus closer than its focal length.
+        let lens = LensSystem::parse("50 5 1.5 20\n-50 45 0 20\n").unwrap();
+        assert!(matches!(
+            camera().with_lens(lens, 36.).with_focus_distance(0.01),
+            Err(CameraError::Lens(LensError::CannotFocus))
+        ));
+    }
+}
@@ -0,0 +1,381 @@
+//! Camera setups that can be validated before rendering, and saved to and loaded from JSON files.
+
+use crate::animation::AnimationError;
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::lens::LensError;
+use crate::projection::Projection;
+use crate::vec3::{Point3, Vec3};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum CameraError {
+    /// The image width or the aspect ratio is not positive.
+    InvalidImageSize,
+    /// The number of samples per pixel is not positive.
+    NonPositiveSamples,
+    /// The maximum number of ray bounces is not positive.
+    NonPositiveMaxDepth,
+    /// The vertical field of view is not between 0 and 180 degrees.
+    InvalidFieldOfView,
+    /// The point the camera looks from or at has an infinite or NaN coordinate.
+    NonFiniteView,
+    /// The camera looks at the point it is looking from.
+    NoViewDirection,
+    /// The up direction is zero or parallel to the view direction.
+    UpParallelToView,
+    /// The plane of perfect focus is not in front of the camera.
+    NonPositiveFocusDistance,
+    /// The viewport of the orthographic projection is not positive.
+    NonPositiveViewportHeight,
+    /// The field of view of the fisheye projection is not between 0 and 360 degrees.
+    InvalidFisheyeFieldOfView,
+    /// The lens system can't be focused at the requested distance.
+    Lens(LensError),
+    /// A camera move couldn't be animated.
+    Animation(AnimationError),
+    Io(io::Error),
+    /// A saved camera file is not valid JSON or misses required values.
+    InvalidFormat(serde_json::Error),
+}
+
+impl Display for CameraError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidImageSize => write!(f, "image width and aspect ratio must be positive"),
+            Self::NonPositiveSamples => write!(f, "samples per pixel must be positive"),
+            Self::NonPositiveMaxDepth => write!(f, "maximum depth must be positive"),
+            Self::InvalidFieldOfView => write!(f, "field of view must be between 0 and 180°"),
+            Self::NonFiniteView => write!(f, "look_from and look_at must be finite"),
+            Self::NoViewDirection => write!(f, "look_from and look_at are the same point"),
+            Self::UpParallelToView => write!(f, "vup is parallel to the view direction"),
+            Self::NonPositiveFocusDistance => write!(f, "focus distance must be positive"),
+            Self::NonPositiveViewportHeight => {
+                write!(f, "orthographic viewport height must be positive")
+            }
+            Self::InvalidFisheyeFieldOfView => {
+                write!(f, "fisheye field of view must be between 0 and 360°")
+            }
+            Self::Lens(error) => write!(f, "{}", error),
+            Self::Animation(error) => write!(f, "{}", error),
+            Self::Io(error) => write!(f, "camera file i/o failed: {}", error),
+            Self::InvalidFormat(error) => write!(f, "not a valid camera file: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for CameraError {}
+
+impl From<io::Error> for CameraError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<LensError> for CameraError {
+    fn from(value: LensError) -> Self {
+        Self::Lens(value)
+    }
+}
+
+impl From<AnimationError> for CameraError {
+    fn from(value: AnimationError) -> Self {
+        Self::Animation(value)
+    }
+}
+
+impl From<serde_json::Error> for CameraError {
+    fn from(value: serde_json::Error) -> Self {
+        Self::InvalidFormat(value)
+    }
+}
+
+/// Everything needed to build a `Camera`, named instead of positional like the arguments of
+/// `Camera::new`. Values missing from a saved file take their defaults.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CameraSettings {
+    pub aspect_ratio: f64,         // Ratio of image width over height
+    pub image_width: i32,          // Rendered image width in pixels
+    pub samples_per_pixel: i32,    // Count of random samples for each pixel
+    pub max_depth: i32,            // Maximum number of ray bounces into scene
+    pub vfov: f64,                 // Vertical view angle (field of view) in degrees
+    pub look_from: Point3,         // Point camera is looking from
+    pub look_at: Point3,           // Point camera is looking at
+    pub vup: Vec3,                 // Camera-relative "up" direction
+    pub defocus_angle: f64,        // Variation angle of rays through each pixel
+    pub focus_distance: f64,       // Distance from look_from to the plane of perfect focus
+    pub projection: Projection,    // Mapping from image positions to rays
+    pub exposure: f64,             // Factor scaling the light reaching the camera
+    pub shutter: [f64; 2],         // Shutter open and close times
+    pub background: Option<Color>, // Color of rays that hit nothing, a sky gradient if not set
+}
+
+impl Default for CameraSettings {
+    /// A 400 pixels wide 16:9 pinhole camera at the origin looking down negative z.
+    fn default() -> Self {
+        Self {
+            aspect_ratio: 16. / 9.,
+            image_width: 400,
+            samples_per_pixel: 100,
+            max_depth: 50,
+            vfov: 90.,
+            look_from: Point3::zero(),
+            look_at: Point3::new(0., 0., -1.),
+            vup: Vec3::new(0., 1., 0.),
+            defocus_angle: 0.,
+            focus_distance: 10.,
+            projection: Projection::Perspective,
+            exposure: 1.,
+            shutter: [0., 0.],
+            background: None,
+        }
+    }
+}
+
+impl CameraSettings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_image_size(mut self, image_width: i32, aspect_ratio: f64) -> Self {
+        self.image_width = image_width;
+        self.aspect_ratio = aspect_ratio;
+        self
+    }
+
+    pub fn with_samples(mut self, samples_per_pixel: i32, max_depth: i32) -> Self {
+        self.samples_per_pixel = samples_per_pixel;
+        self.max_depth = max_depth;
+        self
+    }
+
+    pub fn with_view(mut self, look_from: Point3, look_at: Point3, vup: Vec3) -> Self {
+        self.look_from = look_from;
+        self.look_at = look_at;
+        self.vup = vup;
+        self
+    }
+
+    pub fn with_vfov(mut self, vfov: f64) -> Self {
+        self.vfov = vfov;
+        self
+    }
+
+    pub fn with_defocus(mut self, defocus_angle: f64, focus_distance: f64) -> Self {
+        self.defocus_angle = defocus_angle;
+        self.focus_distance = focus_distance;
+        self
+    }
+
+    pub fn with_projection(mut self, projection: Projection) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    pub fn with_exposure(mut self, exposure: f64) -> Self {
+        self.exposure = exposure;
+        self
+    }
+
+    pub fn with_shutter(mut self, open: f64, close: f64) -> Self {
+        self.shutter = [open, close];
+        self
+    }
+
+    pub fn with_background(mut self, background: Color) -> Self {
+        self.background = Some(background);
+        self
+    }
+
+    /// Checks that the settings describe a camera that can render an image.
+    pub fn validate(&self) -> Result<(), CameraError> {
+        if self.image_width <= 0 || self.aspect_ratio.is_nan() || self.aspect_ratio <= 0. {
+            return Err(CameraError::InvalidImageSize);
+        }
+        if self.samples_per_pixel <= 0 {
+            return Err(CameraError::NonPositiveSamples);
+        }
+        if self.max_depth <= 0 {
+            return Err(CameraError::NonPositiveMaxDepth);
+        }
+        if !(self.vfov > 0. && self.vfov < 180.) {
+            return Err(CameraError::InvalidFieldOfView);
+        }
+        let points = [&self.look_from, &self.look_at];
+        if !points
+            .iter()
+            .all(|p| p.x.is_finite() && p.y.is_finite() && p.z.is_finite())
+        {
+            return Err(CameraError::NonFiniteView);
+        }
+        let view = &self.look_at - &self.look_from;
+        if view.is_near_zero() {
+            return Err(CameraError::NoViewDirection);
+        }
+        let side = self.vup.cross(&view);
+        if side.length() <= 1e-9 * self.vup.length() * view.length() {
+            return Err(CameraError::UpParallelToView);
+        }
+        if self.focus_distance.is_nan() || self.focus_distance <= 0. {
+            return Err(CameraError::NonPositiveFocusDistance);
+        }
+        match self.projection {
+            Projection::Orthographic { height } if height.is_nan() || height <= 0. => {
+                Err(CameraError::NonPositiveViewportHeight)
+            }
+            Projection::Fisheye { fov, .. } if !(fov > 0. && fov <= 360.) => {
+                Err(CameraError::InvalidFisheyeFieldOfView)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn build(&self) -> Result<Camera, CameraError> {
+        self.validate()?;
+        let camera = Camera::new(
+            self.aspect_ratio,
+            self.image_width,
+            self.samples_per_pixel,
+            self.max_depth,
+            self.vfov,
+            self.look_from.clone(),
+            self.look_at.clone(),
+            self.vup.clone(),
+            self.defocus_angle,
+            self.focus_distance,
+        )
+        .with_projection(self.projection.clone())
+        .with_exposure(self.exposure)
+        .with_shutter(self.shutter[0], self.shutter[1]);
+        Ok(match &self.background {
+            Some(background) => camera.with_background(background.clone()),
+            None => camera,
+        })
+    }
+
+    pub fn save_json<P: AsRef<Path>>(&self, path: P) -> Result<(), CameraError> {
+        let mut out = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut out, self)?;
+        out.flush()?;
+        Ok(())
+    }
+
+    pub fn load_json<P: AsRef<Path>>(path: P) -> Result<Self, CameraError> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::projection::FisheyeMapping;
+
+    #[test]
+    fn default_settings_build() {
+        assert!(CameraSettings::new().build().is_ok());
+    }
+
+    #[test]
+    fn invalid_settings_are_refused() {
+        let settings = CameraSettings::new();
+        let refused = |settings: CameraSettings| settings.build().err();
+        assert!(matches!(
+            refused(settings.clone().with_image_size(0, 1.)),
+            Some(CameraError::InvalidImageSize)
+        ));
+        assert!(matches!(
+            refused(settings.clone().with_samples(0, 50)),
+            Some(CameraError::NonPositiveSamples)
+        ));
+        assert!(matches!(
+            refused(settings.clone().with_samples(100, 0)),
+            Some(CameraError::NonPositiveMaxDepth)
+        ));
+        assert!(matches!(
+            refused(settings.clone().with_vfov(180.)),
+            Some(CameraError::InvalidFieldOfView)
+        ));
+        assert!(matches!(
+            refused(settings.clone().with_view(
+                Point3::one(),
+                Point3::one(),
+                Vec3::new(0., 1., 0.)
+            )),
+            Some(CameraError::NoViewDirection)
+        ));
+        assert!(matches!(
+            refused(settings.clone().with_view(
+                Point3::new(f64::NAN, 0., 0.),
+                Point3::one(),
+                Vec3::new(0., 1., 0.)
+            )),
+            Some(CameraError::NonFiniteView)
+        ));
+        assert!(matches!(
+            refused(settings.clone().with_view(
+                Point3::zero(),
+                Point3::new(0., 0., f64::INFINITY),
+                Vec3::new(0., 1., 0.)
+            )),
+            Some(CameraError::NonFiniteView)
+        ));
+        assert!(matches!(
+            refused(settings.clone().with_view(
+                Point3::zero(),
+                Point3::new(0., -3., 0.),
+                Vec3::new(0., 1., 0.)
+            )),
+            Some(CameraError::UpParallelToView)
+        ));
+        assert!(matches!(
+            refused(settings.clone().with_defocus(0.6, 0.)),
+            Some(CameraError::NonPositiveFocusDistance)
+        ));
+        assert!(matches!(
+            refused(
+                settings
+                    .clone()
+                    .with_projection(Projection::Orthographic { height: 0. })
+            ),
+            Some(CameraError::NonPositiveViewportHeight)
+        ));
+        let fisheye = |fov| Projection::Fisheye {
+            fov,
+            mapping: FisheyeMapping::Equidistant,
+        };
+        assert!(matches!(
+            refused(settings.clone().with_projection(fisheye(0.))),
+            Some(CameraError::InvalidFisheyeFieldOfView)
+        ));
+        assert!(matches!(
+            refused(settings.clone().with_projection(fisheye(400.))),
+            Some(CameraError::InvalidFisheyeFieldOfView)
+        ));
+        assert!(refused(settings.clone().with_projection(fisheye(360.))).is_none());
+    }
+
+    #[test]
+    fn settings_round_trip_through_json() {
+        let settings = CameraSettings::new()
+            .with_view(
+                Point3::new(13., 2., 3.),
+                Point3::zero(),
+                Vec3::new(0., 1., 0.),
+            )
+            .with_defocus(0.6, 10.)
+            .with_projection(Projection::Orthographic { height: 4. })
+            .with_background(Color::new(0.1, 0.2, 0.3));
+        let json = serde_json::to_string(&settings).unwrap();
+        let loaded: CameraSettings = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, settings);
+
+        // Missing values take their defaults.
+        let loaded: CameraSettings = serde_json::from_str(r#"{"vfov": 20}"#).unwrap();
+        assert_eq!(loaded, CameraSettings::new().with_vfov(20.));
+    }
+}
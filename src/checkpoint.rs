@@ -0,0 +1,273 @@
+use crate::aabb::Aabb;
+use crate::random::RngState;
+use crate::sampling::{PixelStats, SampleBuffer};
+use crate::vec3::Vec3;
+use std::fmt::{Display, Formatter};
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Once, Weak};
+use std::time::Duration;
+
+const MAGIC: &[u8; 8] = b"RTCKPT01";
+
+// Interrupt flag of the render running now, which Ctrl-C sets.
+static RUNNING: Mutex<Weak<AtomicBool>> = Mutex::new(Weak::new());
+static INSTALL_HANDLER: Once = Once::new();
+
+#[derive(Debug)]
+pub enum CheckpointError {
+    Io(io::Error),
+    /// The file is not a checkpoint or was written by an incompatible version.
+    InvalidFormat,
+    /// The checkpoint was written for a different scene, camera or image size.
+    SceneChanged,
+    /// The render was stopped with Ctrl-C, after the checkpoint was saved.
+    Interrupted,
+}
+
+impl Display for CheckpointError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "checkpoint i/o failed: {}", error),
+            Self::InvalidFormat => write!(f, "not a valid checkpoint file"),
+            Self::SceneChanged => write!(f, "checkpoint was saved for a different scene"),
+            Self::Interrupted => write!(f, "render interrupted, progress saved to checkpoint"),
+        }
+    }
+}
+
+impl std::error::Error for CheckpointError {}
+
+impl From<io::Error> for CheckpointError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+/// Where and how often a resumable render saves its progress.
+#[derive(Clone, Debug)]
+pub struct CheckpointSettings {
+    pub path: PathBuf,      // Checkpoint file, resumed from if it already exists
+    pub interval: Duration, // Minimal time between two saved checkpoints
+    pub scene_version: u64, // Part of the scene fingerprint, for changes it can't see
+}
+
+impl CheckpointSettings {
+    pub fn new<P: Into<PathBuf>>(path: P, interval: Duration) -> Self {
+        Self {
+            path: path.into(),
+            interval,
+            scene_version: 0,
+        }
+    }
+
+    /// Objects only add a summary of themselves to the scene fingerprint, and distance
+    /// functions built from closures only their bounds. Bumping the version after changing
+    /// anything the fingerprint misses keeps the old checkpoint from being resumed.
+    pub fn with_scene_version(mut self, scene_version: u64) -> Self {
+        self.scene_version = scene_version;
+        self
+    }
+}
+
+/// The state of an unfinished render: the accumulated samples of every pixel, the state of the
+/// random generator, and a fingerprint of the scene they belong to.
+pub struct Checkpoint {
+    pub fingerprint: u64,
+    pub rng: RngState,
+    pub buffer: SampleBuffer,
+}
+
+impl Checkpoint {
+    /// Saves the checkpoint next to `path` first and then moves it in place, so that an
+    /// interrupted save never destroys the previous checkpoint.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+
+        let mut out = BufWriter::new(File::create(&temporary)?);
+        self.write_to(&mut out)?;
+        out.flush()?;
+        drop(out);
+        fs::rename(&temporary, path)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, CheckpointError> {
+        let mut input = BufReader::new(File::open(path)?);
+        Self::read_from(&mut input).map_err(|error| match error.kind() {
+            io::ErrorKind::UnexpectedEof | io::ErrorKind::InvalidData => {
+                CheckpointError::InvalidFormat
+            }
+            _ => CheckpointError::Io(error),
+        })
+    }
+
+    fn write_to<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        out.write_all(MAGIC)?;
+        out.write_all(&self.fingerprint.to_le_bytes())?;
+        out.write_all(&(self.buffer.width() as u64).to_le_bytes())?;
+        out.write_all(&(self.buffer.height() as u64).to_le_bytes())?;
+        out.write_all(&self.rng.seed)?;
+        out.write_all(&self.rng.stream.to_le_bytes())?;
+        out.write_all(&self.rng.word_pos.to_le_bytes())?;
+        for stats in self.buffer.pixels() {
+            stats.write_to(out)?;
+        }
+        Ok(())
+    }
+
+    fn read_from<R: Read>(input: &mut R) -> io::Result<Self> {
+        let mut magic = [0; 8];
+        input.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(io::ErrorKind::InvalidData.into());
+        }
+
+        let mut u64_bytes = [0; 8];
+        let mut read_u64 = |input: &mut R| -> io::Result<u64> {
+            input.read_exact(&mut u64_bytes)?;
+            Ok(u64::from_le_bytes(u64_bytes))
+        };
+        let fingerprint = read_u64(input)?;
+        let width = read_u64(input)? as usize;
+        let height = read_u64(input)? as usize;
+
+        let mut seed = [0; 32];
+        input.read_exact(&mut seed)?;
+        let stream = read_u64(input)?;
+        let mut word_pos = [0; 16];
+        input.read_exact(&mut word_pos)?;
+        let rng = RngState {
+            seed,
+            stream,
+            word_pos: u128::from_le_bytes(word_pos),
+        };
+
+        let pixel_count = width
+            .checked_mul(height)
+            .ok_or(io::Error::from(io::ErrorKind::InvalidData))?;
+        let pixels = (0..pixel_count)
+            .map(|_| PixelStats::read_from(input))
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(Self {
+            fingerprint,
+            rng,
+            buffer: SampleBuffer::from_pixels(width, height, pixels),
+        })
+    }
+}
+
+/// Makes Ctrl-C request a checkpoint instead of killing the process, and returns the flag it
+/// sets. Called when a render starts: every render gets a flag of its own, so a Ctrl-C that
+/// stopped an earlier render doesn't stop it. A second Ctrl-C, or one while no render is
+/// running, still terminates immediately.
+pub(crate) fn install_interrupt_handler() -> Arc<AtomicBool> {
+    INSTALL_HANDLER.call_once(|| {
+        let result = ctrlc::set_handler(|| {
+            let running = RUNNING.lock().map(|running| running.upgrade());
+            match running {
+                Ok(Some(interrupted)) if !interrupted.swap(true, Ordering::SeqCst) => {}
+                _ => std::process::exit(130),
+            }
+        });
+        if let Err(error) = result {
+            log::warn!("Ctrl-C will not save a checkpoint: {}", error);
+        }
+    });
+    let interrupted = Arc::new(AtomicBool::new(false));
+    if let Ok(mut running) = RUNNING.lock() {
+        *running = Arc::downgrade(&interrupted);
+    }
+    interrupted
+}
+
+/// Identifies a scene, so that a checkpoint is never resumed against anything else. Objects,
+/// materials and camera parts add what changes how they render, without the tables they
+/// precompute from it.
+///
+/// FNV-1a, used instead of `DefaultHasher` because fingerprints must stay the same across
+/// builds with different compiler versions.
+pub struct Fingerprint(u64);
+
+impl Fingerprint {
+    pub fn new() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+
+    pub fn add_bytes(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    pub fn add_u64(&mut self, value: u64) {
+        self.add_bytes(&value.to_le_bytes())
+    }
+
+    pub fn add_f64(&mut self, value: f64) {
+        self.add_bytes(&value.to_le_bytes())
+    }
+
+    pub fn add_vec3(&mut self, value: &Vec3) {
+        self.add_f64(value.x);
+        self.add_f64(value.y);
+        self.add_f64(value.z);
+    }
+
+    pub fn add_aabb(&mut self, value: &Aabb) {
+        self.add_vec3(&value.min);
+        self.add_vec3(&value.max);
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Default for Fingerprint {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    #[test]
+    fn checkpoint_round_trips_through_bytes() {
+        let mut buffer = SampleBuffer::new(2, 1);
+        buffer.get_mut(1, 0).add(&Color::new(0.25, 0.5, 1.));
+        let checkpoint = Checkpoint {
+            fingerprint: 42,
+            rng: RngState {
+                seed: [7; 32],
+                stream: 3,
+                word_pos: 1 << 70,
+            },
+            buffer,
+        };
+
+        let mut bytes = Vec::new();
+        checkpoint.write_to(&mut bytes).unwrap();
+        let restored = Checkpoint::read_from(&mut bytes.as_slice()).unwrap();
+
+        assert_eq!(restored.fingerprint, 42);
+        assert_eq!(restored.rng, checkpoint.rng);
+        assert_eq!(restored.buffer.get(0, 0).count(), 0);
+        assert_eq!(restored.buffer.get(1, 0).mean(), Color::new(0.25, 0.5, 1.));
+    }
+
+    #[test]
+    fn starting_a_render_forgets_earlier_interrupts() {
+        let earlier = install_interrupt_handler();
+        earlier.store(true, Ordering::SeqCst);
+        let interrupted = install_interrupt_handler();
+        assert!(!interrupted.load(Ordering::SeqCst));
+    }
+}
@@ -1,5 +1,6 @@
 use crate::random::clamp;
 use crate::vec3::Vec3;
+use std::io::{self, Write};
 
 pub type Color = Vec3;
 
@@ -20,9 +21,82 @@ fn f64_to_u8_component(component: f64, apply_gamma: bool) -> u8 {
     (256. * clamp(component, &intensity)) as u8
 }
 
+/// Perceived brightness of a linear color (Rec. 709 weights).
+pub fn luminance(color: &Color) -> f64 {
+    0.2126 * color.x + 0.7152 * color.y + 0.0722 * color.z
+}
+
+// Piecewise Gaussian used by the analytic fit of the CIE 1931 color matching functions by
+// Wyman, Sloan and Shirley.
+fn lobe(wavelength: f64, mean: f64, below: f64, above: f64) -> f64 {
+    let deviation = if wavelength < mean { below } else { above };
+    let x = (wavelength - mean) / deviation;
+    (-0.5 * x * x).exp()
+}
+
+/// Color of the light emitted by a black body at the given temperature in kelvin, as linear
+/// sRGB normalized to a luminance of one. Temperatures around 1500 K give the deep orange of
+/// embers, 6500 K is close to white.
+pub fn blackbody(kelvin: f64) -> Color {
+    const SECOND_RADIATION_CONSTANT: f64 = 1.4388e-2; // m K
+    let (mut x, mut y, mut z) = (0., 0., 0.);
+    for step in 0..=80 {
+        let nanometers = 380. + 5. * step as f64;
+        let meters = nanometers * 1e-9;
+        let radiance =
+            1. / (meters.powi(5) * ((SECOND_RADIATION_CONSTANT / (meters * kelvin)).exp() - 1.));
+        x += radiance
+            * (1.056 * lobe(nanometers, 599.8, 37.9, 31.0)
+                + 0.362 * lobe(nanometers, 442.0, 16.0, 26.7)
+                - 0.065 * lobe(nanometers, 501.1, 20.4, 26.2));
+        y += radiance
+            * (0.821 * lobe(nanometers, 568.8, 46.9, 40.5)
+                + 0.286 * lobe(nanometers, 530.9, 16.3, 31.1));
+        z += radiance
+            * (1.217 * lobe(nanometers, 437.0, 11.8, 36.0)
+                + 0.681 * lobe(nanometers, 459.0, 26.0, 13.8));
+    }
+    let color = Color::new(
+        (3.2406 * x - 1.5372 * y - 0.4986 * z).max(0.),
+        (-0.9689 * x + 1.8758 * y + 0.0415 * z).max(0.),
+        (0.0557 * x - 0.2040 * y + 1.0570 * z).max(0.),
+    );
+    let brightness = luminance(&color);
+    if brightness > 0. && brightness.is_finite() {
+        color / brightness
+    } else {
+        Color::zero()
+    }
+}
+
 pub fn write_color(color: &Color) {
-    let r = f64_to_u8_component(color.x, true);
-    let g = f64_to_u8_component(color.y, true);
-    let b = f64_to_u8_component(color.z, true);
-    println!("{} {} {}", r, g, b);
+    write_color_to(&mut io::stdout().lock(), color).expect("failed to write to stdout")
+}
+
+pub fn write_color_to<W: Write>(out: &mut W, color: &Color) -> io::Result<()> {
+    let [r, g, b] = to_rgb8(color);
+    writeln!(out, "{} {} {}", r, g, b)
+}
+
+/// The gamma corrected 8-bit components written by `write_color`.
+pub fn to_rgb8(color: &Color) -> [u8; 3] {
+    [
+        f64_to_u8_component(color.x, true),
+        f64_to_u8_component(color.y, true),
+        f64_to_u8_component(color.z, true),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn black_bodies_go_from_red_to_white() {
+        let ember = blackbody(1500.);
+        assert!(ember.x > ember.y && ember.y > ember.z);
+        let daylight = blackbody(6500.);
+        assert!((daylight.x - daylight.z).abs() < 0.1 && (daylight.y - daylight.z).abs() < 0.1);
+        assert!((luminance(&daylight) - 1.).abs() < 1e-9);
+    }
 }
@@ -0,0 +1,410 @@
+//! Image reconstruction from samples taken anywhere on the image: every sample counts towards
+//! the pixels around it, weighted by a reconstruction filter, instead of only towards the pixel
+//! it was taken in.
+//!
+//! Image positions are in pixels from the top left corner of the image, so that the pixel i, j
+//! covers i to i + 1 horizontally and j to j + 1 vertically, and its center is at
+//! i + 0.5, j + 0.5.
+
+use crate::color::Color;
+use crate::image::Image;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// Reconstruction filters, all separable, with their radius in pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Filter {
+    /// The same weight everywhere within the radius. A radius of 0.5 averages the samples of
+    /// every pixel on their own.
+    Box { radius: f64 },
+    /// Weights falling off linearly to zero at the radius.
+    Tent { radius: f64 },
+    /// A Gaussian of standard deviation `sigma`, shifted down to reach zero at the radius.
+    Gaussian { radius: f64, sigma: f64 },
+    /// The cubic filter of Mitchell and Netravali with parameters `b` and `c`, stretched over
+    /// the radius. Its negative lobes sharpen edges a little.
+    Mitchell { radius: f64, b: f64, c: f64 },
+    /// A sinc windowed by a wider sinc, whose first zero is at the radius. Sharpest of all, at
+    /// the cost of ringing around edges.
+    Lanczos { radius: f64 },
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Filter::Box { radius: 0.5 }
+    }
+}
+
+impl Filter {
+    pub fn tent() -> Self {
+        Filter::Tent { radius: 1. }
+    }
+
+    pub fn gaussian() -> Self {
+        Filter::Gaussian {
+            radius: 1.5,
+            sigma: 0.5,
+        }
+    }
+
+    /// The Mitchell-Netravali filter with the parameters recommended by its authors.
+    pub fn mitchell() -> Self {
+        Filter::Mitchell {
+            radius: 2.,
+            b: 1. / 3.,
+            c: 1. / 3.,
+        }
+    }
+
+    pub fn lanczos() -> Self {
+        Filter::Lanczos { radius: 3. }
+    }
+
+    pub fn radius(&self) -> f64 {
+        match self {
+            Filter::Box { radius }
+            | Filter::Tent { radius }
+            | Filter::Gaussian { radius, .. }
+            | Filter::Mitchell { radius, .. }
+            | Filter::Lanczos { radius } => *radius,
+        }
+    }
+
+    /// Weight of a sample `x`, `y` pixels away from a pixel center.
+    pub fn evaluate(&self, x: f64, y: f64) -> f64 {
+        self.evaluate_1d(x) * self.evaluate_1d(y)
+    }
+
+    fn evaluate_1d(&self, x: f64) -> f64 {
+        let x = x.abs();
+        let radius = self.radius();
+        if x > radius {
+            return 0.;
+        }
+        match self {
+            Filter::Box { .. } => 1.,
+            Filter::Tent { .. } => radius - x,
+            Filter::Gaussian { sigma, .. } => {
+                let gaussian = |x: f64| (-x * x / (2. * sigma * sigma)).exp();
+                (gaussian(x) - gaussian(radius)).max(0.)
+            }
+            Filter::Mitchell { b, c, .. } => {
+                // The cubic is defined over [-2, 2].
+                let x = 2. * x / radius;
+                if x < 1. {
+                    ((12. - 9. * b - 6. * c) * x * x * x
+                        + (-18. + 12. * b + 6. * c) * x * x
+                        + (6. - 2. * b))
+                        / 6.
+                } else {
+                    ((-b - 6. * c) * x * x * x
+                        + (6. * b + 30. * c) * x * x
+                        + (-12. * b - 48. * c) * x
+                        + (8. * b + 24. * c))
+                        / 6.
+                }
+            }
+            Filter::Lanczos { .. } => sinc(x) * sinc(x / radius),
+        }
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1e-5 {
+        return 1.;
+    }
+    let x = PI * x;
+    x.sin() / x
+}
+
+/// A rectangle of pixels of an image, such as the crop window of a render.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Region {
+    pub x: usize,      // Column of the left edge
+    pub y: usize,      // Row of the top edge
+    pub width: usize,  // Width in pixels
+    pub height: usize, // Height in pixels
+}
+
+impl Region {
+    pub fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// The whole of a width by height image.
+    pub fn full(width: usize, height: usize) -> Self {
+        Self::new(0, 0, width, height)
+    }
+
+    pub fn contains(&self, i: usize, j: usize) -> bool {
+        (self.x..self.x + self.width).contains(&i) && (self.y..self.y + self.height).contains(&j)
+    }
+
+    /// The part of the region inside a width by height image.
+    pub fn clamped(&self, width: usize, height: usize) -> Self {
+        let x = self.x.min(width);
+        let y = self.y.min(height);
+        Self::new(x, y, self.width.min(width - x), self.height.min(height - y))
+    }
+
+    /// The region grown by `margin` pixels on every side, and kept inside a width by height
+    /// image.
+    pub fn expanded(&self, margin: usize, width: usize, height: usize) -> Self {
+        let x = self.x.saturating_sub(margin);
+        let y = self.y.saturating_sub(margin);
+        Self::new(
+            x,
+            y,
+            self.x + self.width + margin - x,
+            self.y + self.height + margin - y,
+        )
+        .clamped(width, height)
+    }
+}
+
+/// Filtered samples and splatted light for an image, or for a region of it. Sample positions
+/// are always in pixels of the whole image, so a cropped film keeps the camera of the full
+/// frame, and its pixels come out the same as the pixels of a full render would.
+#[derive(Clone, Debug)]
+pub struct Film {
+    image_width: usize,  // Width of the whole image
+    image_height: usize, // Height of the whole image
+    region: Region,      // Pixels of the image kept by the film
+    filter: Filter,
+    sums: Vec<Color>,   // Weighted sum of the samples counting towards every pixel
+    weights: Vec<f64>,  // Sum of the weights of those samples
+    splats: Vec<Color>, // Light splatted onto every pixel, added as it is
+}
+
+impl Film {
+    pub fn new(width: usize, height: usize, filter: Filter) -> Self {
+        Self::for_region(width, height, Region::full(width, height), filter)
+    }
+
+    /// A film keeping only the `region` of a width by height image, such as a crop window
+    /// rendered again with more samples and pasted back into the image.
+    pub fn for_region(width: usize, height: usize, region: Region, filter: Filter) -> Self {
+        let region = region.clamped(width, height);
+        let pixels = region.width * region.height;
+        Self {
+            image_width: width,
+            image_height: height,
+            region,
+            filter,
+            sums: vec![Color::zero(); pixels],
+            weights: vec![0.; pixels],
+            splats: vec![Color::zero(); pixels],
+        }
+    }
+
+    /// Width of the film, which is the width of its region.
+    pub fn width(&self) -> usize {
+        self.region.width
+    }
+
+    /// Height of the film, which is the height of its region.
+    pub fn height(&self) -> usize {
+        self.region.height
+    }
+
+    pub fn region(&self) -> Region {
+        self.region
+    }
+
+    pub fn filter(&self) -> &Filter {
+        &self.filter
+    }
+
+    /// Pixels of the image whose samples reach the film: its region grown by the filter
+    /// radius. Sampling all of them gives the pixels along the crop border the samples from
+    /// outside it they would get in a full render, so a pasted patch blends in seamlessly.
+    pub fn sample_region(&self) -> Region {
+        // A sample in pixel k reaches the center of pixel m when m - k < radius + 0.5.
+        let margin = ((self.filter.radius() + 0.5).ceil() - 1.).max(0.) as usize;
+        self.region
+            .expanded(margin, self.image_width, self.image_height)
+    }
+
+    /// Adds a sample of the image taken at `x`, `y` to every pixel within the filter radius.
+    pub fn add_sample(&mut self, x: f64, y: f64, color: &Color) {
+        for (i, j, weight) in self.footprint(x, y) {
+            if let Some(index) = self.index(i, j) {
+                self.sums[index] += &(color * weight);
+                self.weights[index] += weight;
+            }
+        }
+    }
+
+    /// Adds light arriving at `x`, `y` to the pixels within the filter radius, such as light
+    /// traced from the light sources to the camera. Splatted light isn't averaged with the
+    /// samples: it is spread over the pixels as it is, and should already be divided by the
+    /// number of paths traced.
+    pub fn add_splat(&mut self, x: f64, y: f64, color: &Color) {
+        // Weights are normalized over the whole image, so that splats near the crop border
+        // leave the same light in the region as in a full render.
+        let footprint: Vec<_> = self.footprint(x, y).collect();
+        let total: f64 = footprint.iter().map(|(_, _, weight)| weight).sum();
+        if total == 0. {
+            return;
+        }
+        for (i, j, weight) in footprint {
+            if let Some(index) = self.index(i, j) {
+                self.splats[index] += &(color * (weight / total));
+            }
+        }
+    }
+
+    // Pixels of the whole image within the filter radius of the position x, y, with their
+    // filter weight.
+    fn footprint(&self, x: f64, y: f64) -> impl Iterator<Item = (usize, usize, f64)> {
+        let filter = self.filter;
+        let radius = filter.radius();
+        let range = |center: f64, size: usize| {
+            let first = (center - 0.5 - radius).ceil().max(0.) as usize;
+            let last = (center - 0.5 + radius).floor().min(size as f64 - 1.);
+            let end = if last < 0. { 0 } else { last as usize + 1 };
+            first..end.max(first)
+        };
+        let columns = range(x, self.image_width);
+        range(y, self.image_height).flat_map(move |j| {
+            columns.clone().filter_map(move |i| {
+                let weight = filter.evaluate(i as f64 + 0.5 - x, j as f64 + 0.5 - y);
+                (weight != 0.).then_some((i, j, weight))
+            })
+        })
+    }
+
+    // Index in the film of the pixel i, j of the image, if it is in the region.
+    fn index(&self, i: usize, j: usize) -> Option<usize> {
+        self.region
+            .contains(i, j)
+            .then(|| (j - self.region.y) * self.region.width + i - self.region.x)
+    }
+
+    /// The filtered image of the region with the splatted light added. Negative filter lobes
+    /// can make a pixel negative next to a bright edge, those are clamped to black.
+    pub fn to_image(&self) -> Image {
+        let mut image = Image::new(self.width(), self.height());
+        for j in 0..self.height() {
+            for i in 0..self.width() {
+                let index = j * self.width() + i;
+                let weight = self.weights[index];
+                let filtered = if weight > 0. {
+                    &self.sums[index] / weight
+                } else {
+                    Color::zero()
+                };
+                let color = filtered + &self.splats[index];
+                image.set(
+                    i,
+                    j,
+                    Color::new(color.x.max(0.), color.y.max(0.), color.z.max(0.)),
+                );
+            }
+        }
+        image
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_peak_at_the_pixel_center() {
+        for filter in [
+            Filter::default(),
+            Filter::tent(),
+            Filter::gaussian(),
+            Filter::mitchell(),
+            Filter::lanczos(),
+        ] {
+            let center = filter.evaluate(0., 0.);
+            assert!(center > 0., "{:?}", filter);
+            assert!(filter.evaluate(0.3, 0.2) <= center, "{:?}", filter);
+            let radius = filter.radius();
+            assert_eq!(filter.evaluate(radius + 0.01, 0.), 0., "{:?}", filter);
+        }
+        // The Mitchell-Netravali filter is continuous where its two cubics meet.
+        let mitchell = Filter::mitchell();
+        assert!((mitchell.evaluate_1d(0.9999) - mitchell.evaluate_1d(1.0001)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn box_filter_averages_every_pixel_on_its_own() {
+        let mut film = Film::new(2, 1, Filter::default());
+        film.add_sample(0.2, 0.5, &Color::new(1., 0., 0.));
+        film.add_sample(0.7, 0.5, &Color::new(0., 0., 1.));
+        film.add_sample(1.5, 0.5, &Color::one());
+        let image = film.to_image();
+        assert_eq!(image.get(0, 0), &Color::new(0.5, 0., 0.5));
+        assert_eq!(image.get(1, 0), &Color::one());
+    }
+
+    #[test]
+    fn wide_filters_reach_the_neighboring_pixels() {
+        let mut film = Film::new(3, 1, Filter::tent());
+        film.add_sample(1.5, 0.5, &Color::one());
+        // Halfway between the first two pixels, with half the weight of a centered sample.
+        film.add_sample(1., 0.5, &Color::zero());
+        let image = film.to_image();
+        assert_eq!(image.get(0, 0), &Color::zero());
+        assert!((image.get(1, 0).x - 2. / 3.).abs() < 1e-12);
+        // No sample is within the filter radius of the last pixel.
+        assert_eq!(image.get(2, 0), &Color::zero());
+    }
+
+    #[test]
+    fn splats_keep_their_energy() {
+        let mut film = Film::new(4, 4, Filter::gaussian());
+        film.add_splat(2., 2., &Color::one());
+        film.add_splat(0.1, 0.1, &Color::one());
+        let image = film.to_image();
+        let total: f64 = image.pixels().iter().map(|color| color.x).sum();
+        assert!((total - 2.).abs() < 1e-9, "{}", total);
+    }
+
+    #[test]
+    fn regions_stay_inside_the_image() {
+        let region = Region::new(6, 1, 10, 2).clamped(8, 8);
+        assert_eq!(region, Region::new(6, 1, 2, 2));
+        assert_eq!(region.expanded(2, 8, 8), Region::new(4, 0, 4, 5));
+        assert!(region.contains(7, 2) && !region.contains(5, 2));
+    }
+
+    #[test]
+    fn cropped_film_matches_the_full_film() {
+        let filter = Filter::mitchell();
+        let mut full = Film::new(12, 10, filter);
+        let mut cropped = Film::for_region(12, 10, Region::new(3, 4, 5, 3), filter);
+        let sampled = cropped.sample_region();
+        for j in 0..10 {
+            for i in 0..12 {
+                for (dx, dy) in [(0.2, 0.3), (0.7, 0.6), (0.4, 0.9)] {
+                    let (x, y) = (i as f64 + dx, j as f64 + dy);
+                    let color = Color::new(((i * 7 + j * 3) % 5) as f64, dx, dy);
+                    full.add_sample(x, y, &color);
+                    full.add_splat(x, y, &(&color * 0.1));
+                    // Samples outside the sample region can't reach the cropped film.
+                    if sampled.contains(i, j) {
+                        cropped.add_sample(x, y, &color);
+                        cropped.add_splat(x, y, &(&color * 0.1));
+                    }
+                }
+            }
+        }
+
+        let (full, cropped) = (full.to_image(), cropped.to_image());
+        for j in 0..3 {
+            for i in 0..5 {
+                let difference = full.get(i + 3, j + 4) - cropped.get(i, j);
+                assert!(difference.length() < 1e-12, "pixel {}, {}", i, j);
+            }
+        }
+    }
+}
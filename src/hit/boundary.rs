@@ -0,0 +1,73 @@
+use crate::aabb::Aabb;
+use crate::checkpoint::Fingerprint;
+use crate::hit::hittable::{HitRecord, Hittable};
+use crate::materials::Material;
+use crate::medium::Medium;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+use std::ops::Range;
+use std::rc::Rc;
+
+/// Fills a closed object with a medium, which rays are in between entering and leaving the
+/// object. The surface keeps the material of the object, such as glass holding a liquid, or is
+/// a pure interface letting light through, such as the walls of a fog bank.
+pub struct MediumBoundary {
+    object: Box<dyn Hittable>,
+    medium: Rc<Medium>,
+    interface: bool, // Whether the surface lets light through unchanged
+}
+
+impl MediumBoundary {
+    pub fn new<H: Hittable + 'static>(object: H, medium: Rc<Medium>) -> Self {
+        Self {
+            object: Box::new(object),
+            medium,
+            interface: false,
+        }
+    }
+
+    /// A boundary without a visible surface, only marking where the medium begins.
+    pub fn interface<H: Hittable + 'static>(object: H, medium: Rc<Medium>) -> Self {
+        Self {
+            interface: true,
+            ..Self::new(object, medium)
+        }
+    }
+}
+
+impl Hittable for MediumBoundary {
+    fn normal(&self, hit_point: &Point3, shutter_time: f64) -> Vec3 {
+        self.object.normal(hit_point, shutter_time)
+    }
+
+    fn material(&self) -> Material {
+        if self.interface {
+            Material::Interface
+        } else {
+            self.object.material()
+        }
+    }
+
+    fn uv(&self, hit_point: &Point3, shutter_time: f64) -> (f64, f64) {
+        self.object.uv(hit_point, shutter_time)
+    }
+
+    fn hit(&self, ray: &Ray, time_range: &Range<f64>) -> Option<HitRecord> {
+        let mut record = self.object.hit(ray, time_range)?;
+        if self.interface {
+            record.material = Material::Interface;
+        }
+        record.medium = Some(self.medium.clone());
+        Some(record)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.object.bounding_box()
+    }
+
+    fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        self.object.fingerprint(fingerprint);
+        self.medium.fingerprint(fingerprint);
+        fingerprint.add_bytes(&[self.interface as u8]);
+    }
+}
@@ -0,0 +1,127 @@
+use crate::aabb::Aabb;
+use crate::checkpoint::Fingerprint;
+use crate::hit::hittable::{HitRecord, Hittable};
+use crate::materials::Material;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+use std::ops::Range;
+
+/// Bounding volume hierarchy: a binary tree of bounding boxes, which lets a ray skip all the
+/// objects inside of every box it misses.
+pub enum Bvh {
+    Empty,
+    Leaf(Box<dyn Hittable>),
+    Node {
+        bounds: Aabb,
+        left: Box<Bvh>,
+        right: Box<Bvh>,
+    },
+}
+
+impl Bvh {
+    /// Builds the tree by repeatedly splitting the objects in half along the axis on which
+    /// their centers are spread the most.
+    pub fn new(mut objects: Vec<Box<dyn Hittable>>) -> Self {
+        match objects.len() {
+            0 => Self::Empty,
+            1 => Self::Leaf(objects.pop().unwrap()),
+            _ => {
+                // Boxes can be costly to compute, so they are computed once for the sort.
+                let boxes: Vec<Aabb> = objects.iter().map(|item| item.bounding_box()).collect();
+                let bounds = boxes
+                    .iter()
+                    .fold(Aabb::empty(), |bounds, item| bounds.union(item));
+                let centers: Vec<Point3> = boxes.iter().map(Aabb::center).collect();
+                let axis = centers
+                    .iter()
+                    .fold(Aabb::empty(), |span, center| {
+                        span.union(&Aabb::new(center, center))
+                    })
+                    .longest_axis();
+                let mut sorted: Vec<_> = centers
+                    .iter()
+                    .map(|center| center[axis])
+                    .zip(objects)
+                    .collect();
+                sorted.sort_by(|(l, _), (r, _)| l.total_cmp(r));
+                let mut objects: Vec<_> = sorted.into_iter().map(|(_, item)| item).collect();
+                let right = objects.split_off(objects.len() / 2);
+                Self::Node {
+                    bounds,
+                    left: Box::new(Self::new(objects)),
+                    right: Box::new(Self::new(right)),
+                }
+            }
+        }
+    }
+}
+
+impl Hittable for Bvh {
+    fn normal(&self, _hit_point: &Point3, _shutter_time: f64) -> Vec3 {
+        unreachable!("a BVH has no surface of its own, its hits come from the leaves")
+    }
+
+    fn material(&self) -> Material {
+        unreachable!("a BVH has no material of its own, its hits come from the leaves")
+    }
+
+    fn hit(&self, ray: &Ray, time_range: &Range<f64>) -> Option<HitRecord> {
+        match self {
+            Self::Empty => None,
+            Self::Leaf(object) => object.hit(ray, time_range),
+            Self::Node {
+                bounds,
+                left,
+                right,
+            } => {
+                if !bounds.hit(ray, time_range) {
+                    return None;
+                }
+                let left_hit = left.hit(ray, time_range);
+                let end = left_hit.as_ref().map_or(time_range.end, |hit| hit.time);
+                let right_hit = right.hit(ray, &(time_range.start..end));
+                right_hit.or(left_hit)
+            }
+        }
+    }
+
+    fn transmittance(&self, ray: &Ray, time_range: &Range<f64>) -> f64 {
+        match self {
+            Self::Empty => 1.,
+            Self::Leaf(object) => object.transmittance(ray, time_range),
+            Self::Node {
+                bounds,
+                left,
+                right,
+            } => {
+                if !bounds.hit(ray, time_range) {
+                    return 1.;
+                }
+                match left.transmittance(ray, time_range) {
+                    0. => 0.,
+                    left => left * right.transmittance(ray, time_range),
+                }
+            }
+        }
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        match self {
+            Self::Empty => Aabb::empty(),
+            Self::Leaf(object) => object.bounding_box(),
+            Self::Node { bounds, .. } => bounds.clone(),
+        }
+    }
+
+    fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        match self {
+            Self::Empty => fingerprint.add_bytes(b"empty"),
+            Self::Leaf(object) => object.fingerprint(fingerprint),
+            Self::Node { left, right, .. } => {
+                fingerprint.add_bytes(b"node");
+                left.fingerprint(fingerprint);
+                right.fingerprint(fingerprint);
+            }
+        }
+    }
+}
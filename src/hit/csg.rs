@@ -0,0 +1,194 @@
+use crate::aabb::Aabb;
+use crate::checkpoint::Fingerprint;
+use crate::hit::hittable::{HitRecord, Hittable};
+use crate::hit::solid::{Solid, Span};
+use crate::materials::Material;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+use std::ops::Range;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Operation {
+    Union,
+    Intersection,
+    /// Everything in the left solid that is not in the right one.
+    Difference,
+}
+
+impl Operation {
+    fn contains(self, in_left: bool, in_right: bool) -> bool {
+        match self {
+            Self::Union => in_left || in_right,
+            Self::Intersection => in_left && in_right,
+            Self::Difference => in_left && !in_right,
+        }
+    }
+}
+
+/// Constructive solid geometry: a solid made by combining two solids with a boolean operation.
+/// The surfaces keep the materials of the solid they come from.
+pub struct Csg {
+    operation: Operation,
+    left: Box<dyn Solid>,
+    right: Box<dyn Solid>,
+    bounds: Aabb, // Box of the result, checked before tracing the operands
+}
+
+impl Csg {
+    pub fn new<L: Solid + 'static, R: Solid + 'static>(
+        operation: Operation,
+        left: L,
+        right: R,
+    ) -> Self {
+        let bounds = match operation {
+            Operation::Union => left.bounding_box().union(&right.bounding_box()),
+            Operation::Intersection => left.bounding_box().intersection(&right.bounding_box()),
+            Operation::Difference => left.bounding_box(),
+        };
+        Self {
+            operation,
+            left: Box::new(left),
+            right: Box::new(right),
+            bounds,
+        }
+    }
+
+    pub fn union<L: Solid + 'static, R: Solid + 'static>(left: L, right: R) -> Self {
+        Self::new(Operation::Union, left, right)
+    }
+
+    pub fn intersection<L: Solid + 'static, R: Solid + 'static>(left: L, right: R) -> Self {
+        Self::new(Operation::Intersection, left, right)
+    }
+
+    pub fn difference<L: Solid + 'static, R: Solid + 'static>(left: L, right: R) -> Self {
+        Self::new(Operation::Difference, left, right)
+    }
+}
+
+impl Solid for Csg {
+    fn spans(&self, ray: &Ray) -> Vec<Span> {
+        combine(self.left.spans(ray), self.right.spans(ray), self.operation)
+    }
+}
+
+impl Hittable for Csg {
+    fn normal(&self, _hit_point: &Point3, _shutter_time: f64) -> Vec3 {
+        unreachable!("a CSG solid has no surface of its own, its hits come from the operands")
+    }
+
+    fn material(&self) -> Material {
+        unreachable!("a CSG solid has no material of its own, its hits come from the operands")
+    }
+
+    fn hit(&self, ray: &Ray, time_range: &Range<f64>) -> Option<HitRecord> {
+        if !self.bounds.hit(ray, time_range) {
+            return None;
+        }
+        self.spans(ray)
+            .into_iter()
+            .flat_map(|span| [span.enter, span.exit])
+            .find(|record| time_range.contains(&record.time))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bounds.clone()
+    }
+
+    fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        fingerprint.add_bytes(&[self.operation as u8]);
+        self.left.fingerprint(fingerprint);
+        self.right.fingerprint(fingerprint);
+    }
+}
+
+// Walks through the boundaries of both operands along the ray, keeping track of which
+// operands the ray is inside of, and emits a span for every stretch inside the result.
+fn combine(left: Vec<Span>, right: Vec<Span>, operation: Operation) -> Vec<Span> {
+    let mut boundaries = Vec::with_capacity(2 * (left.len() + right.len()));
+    for (spans, from_left) in [(left, true), (right, false)] {
+        for span in spans {
+            boundaries.push((span.enter, from_left, true));
+            boundaries.push((span.exit, from_left, false));
+        }
+    }
+    boundaries.sort_by(|l, r| l.0.time.total_cmp(&r.0.time));
+
+    let (mut in_left, mut in_right) = (false, false);
+    let mut enter: Option<HitRecord> = None;
+    let mut spans = Vec::new();
+    for (mut record, from_left, entering) in boundaries {
+        let was_inside = operation.contains(in_left, in_right);
+        if from_left {
+            in_left = entering;
+        } else {
+            in_right = entering;
+        }
+        let inside = operation.contains(in_left, in_right);
+        if inside == was_inside {
+            continue;
+        }
+
+        // The normal of a hit always faces the ray, so when the exit from the right solid
+        // becomes an entry into a difference, only the side the ray comes from changes.
+        record.outside = inside;
+        if inside {
+            enter = Some(record);
+        } else if let Some(enter) = enter.take() {
+            if enter.time < record.time {
+                spans.push(Span {
+                    enter,
+                    exit: record,
+                });
+            }
+        }
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::hit::cuboid::Cuboid;
+    use crate::hit::sphere::Sphere;
+    use crate::materials::Lambertian;
+
+    fn material() -> Material {
+        Lambertian::new(Color::one()).into()
+    }
+
+    #[test]
+    fn difference_hits_the_carved_out_surface() {
+        // A unit cube with a sphere carved out of its front face.
+        let cube = Cuboid::new(&Point3::new(-1., -1., -1.), &Point3::one(), material());
+        let sphere = Sphere::new(Point3::new(0., 0., 1.), 0.5, material());
+        let carved = Csg::difference(cube, sphere);
+        let all_time = 0.0..f64::INFINITY;
+
+        let through_hole = Ray::new(Point3::new(0., 0., 5.), Vec3::new(0., 0., -1.));
+        let record = carved.hit(&through_hole, &all_time).unwrap();
+        assert!((record.time - 4.5).abs() < 1e-12);
+        assert!(record.outside);
+        assert_eq!(record.normal, Vec3::new(0., 0., 1.));
+
+        let past_hole = Ray::new(Point3::new(0.8, 0., 5.), Vec3::new(0., 0., -1.));
+        let record = carved.hit(&past_hole, &all_time).unwrap();
+        assert!((record.time - 4.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn intersection_keeps_only_the_overlap() {
+        // A lens from two overlapping spheres.
+        let left = Sphere::new(Point3::new(-0.5, 0., 0.), 1., material());
+        let right = Sphere::new(Point3::new(0.5, 0., 0.), 1., material());
+        let lens = Csg::intersection(left, right);
+
+        let ray = Ray::new(Point3::new(-5., 0., 0.), Vec3::new(1., 0., 0.));
+        let spans = lens.spans(&ray);
+        assert_eq!(spans.len(), 1);
+        assert!((spans[0].enter.time - 4.5).abs() < 1e-12);
+        assert!((spans[0].exit.time - 5.5).abs() < 1e-12);
+        assert!(spans[0].enter.outside && !spans[0].exit.outside);
+    }
+}
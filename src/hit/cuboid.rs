@@ -0,0 +1,145 @@
+use crate::aabb::Aabb;
+use crate::hit::hittable::{HitRecord, Hittable};
+use crate::hit::quad::Quad;
+use crate::hit::solid::{Solid, Span};
+use crate::materials::Material;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+use std::cmp::Ordering;
+use std::ops::Range;
+
+/// An axis-aligned box made of six quads, facing outwards.
+pub struct Cuboid {
+    sides: [Quad; 6],
+    bounds: Aabb,
+}
+
+impl Cuboid {
+    /// The box with two opposite corners at `a` and `b`.
+    pub fn new(a: &Point3, b: &Point3, material: Material) -> Self {
+        let bounds = Aabb::new(a, b);
+        let (min, max) = (&bounds.min, &bounds.max);
+        let dx = Vec3::new(max.x - min.x, 0., 0.);
+        let dy = Vec3::new(0., max.y - min.y, 0.);
+        let dz = Vec3::new(0., 0., max.z - min.z);
+
+        let sides = [
+            // front
+            Quad::new(
+                Point3::new(min.x, min.y, max.z),
+                dx.clone(),
+                dy.clone(),
+                material.clone(),
+            ),
+            // right
+            Quad::new(
+                Point3::new(max.x, min.y, max.z),
+                -&dz,
+                dy.clone(),
+                material.clone(),
+            ),
+            // back
+            Quad::new(
+                Point3::new(max.x, min.y, min.z),
+                -&dx,
+                dy.clone(),
+                material.clone(),
+            ),
+            // left
+            Quad::new(min.clone(), dz.clone(), dy, material.clone()),
+            // top
+            Quad::new(
+                Point3::new(min.x, max.y, max.z),
+                dx.clone(),
+                -&dz,
+                material.clone(),
+            ),
+            // bottom
+            Quad::new(min.clone(), dx, dz, material),
+        ];
+        Self { sides, bounds }
+    }
+
+    pub fn sides(&self) -> &[Quad; 6] {
+        &self.sides
+    }
+
+    // The side a point on the surface of the box lies on.
+    fn side_at(&self, point: &Point3) -> &Quad {
+        self.sides
+            .iter()
+            .min_by(|l, r| l.plane_distance(point).total_cmp(&r.plane_distance(point)))
+            .unwrap()
+    }
+}
+
+impl Hittable for Cuboid {
+    fn normal(&self, hit_point: &Point3, shutter_time: f64) -> Vec3 {
+        self.side_at(hit_point).normal(hit_point, shutter_time)
+    }
+
+    fn material(&self) -> Material {
+        self.sides[0].material()
+    }
+
+    fn uv(&self, hit_point: &Point3, shutter_time: f64) -> (f64, f64) {
+        self.side_at(hit_point).uv(hit_point, shutter_time)
+    }
+
+    fn hit(&self, ray: &Ray, time_range: &Range<f64>) -> Option<HitRecord> {
+        self.sides
+            .iter()
+            .filter_map(|side| side.hit(ray, time_range))
+            .min_by(|l, r| l.time.partial_cmp(&r.time).unwrap_or(Ordering::Equal))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bounds.padded(1e-4)
+    }
+}
+
+impl Solid for Cuboid {
+    // Slab test over the whole line of the ray.
+    fn spans(&self, ray: &Ray) -> Vec<Span> {
+        let mut enter = f64::NEG_INFINITY;
+        let mut exit = f64::INFINITY;
+        for axis in 0..3 {
+            let inverse_direction = 1. / ray.direction[axis];
+            let t0 = (self.bounds.min[axis] - ray.origin[axis]) * inverse_direction;
+            let t1 = (self.bounds.max[axis] - ray.origin[axis]) * inverse_direction;
+            if t0.is_nan() || t1.is_nan() {
+                // Parallel to the slab and exactly on one of its planes.
+                return Vec::new();
+            }
+            enter = enter.max(t0.min(t1));
+            exit = exit.min(t0.max(t1));
+        }
+        if exit <= enter {
+            return Vec::new();
+        }
+        vec![Span {
+            enter: HitRecord::new(ray, self, enter),
+            exit: HitRecord::new(ray, self, exit),
+        }]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::materials::Lambertian;
+
+    #[test]
+    fn ray_hits_the_nearest_side() {
+        let material = Lambertian::new(Color::one()).into();
+        let cuboid = Cuboid::new(&Point3::new(-1., -1., -1.), &Point3::one(), material);
+        let ray = Ray::new(Point3::new(0.5, 0., 5.), Vec3::new(0., 0., -1.));
+
+        let record = cuboid.hit(&ray, &(0.0..f64::INFINITY)).unwrap();
+        assert!((record.time - 4.).abs() < 1e-12);
+        assert_eq!(record.normal, Vec3::new(0., 0., 1.));
+        assert!(record.outside);
+        assert!((record.u - 0.75).abs() < 1e-12 && (record.v - 0.5).abs() < 1e-12);
+    }
+}
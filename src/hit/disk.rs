@@ -0,0 +1,73 @@
+use crate::aabb::Aabb;
+use crate::hit::hittable::{HitRecord, Hittable};
+use crate::hit::plane::intersect_plane;
+use crate::materials::Material;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+use std::f64::consts::PI;
+use std::ops::Range;
+
+/// A flat disk, like a round area light or the cap of a cylinder.
+pub struct Disk {
+    center: Point3,
+    normal: Vec3,
+    tangent: Vec3,
+    bitangent: Vec3,
+    radius: f64,
+    material: Material,
+}
+
+impl Disk {
+    pub fn new(center: Point3, normal: Vec3, radius: f64, material: Material) -> Self {
+        let normal = normal.unit();
+        let (tangent, bitangent) = normal.orthonormal_basis();
+        Self {
+            center,
+            normal,
+            tangent,
+            bitangent,
+            radius: radius.max(0.),
+            material,
+        }
+    }
+}
+
+impl Hittable for Disk {
+    fn normal(&self, _hit_point: &Point3, _shutter_time: f64) -> Vec3 {
+        self.normal.clone()
+    }
+
+    fn material(&self) -> Material {
+        self.material.clone()
+    }
+
+    // The angle around the center gives u, the distance from the center relative to the radius
+    // gives v.
+    fn uv(&self, hit_point: &Point3, _shutter_time: f64) -> (f64, f64) {
+        let offset = hit_point - &self.center;
+        let angle = offset.dot(&self.bitangent).atan2(offset.dot(&self.tangent));
+        let u = (angle + PI) / (2. * PI);
+        let v = offset.length() / self.radius;
+        (u, v)
+    }
+
+    fn hit(&self, ray: &Ray, time_range: &Range<f64>) -> Option<HitRecord> {
+        let d = self.normal.dot(&self.center);
+        let time = intersect_plane(&self.normal, d, ray, time_range)?;
+        let offset = ray.position_at(time) - &self.center;
+        if offset.length_squared() > self.radius * self.radius {
+            return None;
+        }
+        Some(HitRecord::new(ray, self, time))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        // The extent of a disk along an axis shrinks with how much the normal points along it.
+        let extent = Vec3::new(
+            self.radius * (1. - self.normal.x * self.normal.x).max(0.).sqrt(),
+            self.radius * (1. - self.normal.y * self.normal.y).max(0.).sqrt(),
+            self.radius * (1. - self.normal.z * self.normal.z).max(0.).sqrt(),
+        );
+        Aabb::new(&(&self.center - &extent), &(&self.center + &extent)).padded(1e-4)
+    }
+}
@@ -1,7 +1,11 @@
+use crate::aabb::Aabb;
+use crate::checkpoint::Fingerprint;
 use crate::materials::Material;
+use crate::medium::Medium;
 use crate::ray::Ray;
 use crate::vec3::{Point3, Vec3};
 use std::ops::Range;
+use std::rc::Rc;
 
 pub struct HitRecord {
     pub point: Point3,
@@ -9,28 +13,77 @@ pub struct HitRecord {
     pub material: Material,
     pub time: f64,
     pub outside: bool,
+    pub u: f64, // Surface coordinates of the hit point, for textures
+    pub v: f64,
+    pub medium: Option<Rc<Medium>>, // Medium filling the inside of the surface, if any
 }
 
 impl HitRecord {
     pub fn new<H: Hittable>(ray: &Ray, hittable: &H, time: f64) -> Self {
         let hit_point = ray.position_at(time);
-        let normal = hittable.normal(&hit_point);
+        let normal = hittable.normal(&hit_point, ray.shutter_time);
         let outside = ray.direction.dot(&normal) < 0.;
+        let (u, v) = hittable.uv(&hit_point, ray.shutter_time);
         Self {
             point: hit_point,
             normal: if outside { normal } else { -normal },
             material: hittable.material(),
             time,
             outside,
+            u,
+            v,
+            medium: None,
+        }
+    }
+
+    /// A scattering event inside a medium, away from any surface.
+    pub fn in_medium(ray: &Ray, time: f64, material: Material) -> Self {
+        Self {
+            point: ray.position_at(time),
+            normal: -ray.direction.unit(),
+            material,
+            time,
+            outside: true,
+            u: 0.,
+            v: 0.,
+            medium: None,
         }
     }
 }
 
 pub trait Hittable {
-    /// This should always return a vector that has a unit length.
-    fn normal(&self, hit_point: &Point3) -> Vec3;
+    /// This should always return a vector that has a unit length. Objects that move during
+    /// the shutter interval use `shutter_time` to find where they were when they were hit.
+    fn normal(&self, hit_point: &Point3, shutter_time: f64) -> Vec3;
 
     fn material(&self) -> Material;
 
+    /// Surface coordinates of a point on the surface, usually both in [0, 1].
+    fn uv(&self, _hit_point: &Point3, _shutter_time: f64) -> (f64, f64) {
+        (0., 0.)
+    }
+
     fn hit(&self, ray: &Ray, time_range: &Range<f64>) -> Option<HitRecord>;
+
+    /// Fraction of the light passing through the object along the ray within `time_range`,
+    /// for shadow rays and other visibility queries between two points. Surfaces block all of
+    /// it, participating media estimate how much they let through.
+    fn transmittance(&self, ray: &Ray, time_range: &Range<f64>) -> f64 {
+        if self.hit(ray, time_range).is_some() {
+            0.
+        } else {
+            1.
+        }
+    }
+
+    /// A box containing the object over the whole shutter interval.
+    fn bounding_box(&self) -> Aabb;
+
+    /// Adds what changes how the object renders to the fingerprint of the scene, which keeps
+    /// checkpoints from being resumed against another scene. Its box and its material by
+    /// default, which objects holding others or changing within their box should extend.
+    fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        fingerprint.add_aabb(&self.bounding_box());
+        self.material().fingerprint(fingerprint);
+    }
 }
@@ -0,0 +1,251 @@
+use crate::aabb::Aabb;
+use crate::checkpoint::Fingerprint;
+use crate::hit::hittable::{HitRecord, Hittable};
+use crate::hit::solid::{Solid, Span};
+use crate::materials::Material;
+use crate::matrix::{Matrix4, Transform};
+use crate::motion::TransformMotion;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+use std::ops::Range;
+use std::rc::Rc;
+
+/// Places a transformed copy of an object in the scene.
+///
+/// The object is shared rather than owned, so the same object, be it a single sphere or a
+/// whole BVH, can be placed any number of times while being stored only once. Instances of
+/// solids are solids themselves, so they can be combined with CSG.
+pub struct Instance<H: Hittable + ?Sized = dyn Hittable> {
+    object: Rc<H>,
+    transform: Transform,
+    normal_matrix: Matrix4, // Inverse transpose of the transform, which normals go through
+}
+
+impl<H: Hittable + ?Sized> Instance<H> {
+    pub fn new(object: Rc<H>, transform: Transform) -> Self {
+        let normal_matrix = transform.inverse.transpose();
+        Self {
+            object,
+            transform,
+            normal_matrix,
+        }
+    }
+
+    pub fn transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    // The direction isn't normalized, so that hit times in object space are the same as in
+    // world space.
+    fn to_object(&self, ray: &Ray) -> Ray {
+        ray.scattered(
+            self.transform.inverse.transform_point(&ray.origin),
+            self.transform.inverse.transform_vector(&ray.direction),
+        )
+    }
+
+    fn to_world(&self, mut record: HitRecord) -> HitRecord {
+        record.point = self.transform.transform_point(&record.point);
+        record.normal = self.normal_matrix.transform_vector(&record.normal).unit();
+        record
+    }
+}
+
+impl<H: Hittable> Instance<H> {
+    /// Wraps a single object that doesn't need to be shared.
+    pub fn of(object: H, transform: Transform) -> Self {
+        Self::new(Rc::new(object), transform)
+    }
+}
+
+impl<H: Hittable + ?Sized> Hittable for Instance<H> {
+    fn normal(&self, hit_point: &Point3, shutter_time: f64) -> Vec3 {
+        let object_point = self.transform.inverse.transform_point(hit_point);
+        let normal = self.object.normal(&object_point, shutter_time);
+        self.normal_matrix.transform_vector(&normal).unit()
+    }
+
+    fn material(&self) -> Material {
+        self.object.material()
+    }
+
+    fn uv(&self, hit_point: &Point3, shutter_time: f64) -> (f64, f64) {
+        let object_point = self.transform.inverse.transform_point(hit_point);
+        self.object.uv(&object_point, shutter_time)
+    }
+
+    fn hit(&self, ray: &Ray, time_range: &Range<f64>) -> Option<HitRecord> {
+        let record = self.object.hit(&self.to_object(ray), time_range)?;
+        Some(self.to_world(record))
+    }
+
+    fn transmittance(&self, ray: &Ray, time_range: &Range<f64>) -> f64 {
+        self.object.transmittance(&self.to_object(ray), time_range)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.object.bounding_box().transformed(&self.transform)
+    }
+
+    fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        for value in self.transform.matrix.rows.iter().flatten() {
+            fingerprint.add_f64(*value);
+        }
+        self.object.fingerprint(fingerprint);
+    }
+}
+
+impl<S: Solid + ?Sized> Solid for Instance<S> {
+    fn spans(&self, ray: &Ray) -> Vec<Span> {
+        self.object
+            .spans(&self.to_object(ray))
+            .into_iter()
+            .map(|span| Span {
+                enter: self.to_world(span.enter),
+                exit: self.to_world(span.exit),
+            })
+            .collect()
+    }
+}
+
+/// Places a copy of an object in the scene whose transform changes while the shutter is open,
+/// so that it is motion blurred as it turns, grows or moves.
+pub struct MovingInstance<H: Hittable + ?Sized = dyn Hittable> {
+    object: Rc<H>,
+    motion: TransformMotion,
+}
+
+impl<H: Hittable + ?Sized> MovingInstance<H> {
+    pub fn new(object: Rc<H>, motion: TransformMotion) -> Self {
+        Self { object, motion }
+    }
+
+    pub fn motion(&self) -> &TransformMotion {
+        &self.motion
+    }
+
+    // Like `Instance::to_object`, with the transform at the time of the ray.
+    fn to_object(ray: &Ray, transform: &Transform) -> Ray {
+        ray.scattered(
+            transform.inverse.transform_point(&ray.origin),
+            transform.inverse.transform_vector(&ray.direction),
+        )
+    }
+
+    fn to_world(mut record: HitRecord, transform: &Transform) -> HitRecord {
+        record.point = transform.transform_point(&record.point);
+        record.normal = transform.transform_normal(&record.normal).unit();
+        record
+    }
+}
+
+impl<H: Hittable> MovingInstance<H> {
+    /// Wraps a single object that doesn't need to be shared.
+    pub fn of(object: H, motion: TransformMotion) -> Self {
+        Self::new(Rc::new(object), motion)
+    }
+}
+
+impl<H: Hittable + ?Sized> Hittable for MovingInstance<H> {
+    fn normal(&self, hit_point: &Point3, shutter_time: f64) -> Vec3 {
+        let transform = self.motion.transform_at(shutter_time);
+        let object_point = transform.inverse.transform_point(hit_point);
+        let normal = self.object.normal(&object_point, shutter_time);
+        transform.transform_normal(&normal).unit()
+    }
+
+    fn material(&self) -> Material {
+        self.object.material()
+    }
+
+    fn uv(&self, hit_point: &Point3, shutter_time: f64) -> (f64, f64) {
+        let transform = self.motion.transform_at(shutter_time);
+        let object_point = transform.inverse.transform_point(hit_point);
+        self.object.uv(&object_point, shutter_time)
+    }
+
+    fn hit(&self, ray: &Ray, time_range: &Range<f64>) -> Option<HitRecord> {
+        let transform = self.motion.transform_at(ray.shutter_time);
+        let record = self
+            .object
+            .hit(&Self::to_object(ray, &transform), time_range)?;
+        Some(Self::to_world(record, &transform))
+    }
+
+    fn transmittance(&self, ray: &Ray, time_range: &Range<f64>) -> f64 {
+        let transform = self.motion.transform_at(ray.shutter_time);
+        self.object
+            .transmittance(&Self::to_object(ray, &transform), time_range)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.motion.swept_box(&self.object.bounding_box())
+    }
+
+    fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        self.motion.fingerprint(fingerprint);
+        self.object.fingerprint(fingerprint);
+    }
+}
+
+impl<S: Solid + ?Sized> Solid for MovingInstance<S> {
+    fn spans(&self, ray: &Ray) -> Vec<Span> {
+        let transform = self.motion.transform_at(ray.shutter_time);
+        self.object
+            .spans(&Self::to_object(ray, &transform))
+            .into_iter()
+            .map(|span| Span {
+                enter: Self::to_world(span.enter, &transform),
+                exit: Self::to_world(span.exit, &transform),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::hit::cuboid::Cuboid;
+    use crate::materials::Lambertian;
+    use crate::matrix::Quaternion;
+    use crate::motion::TransformKeyframe;
+
+    #[test]
+    fn moving_instances_follow_the_shutter_time() {
+        // A long thin bar along X turning a quarter around Y over the shutter interval.
+        let bar = Cuboid::new(
+            &Point3::new(-2., -0.1, -0.1),
+            &Point3::new(2., 0.1, 0.1),
+            Lambertian::new(Color::one()).into(),
+        );
+        let y = Vec3::new(0., 1., 0.);
+        let motion = TransformMotion::keyframed(vec![
+            TransformKeyframe::new(0., Vec3::one(), Quaternion::identity(), Vec3::zero()),
+            TransformKeyframe::new(
+                1.,
+                Vec3::one(),
+                Quaternion::from_axis_angle(&y, 90.),
+                Vec3::zero(),
+            ),
+        ])
+        .unwrap();
+        let bar = MovingInstance::of(bar, motion);
+
+        // Looking down at the tip of the bar at rest, which has turned away at the end.
+        let at = |shutter_time| {
+            Ray::new_at(
+                Point3::new(1.5, 5., 0.),
+                Vec3::new(0., -1., 0.),
+                shutter_time,
+            )
+        };
+        let record = bar.hit(&at(0.), &(0.0..f64::INFINITY)).unwrap();
+        assert!((record.time - 4.9).abs() < 1e-9);
+        assert!((record.normal - y).length() < 1e-9);
+        assert!(bar.hit(&at(1.), &(0.0..f64::INFINITY)).is_none());
+
+        let bounds = bar.bounding_box();
+        assert!(bounds.min.z <= -2. && bounds.max.x >= 2.);
+    }
+}
@@ -1,30 +1,63 @@
+use crate::aabb::Aabb;
+use crate::checkpoint::Fingerprint;
+use crate::hit::bvh::Bvh;
 use crate::hit::hittable::{HitRecord, Hittable};
 use crate::materials::Material;
 use crate::ray::Ray;
 use crate::vec3::{Point3, Vec3};
 use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::ops::Range;
 
 pub struct HittableList {
     list: Vec<Box<dyn Hittable>>,
+    names: HashMap<String, usize>, // Index in the list of the objects added with a name
 }
 
 impl HittableList {
     pub fn new() -> Self {
-        Self { list: Vec::new() }
+        Self {
+            list: Vec::new(),
+            names: HashMap::new(),
+        }
     }
 
     pub fn add<H: Hittable + 'static>(&mut self, object: H) {
         self.list.push(Box::new(object))
     }
 
+    /// Adds an object that can be found again by `name`, replacing the name of any object
+    /// added with the same name before.
+    pub fn add_named<H: Hittable + 'static>(&mut self, name: &str, object: H) {
+        self.names.insert(name.to_string(), self.list.len());
+        self.add(object)
+    }
+
+    /// The object added with `name`, if any.
+    pub fn get(&self, name: &str) -> Option<&dyn Hittable> {
+        self.names.get(name).map(|&index| self.list[index].as_ref())
+    }
+
     pub fn clear(&mut self) {
-        self.list.clear()
+        self.list.clear();
+        self.names.clear()
+    }
+
+    /// Moves all the objects into a bounding volume hierarchy, which is much faster to hit
+    /// than the list once there are more than a handful of objects. Their names are dropped.
+    pub fn into_bvh(self) -> Bvh {
+        Bvh::new(self.list)
+    }
+}
+
+impl Default for HittableList {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl Hittable for HittableList {
-    fn normal(&self, hit_point: &Point3) -> Vec3 {
+    fn normal(&self, _hit_point: &Point3, _shutter_time: f64) -> Vec3 {
         todo!()
     }
 
@@ -38,4 +71,28 @@ impl Hittable for HittableList {
             .filter_map(|item| item.hit(ray, time_range))
             .min_by(|l, r| l.time.partial_cmp(&r.time).unwrap_or(Ordering::Equal))
     }
+
+    fn transmittance(&self, ray: &Ray, time_range: &Range<f64>) -> f64 {
+        let mut transmittance = 1.;
+        for item in &self.list {
+            transmittance *= item.transmittance(ray, time_range);
+            if transmittance == 0. {
+                break;
+            }
+        }
+        transmittance
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.list.iter().fold(Aabb::empty(), |bounds, item| {
+            bounds.union(&item.bounding_box())
+        })
+    }
+
+    fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        fingerprint.add_u64(self.list.len() as u64);
+        for item in &self.list {
+            item.fingerprint(fingerprint);
+        }
+    }
 }
@@ -0,0 +1,112 @@
+use crate::aabb::Aabb;
+use crate::checkpoint::Fingerprint;
+use crate::hit::hittable::{HitRecord, Hittable};
+use crate::materials::Material;
+use crate::random::random;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+use std::ops::Range;
+
+// Gap between the entry into the boundary and the search for the exit.
+const EXIT_OFFSET: f64 = 1e-4;
+
+/// A participating medium of constant density filling a closed convex boundary, such as fog or
+/// smoke. Rays passing through it scatter at an exponentially distributed distance, in a
+/// direction chosen by the phase function, which is usually an `Isotropic` or a
+/// `HenyeyGreenstein` material.
+pub struct ConstantMedium {
+    boundary: Box<dyn Hittable>,
+    negative_inverse_density: f64,
+    phase_function: Material,
+}
+
+impl ConstantMedium {
+    /// `density` is the probability of scattering per unit of distance.
+    pub fn new<H: Hittable + 'static>(boundary: H, density: f64, phase_function: Material) -> Self {
+        Self {
+            boundary: Box::new(boundary),
+            negative_inverse_density: -1. / density,
+            phase_function,
+        }
+    }
+
+    // Times at which the ray enters and leaves the medium within `time_range`, if it goes
+    // through it there.
+    fn inside(&self, ray: &Ray, time_range: &Range<f64>) -> Option<(f64, f64)> {
+        // The ray may start inside the medium, so the boundary is searched along the whole ray.
+        let entry = self
+            .boundary
+            .hit(ray, &(f64::NEG_INFINITY..f64::INFINITY))?;
+        let exit = self
+            .boundary
+            .hit(ray, &(entry.time + EXIT_OFFSET..f64::INFINITY))?;
+
+        let entry_time = entry.time.max(time_range.start);
+        let exit_time = exit.time.min(time_range.end);
+        (entry_time < exit_time).then_some((entry_time, exit_time))
+    }
+}
+
+impl Hittable for ConstantMedium {
+    // Scattering inside a medium doesn't depend on any surface, so any normal will do.
+    fn normal(&self, _hit_point: &Point3, _shutter_time: f64) -> Vec3 {
+        Vec3::new(1., 0., 0.)
+    }
+
+    fn material(&self) -> Material {
+        self.phase_function.clone()
+    }
+
+    fn hit(&self, ray: &Ray, time_range: &Range<f64>) -> Option<HitRecord> {
+        let (entry_time, exit_time) = self.inside(ray, time_range)?;
+        let speed = ray.direction.length();
+        let distance_inside = (exit_time - entry_time) * speed;
+        let hit_distance = self.negative_inverse_density * random().ln();
+        if hit_distance > distance_inside {
+            return None;
+        }
+        Some(HitRecord::new(ray, self, entry_time + hit_distance / speed))
+    }
+
+    /// Exactly the Beer-Lambert law, the density being constant.
+    fn transmittance(&self, ray: &Ray, time_range: &Range<f64>) -> f64 {
+        let Some((entry_time, exit_time)) = self.inside(ray, time_range) else {
+            return 1.;
+        };
+        let distance_inside = (exit_time - entry_time) * ray.direction.length();
+        (distance_inside / self.negative_inverse_density).exp()
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.boundary.bounding_box()
+    }
+
+    fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        self.boundary.fingerprint(fingerprint);
+        fingerprint.add_f64(self.negative_inverse_density);
+        self.phase_function.fingerprint(fingerprint);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::hit::sphere::Sphere;
+    use crate::materials::{Isotropic, Lambertian};
+
+    #[test]
+    fn dense_media_scatter_at_their_boundary() {
+        let boundary = Sphere::new(Point3::zero(), 1., Lambertian::new(Color::one()).into());
+        let phase = Isotropic::new(Color::one());
+        let medium = ConstantMedium::new(boundary, 1e9, phase.into());
+
+        let ray = Ray::new(Point3::new(0., 0., 5.), Vec3::new(0., 0., -2.));
+        let record = medium.hit(&ray, &(0.001..f64::INFINITY)).unwrap();
+        assert!((record.time - 2.).abs() < 1e-6);
+        // From inside the medium, scattering happens right away.
+        let record = medium.hit(&ray, &(2.5..f64::INFINITY)).unwrap();
+        assert!((record.time - 2.5).abs() < 1e-6);
+        assert!(medium.hit(&ray, &(3.5..f64::INFINITY)).is_none());
+    }
+}
@@ -1,3 +1,17 @@
+pub mod boundary;
+pub mod bvh;
+pub mod csg;
+pub mod cuboid;
+pub mod disk;
 pub mod hittable;
+pub mod instance;
 pub mod list;
+pub mod medium;
+pub mod plane;
+pub mod quad;
+pub mod quadric;
+pub mod sdf;
+pub mod solid;
 pub mod sphere;
+pub mod torus;
+pub mod volume;
@@ -0,0 +1,82 @@
+use crate::aabb::Aabb;
+use crate::checkpoint::Fingerprint;
+use crate::hit::hittable::{HitRecord, Hittable};
+use crate::materials::Material;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+use std::ops::Range;
+
+/// An infinite plane, such as the ground of a scene.
+pub struct Plane {
+    point: Point3,
+    normal: Vec3,
+    tangent: Vec3,
+    bitangent: Vec3,
+    material: Material,
+}
+
+impl Plane {
+    /// The plane through `point` facing the `normal` direction.
+    pub fn new(point: Point3, normal: Vec3, material: Material) -> Self {
+        let normal = normal.unit();
+        let (tangent, bitangent) = normal.orthonormal_basis();
+        Self {
+            point,
+            normal,
+            tangent,
+            bitangent,
+            material,
+        }
+    }
+}
+
+impl Hittable for Plane {
+    fn normal(&self, _hit_point: &Point3, _shutter_time: f64) -> Vec3 {
+        self.normal.clone()
+    }
+
+    fn material(&self) -> Material {
+        self.material.clone()
+    }
+
+    // The plane is unbounded, so the coordinates are distances in world units along two
+    // directions in the plane, for textures to tile.
+    fn uv(&self, hit_point: &Point3, _shutter_time: f64) -> (f64, f64) {
+        let offset = hit_point - &self.point;
+        (offset.dot(&self.tangent), offset.dot(&self.bitangent))
+    }
+
+    fn hit(&self, ray: &Ray, time_range: &Range<f64>) -> Option<HitRecord> {
+        let d = self.normal.dot(&self.point);
+        let time = intersect_plane(&self.normal, d, ray, time_range)?;
+        Some(HitRecord::new(ray, self, time))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        Aabb::infinite()
+    }
+
+    fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        fingerprint.add_vec3(&self.point);
+        fingerprint.add_vec3(&self.normal);
+        fingerprint.add_vec3(&self.tangent);
+        self.material.fingerprint(fingerprint);
+    }
+}
+
+/// The ray time at which the ray crosses the plane of points `p` with `normal.dot(p) == d`,
+/// if it does so within `time_range`.
+pub(crate) fn intersect_plane(
+    normal: &Vec3,
+    d: f64,
+    ray: &Ray,
+    time_range: &Range<f64>,
+) -> Option<f64> {
+    let denominator = normal.dot(&ray.direction);
+    // The ray is parallel to the plane.
+    if denominator.abs() < 1e-8 {
+        return None;
+    }
+    let time = (d - normal.dot(&ray.origin)) / denominator;
+    time_range.contains(&time).then_some(time)
+}
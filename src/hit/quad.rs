@@ -0,0 +1,80 @@
+use crate::aabb::Aabb;
+use crate::hit::hittable::{HitRecord, Hittable};
+use crate::hit::plane::intersect_plane;
+use crate::materials::Material;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+use std::ops::Range;
+
+/// A parallelogram with one corner at `corner` and the two sides adjacent to it along `u` and
+/// `v`.
+pub struct Quad {
+    corner: Point3,
+    u: Vec3,
+    v: Vec3,
+    w: Vec3, // Turns a point in the plane into its u, v coordinates
+    normal: Vec3,
+    d: f64,
+    material: Material,
+}
+
+impl Quad {
+    pub fn new(corner: Point3, u: Vec3, v: Vec3, material: Material) -> Self {
+        let n = u.cross(&v);
+        let normal = n.unit();
+        let d = normal.dot(&corner);
+        let w = &n / n.length_squared();
+        Self {
+            corner,
+            u,
+            v,
+            w,
+            normal,
+            d,
+            material,
+        }
+    }
+
+    pub(crate) fn plane_distance(&self, point: &Point3) -> f64 {
+        (self.normal.dot(point) - self.d).abs()
+    }
+
+    // Coordinates of a point in the plane, with the quad spanning [0, 1] on both.
+    fn planar_coordinates(&self, point: &Point3) -> (f64, f64) {
+        let planar = point - &self.corner;
+        let alpha = self.w.dot(&planar.cross(&self.v));
+        let beta = self.w.dot(&self.u.cross(&planar));
+        (alpha, beta)
+    }
+}
+
+impl Hittable for Quad {
+    fn normal(&self, _hit_point: &Point3, _shutter_time: f64) -> Vec3 {
+        self.normal.clone()
+    }
+
+    fn material(&self) -> Material {
+        self.material.clone()
+    }
+
+    fn uv(&self, hit_point: &Point3, _shutter_time: f64) -> (f64, f64) {
+        self.planar_coordinates(hit_point)
+    }
+
+    fn hit(&self, ray: &Ray, time_range: &Range<f64>) -> Option<HitRecord> {
+        let time = intersect_plane(&self.normal, self.d, ray, time_range)?;
+        let (alpha, beta) = self.planar_coordinates(&ray.position_at(time));
+        let unit = 0.0..=1.0;
+        if !unit.contains(&alpha) || !unit.contains(&beta) {
+            return None;
+        }
+        Some(HitRecord::new(ray, self, time))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let opposite = &self.corner + &self.u + &self.v;
+        let diagonal = Aabb::new(&self.corner, &opposite);
+        let other_diagonal = Aabb::new(&(&self.corner + &self.u), &(&self.corner + &self.v));
+        diagonal.union(&other_diagonal).padded(1e-4)
+    }
+}
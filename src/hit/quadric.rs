@@ -0,0 +1,176 @@
+use crate::aabb::Aabb;
+use crate::hit::hittable::{HitRecord, Hittable};
+use crate::materials::Material;
+use crate::polynomial::solve_quadratic;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+use std::f64::consts::PI;
+use std::ops::Range;
+
+/// A surface of revolution around the Y axis whose squared radius is a quadratic function of
+/// the height, `x² + z² = a y² + b y + c`, cut off at two heights and optionally closed by flat
+/// caps there. This covers cylinders, cones, paraboloids and hyperboloids of one sheet.
+///
+/// The shapes are built standing on the origin and pointing up the Y axis, use an `Instance`
+/// to move, rotate or stretch them.
+pub struct Quadric {
+    a: f64,
+    b: f64,
+    c: f64,
+    heights: Range<f64>,
+    capped: bool,
+    material: Material,
+}
+
+impl Quadric {
+    pub fn new(
+        (a, b, c): (f64, f64, f64),
+        heights: Range<f64>,
+        capped: bool,
+        material: Material,
+    ) -> Self {
+        Self {
+            a,
+            b,
+            c,
+            heights,
+            capped,
+            material,
+        }
+    }
+
+    /// A cylinder from height 0 to `height`.
+    pub fn cylinder(radius: f64, height: f64, capped: bool, material: Material) -> Self {
+        Self::new((0., 0., radius * radius), 0.0..height, capped, material)
+    }
+
+    /// A cone with its base at height 0 and its apex at `height`.
+    pub fn cone(radius: f64, height: f64, capped: bool, material: Material) -> Self {
+        // (radius * (1 - y / height))²
+        let r2 = radius * radius;
+        let coefficients = (r2 / (height * height), -2. * r2 / height, r2);
+        Self::new(coefficients, 0.0..height, capped, material)
+    }
+
+    /// A paraboloid with its tip at the origin, `radius` wide at `height`.
+    pub fn paraboloid(radius: f64, height: f64, capped: bool, material: Material) -> Self {
+        let coefficients = (0., radius * radius / height, 0.);
+        Self::new(coefficients, 0.0..height, capped, material)
+    }
+
+    /// A hyperboloid of one sheet centered on the origin, `waist_radius` wide in the middle and
+    /// `end_radius` wide at both ends, `height / 2` above and below.
+    pub fn hyperboloid(
+        waist_radius: f64,
+        end_radius: f64,
+        height: f64,
+        capped: bool,
+        material: Material,
+    ) -> Self {
+        let half_height = height / 2.;
+        let waist = waist_radius * waist_radius;
+        let a = (end_radius * end_radius - waist) / (half_height * half_height);
+        Self::new((a, 0., waist), -half_height..half_height, capped, material)
+    }
+
+    fn squared_radius(&self, y: f64) -> f64 {
+        (self.a * y + self.b) * y + self.c
+    }
+
+    // The cap a point on the surface lies on, if any: -1 for the bottom and 1 for the top.
+    fn cap_at(&self, point: &Point3) -> Option<f64> {
+        if !self.capped {
+            return None;
+        }
+        let epsilon = 1e-7 * (self.heights.end - self.heights.start);
+        let distance_squared = point.x * point.x + point.z * point.z;
+        [(self.heights.start, -1.), (self.heights.end, 1.)]
+            .into_iter()
+            .find(|(y, _)| {
+                (point.y - y).abs() < epsilon
+                    && distance_squared < self.squared_radius(*y) * (1. - 1e-7)
+            })
+            .map(|(_, side)| side)
+    }
+}
+
+impl Hittable for Quadric {
+    fn normal(&self, hit_point: &Point3, _shutter_time: f64) -> Vec3 {
+        if let Some(side) = self.cap_at(hit_point) {
+            return Vec3::new(0., side, 0.);
+        }
+        // Gradient of x² + z² - a y² - b y - c
+        let gradient = Vec3::new(
+            2. * hit_point.x,
+            -2. * self.a * hit_point.y - self.b,
+            2. * hit_point.z,
+        );
+        gradient.unit()
+    }
+
+    fn material(&self) -> Material {
+        self.material.clone()
+    }
+
+    // The angle around the Y axis gives u. On the side v goes from 0 at the bottom to 1 at the
+    // top, on the caps it is the distance from the axis relative to the cap radius.
+    fn uv(&self, hit_point: &Point3, _shutter_time: f64) -> (f64, f64) {
+        let u = ((-hit_point.z).atan2(hit_point.x) + PI) / (2. * PI);
+        let v = match self.cap_at(hit_point) {
+            Some(side) => {
+                let y = if side < 0. {
+                    self.heights.start
+                } else {
+                    self.heights.end
+                };
+                let distance_squared = hit_point.x * hit_point.x + hit_point.z * hit_point.z;
+                (distance_squared / self.squared_radius(y)).sqrt()
+            }
+            None => (hit_point.y - self.heights.start) / (self.heights.end - self.heights.start),
+        };
+        (u, v)
+    }
+
+    fn hit(&self, ray: &Ray, time_range: &Range<f64>) -> Option<HitRecord> {
+        let (o, d) = (&ray.origin, &ray.direction);
+        let a = d.x * d.x + d.z * d.z - self.a * d.y * d.y;
+        let b = 2. * (o.x * d.x + o.z * d.z) - 2. * self.a * o.y * d.y - self.b * d.y;
+        let c = o.x * o.x + o.z * o.z - self.a * o.y * o.y - self.b * o.y - self.c;
+
+        let mut nearest = solve_quadratic(a, b, c)
+            .into_iter()
+            .find(|time| time_range.contains(time) && self.heights.contains(&(o.y + time * d.y)));
+
+        if self.capped && d.y != 0. {
+            for y in [self.heights.start, self.heights.end] {
+                let time = (y - o.y) / d.y;
+                if !time_range.contains(&time) || nearest.is_some_and(|nearest| nearest <= time) {
+                    continue;
+                }
+                let point = ray.position_at(time);
+                if point.x * point.x + point.z * point.z <= self.squared_radius(y) {
+                    nearest = Some(time);
+                }
+            }
+        }
+
+        nearest.map(|time| HitRecord::new(ray, self, time))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let mut widest = self
+            .squared_radius(self.heights.start)
+            .max(self.squared_radius(self.heights.end));
+        if self.a < 0. {
+            let vertex = -self.b / (2. * self.a);
+            if self.heights.contains(&vertex) {
+                widest = widest.max(self.squared_radius(vertex));
+            }
+        }
+        let radius = widest.max(0.).sqrt();
+        Aabb::new(
+            &Point3::new(-radius, self.heights.start, -radius),
+            &Point3::new(radius, self.heights.end, radius),
+        )
+    }
+}
@@ -0,0 +1,140 @@
+use crate::aabb::Aabb;
+use crate::checkpoint::Fingerprint;
+use crate::hit::hittable::{HitRecord, Hittable};
+use crate::materials::Material;
+use crate::ray::Ray;
+use crate::sdf::Sdf;
+use crate::vec3::{Point3, Vec3};
+use std::ops::Range;
+
+/// A surface given by a signed distance field, intersected by sphere tracing: the ray advances
+/// by the distance to the surface, which can't be crossed within that distance, until it gets
+/// closer than `epsilon`.
+pub struct SdfObject {
+    sdf: Box<dyn Sdf>,
+    material: Material,
+    max_steps: u32,  // Steps after which the ray is considered to miss the surface
+    epsilon: f64,    // Distance at which the ray is considered to hit the surface
+    step_scale: f64, // Fraction of the distance advanced at every step
+}
+
+impl SdfObject {
+    pub fn new<S: Sdf + 'static>(sdf: S, material: Material) -> Self {
+        Self {
+            sdf: Box::new(sdf),
+            material,
+            max_steps: 256,
+            epsilon: 1e-4,
+            step_scale: 1.,
+        }
+    }
+
+    /// Shortens the steps, for distance fields that overestimate the distance, like twisted
+    /// or smoothly combined shapes.
+    pub fn with_step_scale(mut self, step_scale: f64) -> Self {
+        self.step_scale = step_scale.clamp(0.01, 1.);
+        self
+    }
+
+    pub fn with_max_steps(mut self, max_steps: u32) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    pub fn with_epsilon(mut self, epsilon: f64) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+}
+
+impl Hittable for SdfObject {
+    fn normal(&self, hit_point: &Point3, _shutter_time: f64) -> Vec3 {
+        self.sdf.gradient(hit_point).unit()
+    }
+
+    fn material(&self) -> Material {
+        self.material.clone()
+    }
+
+    fn uv(&self, hit_point: &Point3, _shutter_time: f64) -> (f64, f64) {
+        self.sdf.uv(hit_point)
+    }
+
+    fn hit(&self, ray: &Ray, time_range: &Range<f64>) -> Option<HitRecord> {
+        let range = self.bounding_box().clip(ray, time_range)?;
+
+        // Marching along a unit direction makes the distances directly usable as steps.
+        let scale = ray.direction.length();
+        let direction = &ray.direction / scale;
+        let position_at = |distance_along: f64| &ray.origin + &(&direction * distance_along);
+        let end = range.end * scale;
+        let mut distance_along = range.start * scale;
+
+        // The ray may start inside the shape, in which case it marches towards the surface from
+        // the inside. A ray leaving the surface, like a scattered one, only hits once it got
+        // away from it.
+        let start = position_at(distance_along);
+        let start_distance = self.sdf.distance(&start);
+        let mut left_surface = start_distance.abs() >= self.epsilon;
+        let sign = if left_surface {
+            start_distance.signum()
+        } else if self.sdf.gradient(&start).dot(&direction) > 0. {
+            1.
+        } else {
+            -1.
+        };
+
+        for _ in 0..self.max_steps {
+            if distance_along >= end {
+                return None;
+            }
+            let point = position_at(distance_along);
+            let distance = sign * self.sdf.distance(&point);
+            if distance < self.epsilon {
+                if left_surface {
+                    return Some(HitRecord::new(ray, self, distance_along / scale));
+                }
+            } else {
+                left_surface = true;
+            }
+            distance_along += distance.max(self.epsilon) * self.step_scale;
+        }
+        None
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.sdf.bounds().expanded(self.epsilon)
+    }
+
+    fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        self.sdf.fingerprint(fingerprint);
+        self.material.fingerprint(fingerprint);
+        fingerprint.add_u64(self.max_steps as u64);
+        fingerprint.add_f64(self.epsilon);
+        fingerprint.add_f64(self.step_scale);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::materials::Lambertian;
+    use crate::sdf::shapes;
+
+    #[test]
+    fn traces_spheres_from_outside_and_inside() {
+        let sphere = shapes::Sphere::new(Point3::new(0., 0., -5.), 1.);
+        let object = SdfObject::new(sphere, Lambertian::new(Color::one()).into());
+        let ray = Ray::new(Point3::zero(), Vec3::new(0., 0., -2.));
+
+        let record = object.hit(&ray, &(0.001..f64::INFINITY)).unwrap();
+        assert!((record.time - 2.).abs() < 1e-3);
+        assert!(record.outside);
+        assert!(object.hit(&ray, &(0.001..1.9)).is_none());
+
+        let from_inside = object.hit(&ray, &(2.5..f64::INFINITY)).unwrap();
+        assert!((from_inside.time - 3.).abs() < 1e-3);
+        assert!(!from_inside.outside);
+    }
+}
@@ -0,0 +1,18 @@
+use crate::hit::hittable::{HitRecord, Hittable};
+use crate::ray::Ray;
+
+/// A stretch of a ray inside a solid, from the hit where the ray enters it to the hit where it
+/// leaves it again.
+pub struct Span {
+    pub enter: HitRecord,
+    pub exit: HitRecord,
+}
+
+/// A closed object with a well defined inside, which CSG can combine with other solids.
+pub trait Solid: Hittable {
+    /// Every span of the ray's line inside the solid, sorted along the ray and not overlapping.
+    ///
+    /// Unlike `hit`, this isn't limited to a time range: spans behind the ray origin are needed
+    /// to tell whether the origin itself is inside.
+    fn spans(&self, ray: &Ray) -> Vec<Span>;
+}
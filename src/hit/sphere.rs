@@ -1,17 +1,44 @@
+use crate::aabb::Aabb;
+use crate::checkpoint::Fingerprint;
 use crate::hit::hittable::{HitRecord, Hittable};
+use crate::hit::solid::{Solid, Span};
 use crate::materials::Material;
+use crate::motion::{Keyframe, Motion, MotionError};
 use crate::ray::Ray;
 use crate::vec3::{Point3, Vec3};
+use std::f64::consts::PI;
 use std::ops::Range;
 
 pub struct Sphere {
-    center: Point3,
+    center: Motion,
     radius: f64,
     material: Material,
 }
 
 impl Sphere {
     pub fn new(center: Point3, radius: f64, material: Material) -> Self {
+        Self::with_motion(Motion::Static(center), radius, material)
+    }
+
+    /// A sphere moving linearly from `from` at shutter time 0 to `to` at shutter time 1.
+    pub fn moving(from: Point3, to: Point3, radius: f64, material: Material) -> Self {
+        Self::with_motion(Motion::linear(from, to), radius, material)
+    }
+
+    /// A sphere whose center follows the keyframes during the shutter interval.
+    pub fn keyframed(
+        keyframes: Vec<Keyframe>,
+        radius: f64,
+        material: Material,
+    ) -> Result<Self, MotionError> {
+        Ok(Self::with_motion(
+            Motion::keyframed(keyframes)?,
+            radius,
+            material,
+        ))
+    }
+
+    pub fn with_motion(center: Motion, radius: f64, material: Material) -> Self {
         let radius = radius.max(0.);
         Self {
             center,
@@ -22,16 +49,24 @@ impl Sphere {
 }
 
 impl Hittable for Sphere {
-    fn normal(&self, hit_point: &Point3) -> Vec3 {
-        &(hit_point - &self.center) / self.radius
+    fn normal(&self, hit_point: &Point3, shutter_time: f64) -> Vec3 {
+        &(hit_point - self.center.position_at(shutter_time)) / self.radius
     }
 
     fn material(&self) -> Material {
         self.material.clone()
     }
 
+    // The angle around the Y axis starting from -X gives u, the angle from -Y up to +Y gives v.
+    fn uv(&self, hit_point: &Point3, shutter_time: f64) -> (f64, f64) {
+        let p = self.normal(hit_point, shutter_time);
+        let theta = (-p.y).clamp(-1., 1.).acos();
+        let phi = (-p.z).atan2(p.x) + PI;
+        (phi / (2. * PI), theta / PI)
+    }
+
     fn hit(&self, ray: &Ray, time_range: &Range<f64>) -> Option<HitRecord> {
-        let oc = &self.center - &ray.origin;
+        let oc = self.center.position_at(ray.shutter_time) - &ray.origin;
         let a = ray.direction.length_squared();
         let h = ray.direction.dot(&oc);
         let c = oc.length_squared() - self.radius * self.radius;
@@ -54,4 +89,35 @@ impl Hittable for Sphere {
 
         None
     }
+
+    fn bounding_box(&self) -> Aabb {
+        let extent = Vec3::new(self.radius, self.radius, self.radius);
+        self.center.swept_box(&extent)
+    }
+
+    fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        self.center.fingerprint(fingerprint);
+        fingerprint.add_f64(self.radius);
+        self.material.fingerprint(fingerprint);
+    }
+}
+
+impl Solid for Sphere {
+    fn spans(&self, ray: &Ray) -> Vec<Span> {
+        let oc = self.center.position_at(ray.shutter_time) - &ray.origin;
+        let a = ray.direction.length_squared();
+        let h = ray.direction.dot(&oc);
+        let c = oc.length_squared() - self.radius * self.radius;
+        let discriminant = h * h - a * c;
+
+        // Grazing the sphere in a single point doesn't enclose anything.
+        if discriminant <= 0. {
+            return Vec::new();
+        }
+        let sqrt = discriminant.sqrt();
+        vec![Span {
+            enter: HitRecord::new(ray, self, (h - sqrt) / a),
+            exit: HitRecord::new(ray, self, (h + sqrt) / a),
+        }]
+    }
 }
@@ -0,0 +1,108 @@
+use crate::aabb::Aabb;
+use crate::hit::hittable::{HitRecord, Hittable};
+use crate::materials::Material;
+use crate::polynomial::solve_quartic;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+use std::f64::consts::PI;
+use std::ops::Range;
+
+/// A torus lying in the XZ plane around the origin: a tube of `minor_radius` around a circle of
+/// `major_radius`. Use an `Instance` to place it.
+pub struct Torus {
+    major_radius: f64,
+    minor_radius: f64,
+    material: Material,
+}
+
+impl Torus {
+    pub fn new(major_radius: f64, minor_radius: f64, material: Material) -> Self {
+        Self {
+            major_radius,
+            minor_radius,
+            material,
+        }
+    }
+}
+
+impl Hittable for Torus {
+    fn normal(&self, hit_point: &Point3, _shutter_time: f64) -> Vec3 {
+        // The normal points away from the closest point of the circle inside the tube.
+        let radial = Vec3::new(hit_point.x, 0., hit_point.z);
+        let length = radial.length();
+        if length == 0. {
+            return Vec3::new(0., hit_point.y.signum(), 0.);
+        }
+        let tube_center = radial * (self.major_radius / length);
+        (hit_point - tube_center).unit()
+    }
+
+    fn material(&self) -> Material {
+        self.material.clone()
+    }
+
+    // The angle around the Y axis gives u, the angle around the tube gives v.
+    fn uv(&self, hit_point: &Point3, _shutter_time: f64) -> (f64, f64) {
+        let u = ((-hit_point.z).atan2(hit_point.x) + PI) / (2. * PI);
+        let radial = (hit_point.x * hit_point.x + hit_point.z * hit_point.z).sqrt();
+        let v = (hit_point.y.atan2(radial - self.major_radius) + PI) / (2. * PI);
+        (u, v)
+    }
+
+    fn hit(&self, ray: &Ray, time_range: &Range<f64>) -> Option<HitRecord> {
+        // Solving with a unit direction keeps the quartic coefficients well scaled.
+        let scale = ray.direction.length();
+        let d = &ray.direction / scale;
+        let o = &ray.origin;
+
+        // (|p|² + R² - r²)² = 4R²(x² + z²) for p = o + t d
+        let r2 = self.major_radius * self.major_radius;
+        let b = 2. * o.dot(&d);
+        let c = o.length_squared() + r2 - self.minor_radius * self.minor_radius;
+        let planar_a = d.x * d.x + d.z * d.z;
+        let planar_b = 2. * (o.x * d.x + o.z * d.z);
+        let planar_c = o.x * o.x + o.z * o.z;
+        let roots = solve_quartic(
+            1.,
+            2. * b,
+            b * b + 2. * c - 4. * r2 * planar_a,
+            2. * b * c - 4. * r2 * planar_b,
+            c * c - 4. * r2 * planar_c,
+        );
+
+        roots
+            .into_iter()
+            .map(|time| time / scale)
+            .find(|time| time_range.contains(time))
+            .map(|time| HitRecord::new(ray, self, time))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let outer = self.major_radius + self.minor_radius;
+        Aabb::new(
+            &Point3::new(-outer, -self.minor_radius, -outer),
+            &Point3::new(outer, self.minor_radius, outer),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::materials::Lambertian;
+
+    #[test]
+    fn ray_through_the_hole_misses() {
+        let torus = Torus::new(2., 0.5, Lambertian::new(Color::one()).into());
+        let all_time = 0.0..f64::INFINITY;
+
+        let down_the_hole = Ray::new(Point3::new(0., 5., 0.), Vec3::new(0., -1., 0.));
+        assert!(torus.hit(&down_the_hole, &all_time).is_none());
+
+        let through_the_ring = Ray::new(Point3::new(-5., 0., 0.), Vec3::new(2., 0., 0.));
+        let record = torus.hit(&through_the_ring, &all_time).unwrap();
+        assert!((record.time - 1.25).abs() < 1e-9);
+        assert!((&record.normal - Vec3::new(-1., 0., 0.)).length() < 1e-9);
+    }
+}
@@ -0,0 +1,186 @@
+use crate::aabb::Aabb;
+use crate::checkpoint::Fingerprint;
+use crate::color::{blackbody, Color};
+use crate::hit::hittable::{HitRecord, Hittable};
+use crate::materials::{EmissiveMedium, Material};
+use crate::random::random;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+use crate::voxel::VoxelGrid;
+use std::ops::Range;
+
+/// A heterogeneous participating medium, whose density, and optionally temperature, come from a
+/// voxel grid stretched over a box. Collisions are found with delta tracking: the ray takes
+/// exponentially distributed steps as if the whole volume had the maximal density, and each
+/// tentative collision is a real one with the probability of the local density over the
+/// maximal one.
+pub struct VoxelVolume {
+    grid: VoxelGrid,
+    bounds: Aabb,
+    density_scale: f64, // Probability of scattering per unit of distance at a density of one
+    phase_function: Material,
+    emission_scale: f64, // Radiance emitted at 1000 K, growing with the fourth power of it
+}
+
+impl VoxelVolume {
+    pub fn new(
+        grid: VoxelGrid,
+        bounds: Aabb,
+        density_scale: f64,
+        phase_function: Material,
+    ) -> Self {
+        Self {
+            grid,
+            bounds,
+            density_scale,
+            phase_function,
+            emission_scale: 0.,
+        }
+    }
+
+    /// Makes the volume glow with the color of a black body at the temperature of the grid,
+    /// which must have temperatures.
+    pub fn with_emission(mut self, emission_scale: f64) -> Self {
+        assert!(
+            self.grid.has_temperature(),
+            "emission needs a grid with temperatures"
+        );
+        self.emission_scale = emission_scale;
+        self
+    }
+
+    fn majorant(&self) -> f64 {
+        self.grid.max_density() * self.density_scale
+    }
+
+    fn grid_position(&self, point: &Point3) -> [f64; 3] {
+        let size = &self.bounds.max - &self.bounds.min;
+        let local = point - &self.bounds.min;
+        [local.x / size.x, local.y / size.y, local.z / size.z]
+    }
+
+    fn density_at(&self, point: &Point3) -> f64 {
+        self.grid.density_at(self.grid_position(point)) * self.density_scale
+    }
+
+    fn emission_at(&self, point: &Point3) -> Color {
+        let temperature = self.grid.temperature_at(self.grid_position(point));
+        if temperature <= 0. {
+            return Color::zero();
+        }
+        blackbody(temperature) * (self.emission_scale * (temperature / 1000.).powi(4))
+    }
+
+    // Parameters of the ray at tentative collisions, from an exponential distribution with the
+    // majorant as rate, within the part of `time_range` inside the volume.
+    fn tentative_collisions(
+        &self,
+        ray: &Ray,
+        time_range: &Range<f64>,
+    ) -> impl Iterator<Item = f64> {
+        let range = self.bounds.clip(ray, time_range);
+        let rate = self.majorant() * ray.direction.length();
+        let mut time = range.as_ref().map_or(0., |range| range.start);
+        let end = range.map_or(f64::NEG_INFINITY, |range| range.end);
+        std::iter::from_fn(move || {
+            if rate <= 0. {
+                return None;
+            }
+            time -= (1. - random()).ln() / rate;
+            (time < end).then_some(time)
+        })
+    }
+}
+
+impl Hittable for VoxelVolume {
+    // Scattering inside a medium doesn't depend on any surface, so any normal will do.
+    fn normal(&self, _hit_point: &Point3, _shutter_time: f64) -> Vec3 {
+        Vec3::new(1., 0., 0.)
+    }
+
+    fn material(&self) -> Material {
+        self.phase_function.clone()
+    }
+
+    fn hit(&self, ray: &Ray, time_range: &Range<f64>) -> Option<HitRecord> {
+        let majorant = self.majorant();
+        let time = self
+            .tentative_collisions(ray, time_range)
+            .find(|time| random() * majorant < self.density_at(&ray.position_at(*time)))?;
+
+        let mut record = HitRecord::new(ray, self, time);
+        if self.emission_scale > 0. {
+            let emit = self.emission_at(&record.point);
+            record.material = EmissiveMedium::new(self.phase_function.clone(), emit).into();
+        }
+        Some(record)
+    }
+
+    /// Estimated with ratio tracking: the product over tentative collisions of the probability
+    /// that they are not real. It is unbiased and never noisier than testing for a collision,
+    /// which makes it the estimator for shadow rays.
+    fn transmittance(&self, ray: &Ray, time_range: &Range<f64>) -> f64 {
+        let majorant = self.majorant();
+        self.tentative_collisions(ray, time_range)
+            .map(|time| 1. - self.density_at(&ray.position_at(time)) / majorant)
+            .product()
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bounds.clone()
+    }
+
+    /// The grid is summed up by its size and its highest density rather than added voxel by
+    /// voxel.
+    fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        fingerprint.add_aabb(&self.bounds);
+        for size in self.grid.size() {
+            fingerprint.add_u64(size as u64);
+        }
+        fingerprint.add_f64(self.grid.max_density());
+        fingerprint.add_bytes(&[self.grid.has_temperature() as u8]);
+        fingerprint.add_f64(self.density_scale);
+        fingerprint.add_f64(self.emission_scale);
+        self.phase_function.fingerprint(fingerprint);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hit::list::HittableList;
+    use crate::materials::Isotropic;
+    use crate::random::seed;
+
+    #[test]
+    fn tracking_matches_beer_lambert() {
+        seed(1);
+        let grid = VoxelGrid::new([2, 2, 2], vec![0.5; 8]);
+        let bounds = Aabb::new(&Point3::zero(), &Point3::new(2., 2., 2.));
+        let volume = VoxelVolume::new(grid, bounds, 1., Isotropic::new(Color::one()).into());
+
+        // Half of the density over two units of distance.
+        let ray = Ray::new(Point3::new(-1., 1., 1.), Vec3::new(1., 0., 0.));
+        let samples = 20000;
+        let mean = (0..samples)
+            .map(|_| volume.transmittance(&ray, &(0.0..f64::INFINITY)))
+            .sum::<f64>()
+            / samples as f64;
+        assert!((mean - (-1f64).exp()).abs() < 0.01, "{}", mean);
+
+        // Through a scene, shadow rays see the volume the same way.
+        let mut world = HittableList::new();
+        world.add(volume);
+        let mean = (0..samples)
+            .map(|_| world.transmittance(&ray, &(0.0..f64::INFINITY)))
+            .sum::<f64>()
+            / samples as f64;
+        assert!((mean - (-1f64).exp()).abs() < 0.01, "{}", mean);
+        let volume = world.into_bvh();
+
+        let misses = (0..samples)
+            .filter(|_| volume.hit(&ray, &(0.0..f64::INFINITY)).is_none())
+            .count();
+        assert!((misses as f64 / samples as f64 - (-1f64).exp()).abs() < 0.01);
+    }
+}
@@ -0,0 +1,197 @@
+use crate::color::{write_color_to, Color};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+/// A rectangular buffer of linear colors, stored row by row from the top left pixel.
+#[derive(Clone, Debug)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![Color::zero(); width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, i: usize, j: usize) -> &Color {
+        &self.pixels[j * self.width + i]
+    }
+
+    pub fn set(&mut self, i: usize, j: usize, color: Color) {
+        self.pixels[j * self.width + i] = color;
+    }
+
+    pub fn pixels(&self) -> &[Color] {
+        &self.pixels
+    }
+
+    /// Copies `patch` over the image with its top left corner at the pixel x, y. The parts of
+    /// the patch falling outside the image are left out.
+    pub fn paste(&mut self, patch: &Image, x: usize, y: usize) {
+        for j in 0..patch.height.min(self.height.saturating_sub(y)) {
+            for i in 0..patch.width.min(self.width.saturating_sub(x)) {
+                self.set(x + i, y + j, patch.get(i, j).clone());
+            }
+        }
+    }
+
+    /// Writes the image as a plain text PPM, gamma corrected the same way as `write_color`.
+    pub fn write_ppm<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "P3\n{:} {:}\n255\n", self.width, self.height)?;
+        for color in &self.pixels {
+            write_color_to(out, color)?;
+        }
+        Ok(())
+    }
+
+    pub fn save_ppm<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.write_ppm(&mut out)?;
+        out.flush()
+    }
+
+    /// Reads a plain text (P3) or binary (P6) PPM, undoing the gamma correction of `write_ppm`
+    /// to get linear colors back.
+    pub fn read_ppm<R: Read>(input: &mut R) -> io::Result<Self> {
+        let mut bytes = Vec::new();
+        input.read_to_end(&mut bytes)?;
+        let invalid = || io::Error::from(io::ErrorKind::InvalidData);
+
+        // Header tokens are separated by whitespace, and comments run to the end of the line.
+        let mut position = 0;
+        let next_token = |position: &mut usize| -> io::Result<String> {
+            loop {
+                match bytes.get(*position) {
+                    Some(b'#') => {
+                        while bytes.get(*position).is_some_and(|&byte| byte != b'\n') {
+                            *position += 1;
+                        }
+                    }
+                    Some(byte) if byte.is_ascii_whitespace() => *position += 1,
+                    Some(_) => break,
+                    None => return Err(invalid()),
+                }
+            }
+            let start = *position;
+            while bytes
+                .get(*position)
+                .is_some_and(|byte| !byte.is_ascii_whitespace())
+            {
+                *position += 1;
+            }
+            Ok(String::from_utf8_lossy(&bytes[start..*position]).into_owned())
+        };
+        let next_number = |position: &mut usize| -> io::Result<usize> {
+            next_token(position)?.parse().map_err(|_| invalid())
+        };
+
+        let binary = match next_token(&mut position)?.as_str() {
+            "P3" => false,
+            "P6" => true,
+            _ => return Err(invalid()),
+        };
+        let width = next_number(&mut position)?;
+        let height = next_number(&mut position)?;
+        let max_value = next_number(&mut position)?;
+        if width == 0 || height == 0 || !(1..256).contains(&max_value) {
+            return Err(invalid());
+        }
+
+        let count = width
+            .checked_mul(height)
+            .and_then(|pixels| pixels.checked_mul(3))
+            .ok_or_else(invalid)?;
+        let values = if binary {
+            // A single whitespace separates the header from the pixels.
+            let start = position + 1;
+            let end = start.checked_add(count).ok_or_else(invalid)?;
+            bytes
+                .get(start..end)
+                .ok_or_else(invalid)?
+                .iter()
+                .map(|&value| value as usize)
+                .collect()
+        } else {
+            (0..count)
+                .map(|_| next_number(&mut position))
+                .collect::<io::Result<Vec<_>>>()?
+        };
+
+        let linear = |value: usize| {
+            let gamma = value.min(max_value) as f64 / max_value as f64;
+            gamma * gamma
+        };
+        let mut image = Self::new(width, height);
+        for (pixel, value) in image.pixels.iter_mut().zip(values.chunks(3)) {
+            *pixel = Color::new(linear(value[0]), linear(value[1]), linear(value[2]));
+        }
+        Ok(image)
+    }
+
+    pub fn load_ppm<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::read_ppm(&mut BufReader::new(File::open(path)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_ppm_undoes_the_gamma_correction() {
+        let mut image = Image::new(2, 1);
+        image.set(0, 0, Color::new(0.25, 0., 1.));
+        let mut bytes = Vec::new();
+        image.write_ppm(&mut bytes).unwrap();
+
+        let read = Image::read_ppm(&mut bytes.as_slice()).unwrap();
+        assert_eq!((read.width(), read.height()), (2, 1));
+        let color = read.get(0, 0);
+        assert!((color.x - 0.25).abs() < 0.01);
+        assert_eq!(color.y, 0.);
+        assert!((color.z - 1.).abs() < 0.01);
+    }
+
+    #[test]
+    fn read_binary_ppm_with_comments() {
+        let mut bytes = b"P6\n# mask\n2 1\n255\n".to_vec();
+        bytes.extend_from_slice(&[255, 255, 255, 0, 0, 0]);
+        let read = Image::read_ppm(&mut bytes.as_slice()).unwrap();
+        assert_eq!(read.get(0, 0), &Color::one());
+        assert_eq!(read.get(1, 0), &Color::zero());
+    }
+
+    #[test]
+    fn read_ppm_rejects_overflowing_sizes() {
+        let header = format!("P6\n{} 2\n255\n", usize::MAX / 4);
+        let error = Image::read_ppm(&mut header.as_bytes()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn paste_clips_the_patch_to_the_image() {
+        let mut image = Image::new(3, 2);
+        let mut patch = Image::new(2, 2);
+        patch.set(0, 0, Color::one());
+        patch.set(1, 1, Color::one());
+        image.paste(&patch, 2, 1);
+        assert_eq!(image.get(2, 1), &Color::one());
+        let lit = image.pixels().iter().filter(|color| color.x > 0.).count();
+        assert_eq!(lit, 1);
+    }
+}
@@ -0,0 +1,556 @@
+//! Camera lenses made of spherical elements, traced surface by surface instead of being
+//! approximated by a thin lens, so that their distortion, vignetting and focus breathing show
+//! in the image.
+//!
+//! Lens prescriptions are text files with a line per surface, from the front of the lens to the
+//! back: the signed radius of curvature, the thickness to the next surface, the refractive
+//! index behind the surface and the surface diameter, all in millimeters and separated by
+//! whitespace. A radius of 0 marks the aperture stop, an index of 0 stands for air, and `#`
+//! starts a comment. The thickness of the last surface is the distance to the film.
+//!
+//! Lenses are traced in their own frame, in millimeters: the film is in the plane z = 0 and the
+//! lens elements are along positive z, towards the scene.
+
+use crate::checkpoint::Fingerprint;
+use crate::vec3::Vec3;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// Film radii for which the area of the rear element letting light through is precomputed, and
+// the number of rays probing it along each side of the rear element.
+const PUPIL_BINS: usize = 64;
+const PUPIL_PROBES: usize = 64;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LensElement {
+    pub radius: f64,    // Radius of curvature, positive with the center towards the film
+    pub thickness: f64, // Distance to the next surface along the optical axis
+    pub ior: f64,       // Refractive index of the glass behind the surface
+    pub aperture: f64,  // Diameter of the surface
+}
+
+impl LensElement {
+    pub fn new(radius: f64, thickness: f64, ior: f64, aperture: f64) -> Self {
+        Self {
+            radius,
+            thickness,
+            ior,
+            aperture,
+        }
+    }
+
+    fn is_stop(&self) -> bool {
+        self.radius == 0.
+    }
+
+    fn refractive_index(&self) -> f64 {
+        if self.ior <= 0. {
+            1.
+        } else {
+            self.ior
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum LensError {
+    Io(io::Error),
+    /// A line of the prescription, counted from 1, doesn't have four numbers.
+    InvalidLine(usize),
+    /// The prescription has no surfaces.
+    Empty,
+    /// No film position brings the requested distance into focus.
+    CannotFocus,
+}
+
+impl Display for LensError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "lens prescription i/o failed: {}", error),
+            Self::InvalidLine(line) => write!(f, "invalid lens prescription line {}", line),
+            Self::Empty => write!(f, "lens prescription has no surfaces"),
+            Self::CannotFocus => write!(f, "lens cannot focus at that distance"),
+        }
+    }
+}
+
+impl std::error::Error for LensError {}
+
+impl From<io::Error> for LensError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LensSystem {
+    elements: Vec<LensElement>,
+    units_per_meter: f64, // Size of a meter in scene units
+}
+
+impl LensSystem {
+    pub fn new(elements: Vec<LensElement>) -> Result<Self, LensError> {
+        if elements.is_empty() {
+            return Err(LensError::Empty);
+        }
+        Ok(Self {
+            elements,
+            units_per_meter: 1.,
+        })
+    }
+
+    pub fn parse(prescription: &str) -> Result<Self, LensError> {
+        let mut elements = Vec::new();
+        for (index, line) in prescription.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let numbers = line
+                .split_whitespace()
+                .map(str::parse)
+                .collect::<Result<Vec<f64>, _>>()
+                .map_err(|_| LensError::InvalidLine(index + 1))?;
+            let [radius, thickness, ior, aperture] = numbers[..] else {
+                return Err(LensError::InvalidLine(index + 1));
+            };
+            elements.push(LensElement::new(radius, thickness, ior, aperture));
+        }
+        Self::new(elements)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, LensError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn with_units_per_meter(mut self, units_per_meter: f64) -> Self {
+        self.units_per_meter = units_per_meter;
+        self
+    }
+
+    /// Stops the aperture down, or opens it up, to `diameter` millimeters.
+    pub fn with_stop_diameter(mut self, diameter: f64) -> Self {
+        for element in self.elements.iter_mut().filter(|element| element.is_stop()) {
+            element.aperture = diameter;
+        }
+        self
+    }
+
+    pub fn elements(&self) -> &[LensElement] {
+        &self.elements
+    }
+
+    /// Distance from the rear element to the film.
+    pub fn film_distance(&self) -> f64 {
+        self.elements.last().map_or(0., |element| element.thickness)
+    }
+
+    /// Distance from the front element to the film.
+    pub fn length(&self) -> f64 {
+        self.elements.iter().map(|element| element.thickness).sum()
+    }
+
+    // Millimeters in a scene unit.
+    fn millimeters_per_unit(&self) -> f64 {
+        1000. / self.units_per_meter
+    }
+
+    /// The effective focal length, from a ray coming in parallel to the axis close to it.
+    pub fn focal_length(&self) -> Option<f64> {
+        let height = self.paraxial_height();
+        let origin = Vec3::new(0., height, self.length() + 1.);
+        let (_, direction) = self.trace_from_scene(&origin, &Vec3::new(0., 0., -1.))?;
+        (direction.y < 0.).then(|| height * direction.z.abs() / -direction.y)
+    }
+
+    /// Moves the lens along its axis so that objects `distance` scene units in front of the
+    /// front element are in focus, or at infinity for an infinite distance. Focusing closer
+    /// moves the lens away from the film, narrowing the field of view.
+    pub fn focused_at(mut self, distance: f64) -> Result<Self, LensError> {
+        let height = self.paraxial_height();
+        let length = self.length();
+        let (origin, direction) = if distance.is_finite() {
+            let object = Vec3::new(0., 0., length + distance * self.millimeters_per_unit());
+            let direction = Vec3::new(0., height, length) - &object;
+            (object, direction)
+        } else {
+            (Vec3::new(0., height, length + 1.), Vec3::new(0., 0., -1.))
+        };
+        let (origin, direction) = self
+            .trace_from_scene(&origin, &direction)
+            .ok_or(LensError::CannotFocus)?;
+
+        // The image is where the ray crosses the axis again.
+        let time = -origin.y / direction.y;
+        if !time.is_finite() || time <= 0. {
+            return Err(LensError::CannotFocus);
+        }
+        let image = origin.z + direction.z * time;
+        let film_distance = self.film_distance() - image;
+        if film_distance <= 0. {
+            return Err(LensError::CannotFocus);
+        }
+        if let Some(rear) = self.elements.last_mut() {
+            rear.thickness = film_distance;
+        }
+        Ok(self)
+    }
+
+    // A ray height small enough for the paraxial approximation, but well above rounding errors.
+    fn paraxial_height(&self) -> f64 {
+        let smallest = self
+            .elements
+            .iter()
+            .map(|element| element.aperture)
+            .fold(f64::INFINITY, f64::min);
+        smallest * 1e-3
+    }
+
+    /// Traces a ray leaving the film through the lens, returning it where it leaves the front
+    /// element, or `None` if it is blocked on the way.
+    pub fn trace_from_film(&self, origin: &Vec3, direction: &Vec3) -> Option<(Vec3, Vec3)> {
+        self.trace(origin, direction, true)
+    }
+
+    /// Traces a ray from the scene through the lens, returning it where it leaves the rear
+    /// element, or `None` if it is blocked on the way.
+    pub fn trace_from_scene(&self, origin: &Vec3, direction: &Vec3) -> Option<(Vec3, Vec3)> {
+        self.trace(origin, direction, false)
+    }
+
+    fn trace(&self, origin: &Vec3, direction: &Vec3, towards_scene: bool) -> Option<(Vec3, Vec3)> {
+        // Position of the vertex of every surface along the axis.
+        let mut vertices = vec![0.; self.elements.len()];
+        let mut z = 0.;
+        for (vertex, element) in vertices.iter_mut().zip(&self.elements).rev() {
+            z += element.thickness;
+            *vertex = z;
+        }
+        let mut order: Vec<usize> = (0..self.elements.len()).collect();
+        if towards_scene {
+            order.reverse();
+        }
+
+        let mut origin = origin.clone();
+        let mut direction = direction.unit();
+        for index in order {
+            let element = &self.elements[index];
+            let vertex = vertices[index];
+            let (time, normal) = if element.is_stop() {
+                let time = (vertex - origin.z) / direction.z;
+                (time, Vec3::new(0., 0., 1.))
+            } else {
+                intersect_surface(&origin, &direction, vertex, element.radius)?
+            };
+            if !time.is_finite() || time <= 0. {
+                return None;
+            }
+            let point = &origin + &direction * time;
+            let radius = element.aperture / 2.;
+            if point.x * point.x + point.y * point.y > radius * radius {
+                return None;
+            }
+            origin = point;
+
+            if !element.is_stop() {
+                let front = if index == 0 {
+                    1.
+                } else {
+                    self.elements[index - 1].refractive_index()
+                };
+                let back = element.refractive_index();
+                let ratio = if towards_scene {
+                    back / front
+                } else {
+                    front / back
+                };
+                let normal = if normal.dot(&direction) > 0. {
+                    -normal
+                } else {
+                    normal
+                };
+                direction = refract(&direction, &normal, ratio)?;
+            }
+        }
+        Some((origin, direction))
+    }
+}
+
+// Intersects a unit direction ray with the spherical surface whose vertex is at `vertex` on
+// the axis, returning the time of the hit on the side of the vertex and the outward normal.
+fn intersect_surface(
+    origin: &Vec3,
+    direction: &Vec3,
+    vertex: f64,
+    radius: f64,
+) -> Option<(f64, Vec3)> {
+    let center = Vec3::new(0., 0., vertex - radius);
+    let oc = origin - &center;
+    let half_b = oc.dot(direction);
+    let c = oc.length_squared() - radius * radius;
+    let discriminant = half_b * half_b - c;
+    if discriminant < 0. {
+        return None;
+    }
+    let root = discriminant.sqrt();
+    [-half_b - root, -half_b + root]
+        .into_iter()
+        .filter(|&time| time > 1e-9)
+        .find(|&time| (origin.z + direction.z * time - center.z) * radius > 0.)
+        .map(|time| {
+            let point = origin + direction * time;
+            (time, (point - center) / radius.abs())
+        })
+}
+
+// Refracts a unit direction through a surface whose normal faces it, with `ratio` the index
+// of the medium it leaves over the index of the medium it enters. Totally reflected light is
+// lost.
+fn refract(direction: &Vec3, normal: &Vec3, ratio: f64) -> Option<Vec3> {
+    let cos_incident = -normal.dot(direction);
+    let sin2_transmitted = ratio * ratio * (1. - cos_incident * cos_incident);
+    if sin2_transmitted > 1. {
+        return None;
+    }
+    let cos_transmitted = (1. - sin2_transmitted).sqrt();
+    Some(direction * ratio + normal * (ratio * cos_incident - cos_transmitted))
+}
+
+/// A lens system in front of a film, sampling the rays that make it through the lens.
+#[derive(Clone, Debug)]
+pub(crate) struct LensCamera {
+    system: LensSystem,
+    film_width: f64,  // Film size in millimeters
+    film_height: f64, // Film height in millimeters
+    // Bounds of the rear element area letting light through, for film points along the x axis
+    // in every bin of film radius, as minimum x, maximum x, minimum y and maximum y.
+    pupils: Vec<Option<[f64; 4]>>,
+    center_area: f64, // Rear element area letting light through to the center of the film
+}
+
+impl LensCamera {
+    pub(crate) fn new(system: LensSystem, film_width: f64, film_height: f64) -> Self {
+        let rear = system.elements.last().expect("lens systems have surfaces");
+        let rear_radius = rear.aperture / 2.;
+        let rear_z = system.film_distance();
+        let max_radius = (film_width * film_width + film_height * film_height).sqrt() / 2.;
+        let cell = 2. * rear_radius / PUPIL_PROBES as f64;
+
+        // Probes the rear element on a grid from the film point r, 0, returning the bounds of
+        // the points letting light through and their count.
+        let probe = |r: f64| {
+            let mut bounds: Option<[f64; 4]> = None;
+            let mut passed = 0;
+            let film = Vec3::new(r, 0., 0.);
+            for a in 0..PUPIL_PROBES {
+                for b in 0..PUPIL_PROBES {
+                    let x = -rear_radius + (a as f64 + 0.5) * cell;
+                    let y = -rear_radius + (b as f64 + 0.5) * cell;
+                    let direction = Vec3::new(x, y, rear_z) - &film;
+                    if system.trace_from_film(&film, &direction).is_none() {
+                        continue;
+                    }
+                    passed += 1;
+                    bounds = Some(match bounds {
+                        None => [x, x, y, y],
+                        Some([x0, x1, y0, y1]) => [x0.min(x), x1.max(x), y0.min(y), y1.max(y)],
+                    });
+                }
+            }
+            (bounds, passed)
+        };
+
+        // Every bin is bounded by the radii at its two edges, each shared with the next bin.
+        let edges: Vec<_> = (0..=PUPIL_BINS)
+            .map(|k| probe(max_radius * k as f64 / PUPIL_BINS as f64))
+            .collect();
+        let pupils = edges
+            .windows(2)
+            .map(|pair| {
+                let bounds = match (pair[0].0, pair[1].0) {
+                    (Some(l), Some(r)) => Some([
+                        l[0].min(r[0]),
+                        l[1].max(r[1]),
+                        l[2].min(r[2]),
+                        l[3].max(r[3]),
+                    ]),
+                    (l, r) => l.or(r),
+                };
+                // Grow by a probe spacing, so that the bounds don't miss the edges between
+                // probes.
+                bounds.map(|[x0, x1, y0, y1]| [x0 - cell, x1 + cell, y0 - cell, y1 + cell])
+            })
+            .collect();
+        let center_area = edges[0].1 as f64 * cell * cell;
+
+        Self {
+            system,
+            film_width,
+            film_height,
+            pupils,
+            center_area,
+        }
+    }
+
+    /// The same camera with the lens focused at `distance` from its front element.
+    pub(crate) fn refocused(&self, distance: f64) -> Result<Self, LensError> {
+        let system = self.system.clone().focused_at(distance)?;
+        Ok(Self::new(system, self.film_width, self.film_height))
+    }
+
+    /// The pupils are computed from the lens and the film, so they are left out.
+    pub(crate) fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        for element in &self.system.elements {
+            fingerprint.add_f64(element.radius);
+            fingerprint.add_f64(element.thickness);
+            fingerprint.add_f64(element.ior);
+            fingerprint.add_f64(element.aperture);
+        }
+        fingerprint.add_f64(self.system.units_per_meter);
+        fingerprint.add_f64(self.film_width);
+        fingerprint.add_f64(self.film_height);
+    }
+
+    /// A ray through the lens for the image position `s`, `t`, both from 0 to 1 from the top
+    /// left corner, in the camera frame and in scene units with the front element at the
+    /// origin, and its weight. The lens turns the image upside down, so the top left corner of
+    /// the image is at the bottom right of the film.
+    ///
+    /// The weight accounts for the light falling off with the angle to the film, and for the
+    /// share of the lens letting light through, making the center of the film weigh 1.
+    pub(crate) fn sample_ray(
+        &self,
+        s: f64,
+        t: f64,
+        sample: (f64, f64),
+    ) -> Option<(Vec3, Vec3, f64)> {
+        let film = Vec3::new(
+            (0.5 - s) * self.film_width,
+            (t - 0.5) * self.film_height,
+            0.,
+        );
+        let radius = (film.x * film.x + film.y * film.y).sqrt();
+        let max_radius =
+            (self.film_width * self.film_width + self.film_height * self.film_height).sqrt() / 2.;
+        let bin = ((radius / max_radius * PUPIL_BINS as f64) as usize).min(PUPIL_BINS - 1);
+        let [x0, x1, y0, y1] = self.pupils[bin]?;
+
+        // The bounds are for film points along the x axis, turn them to the film point.
+        let x = x0 + (x1 - x0) * sample.0;
+        let y = y0 + (y1 - y0) * sample.1;
+        let (sin, cos) = film.y.atan2(film.x).sin_cos();
+        let pupil = Vec3::new(
+            x * cos - y * sin,
+            x * sin + y * cos,
+            self.system.film_distance(),
+        );
+        let direction = pupil - &film;
+        let cos_theta = direction.z / direction.length();
+        let (origin, direction) = self.system.trace_from_film(&film, &direction)?;
+
+        let weight = cos_theta.powi(4) * (x1 - x0) * (y1 - y0) / self.center_area;
+        let scale = 1. / self.system.millimeters_per_unit();
+        let length = self.system.length();
+        let origin = Vec3::new(origin.x, origin.y, -(origin.z - length)) * scale;
+        let direction = Vec3::new(direction.x, direction.y, -direction.z);
+        Some((origin, direction, weight))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A biconvex lens of 50 mm radii and 5 mm thick, in glass of index 1.5.
+    const BICONVEX: &str = "
+        # radius thickness ior aperture
+        50   5   1.5  20
+        -50  45  0    20
+    ";
+
+    #[test]
+    fn parse_prescription() {
+        let lens = LensSystem::parse(BICONVEX).unwrap();
+        assert_eq!(lens.elements().len(), 2);
+        assert_eq!(lens.elements()[1], LensElement::new(-50., 45., 0., 20.));
+        assert_eq!(lens.length(), 50.);
+
+        assert!(matches!(
+            LensSystem::parse("50 5 1.5\n"),
+            Err(LensError::InvalidLine(1))
+        ));
+        assert!(matches!(
+            LensSystem::parse("# nothing\n"),
+            Err(LensError::Empty)
+        ));
+    }
+
+    #[test]
+    fn thick_lens_focal_length() {
+        // The lensmaker's equation: 1/f = (n - 1) (1/R1 - 1/R2 + (n - 1) d / (n R1 R2)).
+        let (n, r, d) = (1.5, 50., 5.);
+        let expected = 1. / ((n - 1.) * (2. / r - (n - 1.) * d / (n * r * r)));
+        let lens = LensSystem::parse(BICONVEX).unwrap();
+        let focal_length = lens.focal_length().unwrap();
+        assert!((focal_length - expected).abs() < 0.01, "{}", focal_length);
+    }
+
+    #[test]
+    fn focused_lens_images_a_point_on_the_film() {
+        // Focus 1 m away with a scene measured in meters.
+        let lens = LensSystem::parse(BICONVEX).unwrap().focused_at(1.).unwrap();
+        let object = Vec3::new(0., 0., lens.length() + 1000.);
+        for height in [-2., 1., 3.] {
+            let direction = Vec3::new(height, 0., lens.length()) - &object;
+            let (origin, direction) = lens.trace_from_scene(&object, &direction).unwrap();
+            let on_film = origin.clone() + &direction * (-origin.z / direction.z);
+            // Spherical aberration spreads the image a little away from the axis.
+            assert!(on_film.x.abs() < 0.05, "{}", on_film);
+        }
+
+        let closer = LensSystem::parse(BICONVEX)
+            .unwrap()
+            .focused_at(0.5)
+            .unwrap();
+        assert!(closer.film_distance() > lens.film_distance());
+        assert!(matches!(
+            LensSystem::parse(BICONVEX).unwrap().focused_at(0.01),
+            Err(LensError::CannotFocus)
+        ));
+    }
+
+    #[test]
+    fn center_of_the_film_weighs_one_on_average() {
+        let lens = LensSystem::parse(BICONVEX).unwrap().focused_at(2.).unwrap();
+        let camera = LensCamera::new(lens, 36., 24.);
+        let count = 100;
+        let mut total = 0.;
+        for a in 0..count {
+            for b in 0..count {
+                let sample = (
+                    (a as f64 + 0.5) / count as f64,
+                    (b as f64 + 0.5) / count as f64,
+                );
+                if let Some((_, direction, weight)) = camera.sample_ray(0.5, 0.5, sample) {
+                    assert!(direction.z < 0.);
+                    total += weight;
+                }
+            }
+        }
+        let average = total / (count * count) as f64;
+        assert!((average - 1.).abs() < 0.05, "{}", average);
+    }
+
+    #[test]
+    fn refocused_cameras_move_the_lens() {
+        let lens = LensSystem::parse(BICONVEX).unwrap().focused_at(2.).unwrap();
+        let camera = LensCamera::new(lens, 36., 24.).refocused(0.5).unwrap();
+        let closer = LensSystem::parse(BICONVEX)
+            .unwrap()
+            .focused_at(0.5)
+            .unwrap();
+        assert!((camera.system.film_distance() - closer.film_distance()).abs() < 1e-9);
+    }
+}
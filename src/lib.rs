@@ -1,10 +1,31 @@
+pub mod aabb;
+pub mod animation;
+pub mod aperture;
 pub mod camera;
+pub mod camera_settings;
+pub mod checkpoint;
 pub mod color;
+pub mod film;
 pub mod hit;
+pub mod image;
+pub mod lens;
 pub mod materials;
+pub mod matrix;
+pub mod medium;
+pub mod motion;
+pub mod physical;
+pub mod polynomial;
+pub mod progressive;
+pub mod projection;
 pub mod random;
 pub mod ray;
+pub mod sampling;
+pub mod sdf;
+pub mod stereo;
+pub mod texture;
 pub mod vec3;
+pub mod video;
+pub mod voxel;
 
 pub fn add(left: usize, right: usize) -> usize {
     left + right
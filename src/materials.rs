@@ -1,17 +1,26 @@
+use crate::checkpoint::Fingerprint;
 use crate::color::Color;
 use crate::hit::hittable::HitRecord;
 use crate::random::random;
 use crate::ray::Ray;
+use crate::texture::Texture;
 use crate::vec3::Vec3;
+use std::f64::consts::PI;
 
 pub struct Scatter {
     pub attenuation: Color,
     pub ray: Ray,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub enum Material {
     Dielectric(Dielectric),
+    DiffuseLight(DiffuseLight),
+    EmissiveMedium(EmissiveMedium),
+    HenyeyGreenstein(HenyeyGreenstein),
+    /// The surface of a medium boundary that is invisible, letting light through unchanged.
+    Interface,
+    Isotropic(Isotropic),
     Lambertian(Lambertian),
     Metal(Metal),
 }
@@ -20,8 +29,62 @@ impl Material {
     pub fn scatter(&self, ray: &Ray, hit_record: HitRecord) -> Option<Scatter> {
         match self {
             Self::Dielectric(dielectric) => dielectric.scatter(ray, hit_record),
+            Self::DiffuseLight(_) => None,
+            Self::EmissiveMedium(medium) => medium.phase_function.scatter(ray, hit_record),
+            Self::HenyeyGreenstein(phase) => phase.scatter(ray, hit_record),
+            Self::Interface => Some(Scatter {
+                attenuation: Color::one(),
+                ray: ray.scattered(hit_record.point, ray.direction.clone()),
+            }),
+            Self::Isotropic(isotropic) => isotropic.scatter(ray, hit_record),
             Self::Metal(metal) => metal.scatter(ray, hit_record),
-            Self::Lambertian(lambertian) => lambertian.scatter(hit_record),
+            Self::Lambertian(lambertian) => lambertian.scatter(ray, hit_record),
+        }
+    }
+
+    /// Light given off by the surface at the hit point, black for anything but light sources.
+    pub fn emitted(&self, _hit_record: &HitRecord) -> Color {
+        match self {
+            Self::DiffuseLight(light) => light.emit.clone(),
+            Self::EmissiveMedium(medium) => medium.emit.clone(),
+            _ => Color::zero(),
+        }
+    }
+
+    pub fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        match self {
+            Self::Dielectric(dielectric) => {
+                fingerprint.add_bytes(b"dielectric");
+                fingerprint.add_f64(dielectric.refraction_index);
+            }
+            Self::DiffuseLight(light) => {
+                fingerprint.add_bytes(b"diffuse light");
+                fingerprint.add_vec3(&light.emit);
+            }
+            Self::EmissiveMedium(medium) => {
+                fingerprint.add_bytes(b"emissive medium");
+                medium.phase_function.fingerprint(fingerprint);
+                fingerprint.add_vec3(&medium.emit);
+            }
+            Self::HenyeyGreenstein(phase) => {
+                fingerprint.add_bytes(b"henyey-greenstein");
+                phase.albedo.fingerprint(fingerprint);
+                fingerprint.add_f64(phase.anisotropy);
+            }
+            Self::Interface => fingerprint.add_bytes(b"interface"),
+            Self::Isotropic(isotropic) => {
+                fingerprint.add_bytes(b"isotropic");
+                isotropic.albedo.fingerprint(fingerprint);
+            }
+            Self::Lambertian(lambertian) => {
+                fingerprint.add_bytes(b"lambertian");
+                lambertian.albedo.fingerprint(fingerprint);
+            }
+            Self::Metal(metal) => {
+                fingerprint.add_bytes(b"metal");
+                fingerprint.add_vec3(&metal.albedo);
+                fingerprint.add_f64(metal.fuzz);
+            }
         }
     }
 }
@@ -44,29 +107,58 @@ impl From<Dielectric> for Material {
     }
 }
 
-#[derive(Clone)]
+impl From<DiffuseLight> for Material {
+    fn from(value: DiffuseLight) -> Self {
+        Self::DiffuseLight(value)
+    }
+}
+
+impl From<EmissiveMedium> for Material {
+    fn from(value: EmissiveMedium) -> Self {
+        Self::EmissiveMedium(value)
+    }
+}
+
+impl From<Isotropic> for Material {
+    fn from(value: Isotropic) -> Self {
+        Self::Isotropic(value)
+    }
+}
+
+impl From<HenyeyGreenstein> for Material {
+    fn from(value: HenyeyGreenstein) -> Self {
+        Self::HenyeyGreenstein(value)
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct Lambertian {
-    albedo: Color,
+    albedo: Texture,
 }
 
 impl Lambertian {
-    pub fn new(albedo: Color) -> Self {
-        Self { albedo }
+    /// A diffuse material of a single color, or of a texture.
+    pub fn new<T: Into<Texture>>(albedo: T) -> Self {
+        Self {
+            albedo: albedo.into(),
+        }
     }
 
-    fn scatter(&self, hit_record: HitRecord) -> Option<Scatter> {
+    fn scatter(&self, ray: &Ray, hit_record: HitRecord) -> Option<Scatter> {
         let mut scatter_direction = &hit_record.normal + Vec3::random_unit();
         if scatter_direction.is_near_zero() {
             scatter_direction = hit_record.normal
         }
         Some(Scatter {
-            attenuation: self.albedo.clone(),
-            ray: Ray::new(hit_record.point, scatter_direction),
+            attenuation: self
+                .albedo
+                .value(hit_record.u, hit_record.v, &hit_record.point),
+            ray: ray.scattered(hit_record.point, scatter_direction),
         })
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct Metal {
     albedo: Color,
     fuzz: f64,
@@ -74,14 +166,14 @@ pub struct Metal {
 
 impl Metal {
     pub fn new(albedo: Color, fuzz: f64) -> Self {
-        let fuzz = fuzz.min(1.).max(0.);
+        let fuzz = fuzz.clamp(0., 1.);
         Self { albedo, fuzz }
     }
 
     fn reflect(&self, ray: &Ray, hit_record: &HitRecord) -> Ray {
         let n = &hit_record.normal;
         let direction = &ray.direction - n * ray.direction.dot(n) * 2.;
-        Ray::new(hit_record.point.clone(), direction)
+        ray.scattered(hit_record.point.clone(), direction)
     }
 
     fn scatter(&self, ray: &Ray, hit_record: HitRecord) -> Option<Scatter> {
@@ -98,7 +190,7 @@ impl Metal {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct Dielectric {
     // Refractive index in vacuum or air, or the ratio of the material's refractive index over
     // the refractive index of the enclosing media
@@ -128,7 +220,7 @@ impl Dielectric {
     fn reflect(&self, ray: &Ray, hit_record: &HitRecord) -> Ray {
         let n = &hit_record.normal;
         let direction = &ray.direction - n * ray.direction.dot(n) * 2.;
-        Ray::new(hit_record.point.clone(), direction)
+        ray.scattered(hit_record.point.clone(), direction)
     }
 
     fn refract(&self, ray: &Ray, hit_record: &HitRecord) -> Option<Ray> {
@@ -144,7 +236,7 @@ impl Dielectric {
 
         let perpendicular = (direction + n * cos_theta) * refraction_index;
         let parallel = -n * (1. - perpendicular.length_squared()).abs().sqrt();
-        Some(Ray::new(hit_record.point.clone(), perpendicular + parallel))
+        Some(ray.scattered(hit_record.point.clone(), perpendicular + parallel))
     }
 
     fn scatter(&self, ray: &Ray, hit_record: HitRecord) -> Option<Scatter> {
@@ -157,3 +249,103 @@ impl Dielectric {
         })
     }
 }
+
+/// A light source emitting the same light in all directions from every point of its surface,
+/// which turns any object, such as a quad, into an area light.
+#[derive(Clone, Debug)]
+pub struct DiffuseLight {
+    emit: Color,
+}
+
+impl DiffuseLight {
+    pub fn new(emit: Color) -> Self {
+        Self { emit }
+    }
+}
+
+/// The phase function of a participating medium scattering light equally in all directions,
+/// for use with `ConstantMedium`.
+#[derive(Clone, Debug)]
+pub struct Isotropic {
+    albedo: Texture,
+}
+
+impl Isotropic {
+    pub fn new<T: Into<Texture>>(albedo: T) -> Self {
+        Self {
+            albedo: albedo.into(),
+        }
+    }
+
+    fn scatter(&self, ray: &Ray, hit_record: HitRecord) -> Option<Scatter> {
+        Some(Scatter {
+            attenuation: self
+                .albedo
+                .value(hit_record.u, hit_record.v, &hit_record.point),
+            ray: ray.scattered(hit_record.point, Vec3::random_unit()),
+        })
+    }
+}
+
+/// The Henyey-Greenstein phase function, scattering light preferably forward for a positive
+/// `anisotropy`, like fog and clouds do, or backward for a negative one. Zero is isotropic.
+#[derive(Clone, Debug)]
+pub struct HenyeyGreenstein {
+    albedo: Texture,
+    anisotropy: f64, // Mean cosine of the scattering angle, in (-1, 1)
+}
+
+impl HenyeyGreenstein {
+    pub fn new<T: Into<Texture>>(albedo: T, anisotropy: f64) -> Self {
+        Self {
+            albedo: albedo.into(),
+            anisotropy: anisotropy.clamp(-0.99, 0.99),
+        }
+    }
+
+    /// Cosine of the angle between the incoming and the scattered direction, sampled from the
+    /// phase function.
+    fn sample_cos_theta(&self) -> f64 {
+        let g = self.anisotropy;
+        let xi = random();
+        if g.abs() < 1e-3 {
+            return 1. - 2. * xi;
+        }
+        let s = (1. - g * g) / (1. - g + 2. * g * xi);
+        ((1. + g * g - s * s) / (2. * g)).clamp(-1., 1.)
+    }
+
+    fn scatter(&self, ray: &Ray, hit_record: HitRecord) -> Option<Scatter> {
+        let forward = ray.direction.unit();
+        let (tangent, bitangent) = forward.orthonormal_basis();
+        let cos_theta = self.sample_cos_theta();
+        let sin_theta = (1. - cos_theta * cos_theta).sqrt();
+        let phi = 2. * PI * random();
+        let direction = &forward * cos_theta
+            + &tangent * (sin_theta * phi.cos())
+            + &bitangent * (sin_theta * phi.sin());
+        Some(Scatter {
+            attenuation: self
+                .albedo
+                .value(hit_record.u, hit_record.v, &hit_record.point),
+            ray: ray.scattered(hit_record.point, direction),
+        })
+    }
+}
+
+/// A collision inside a glowing medium, such as fire, which emits light and scatters like its
+/// phase function. Volumes create one for every collision, from the local temperature.
+#[derive(Clone, Debug)]
+pub struct EmissiveMedium {
+    phase_function: Box<Material>,
+    emit: Color,
+}
+
+impl EmissiveMedium {
+    pub fn new(phase_function: Material, emit: Color) -> Self {
+        Self {
+            phase_function: Box::new(phase_function),
+            emit,
+        }
+    }
+}
@@ -0,0 +1,373 @@
+use crate::vec3::{Point3, Vec3};
+use std::ops::Mul;
+
+/// A 4x4 matrix of affine transforms, stored row by row. Points are column vectors, so
+/// `a * b` applies `b` first and `a` second.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Matrix4 {
+    pub rows: [[f64; 4]; 4],
+}
+
+impl Matrix4 {
+    pub const fn new(rows: [[f64; 4]; 4]) -> Self {
+        Self { rows }
+    }
+
+    pub const fn identity() -> Self {
+        Self::new([
+            [1., 0., 0., 0.],
+            [0., 1., 0., 0.],
+            [0., 0., 1., 0.],
+            [0., 0., 0., 1.],
+        ])
+    }
+
+    pub fn translation(offset: &Vec3) -> Self {
+        Self::new([
+            [1., 0., 0., offset.x],
+            [0., 1., 0., offset.y],
+            [0., 0., 1., offset.z],
+            [0., 0., 0., 1.],
+        ])
+    }
+
+    pub fn scaling(factors: &Vec3) -> Self {
+        Self::new([
+            [factors.x, 0., 0., 0.],
+            [0., factors.y, 0., 0.],
+            [0., 0., factors.z, 0.],
+            [0., 0., 0., 1.],
+        ])
+    }
+
+    /// Rotation by `degrees` counterclockwise around `axis`, looking against its direction.
+    pub fn rotation(axis: &Vec3, degrees: f64) -> Self {
+        Quaternion::from_axis_angle(axis, degrees).to_matrix()
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut rows = [[0.; 4]; 4];
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = self.rows[j][i];
+            }
+        }
+        Self::new(rows)
+    }
+
+    /// Inverts the matrix with Gauss-Jordan elimination. Returns `None` for singular matrices,
+    /// such as a scaling by zero.
+    pub fn inverse(&self) -> Option<Self> {
+        let mut left = self.rows;
+        let mut right = Self::identity().rows;
+        for column in 0..4 {
+            let pivot = (column..4)
+                .max_by(|&a, &b| left[a][column].abs().total_cmp(&left[b][column].abs()))
+                .unwrap();
+            if left[pivot][column].abs() < 1e-12 {
+                return None;
+            }
+            left.swap(column, pivot);
+            right.swap(column, pivot);
+
+            let scale = 1. / left[column][column];
+            for j in 0..4 {
+                left[column][j] *= scale;
+                right[column][j] *= scale;
+            }
+            for row in 0..4 {
+                let factor = left[row][column];
+                if row == column || factor == 0. {
+                    continue;
+                }
+                for j in 0..4 {
+                    left[row][j] -= factor * left[column][j];
+                    right[row][j] -= factor * right[column][j];
+                }
+            }
+        }
+        Some(Self::new(right))
+    }
+
+    pub fn transform_point(&self, point: &Point3) -> Point3 {
+        let m = &self.rows;
+        let x = m[0][0] * point.x + m[0][1] * point.y + m[0][2] * point.z + m[0][3];
+        let y = m[1][0] * point.x + m[1][1] * point.y + m[1][2] * point.z + m[1][3];
+        let z = m[2][0] * point.x + m[2][1] * point.y + m[2][2] * point.z + m[2][3];
+        let w = m[3][0] * point.x + m[3][1] * point.y + m[3][2] * point.z + m[3][3];
+        if w == 1. {
+            Point3::new(x, y, z)
+        } else {
+            Point3::new(x / w, y / w, z / w)
+        }
+    }
+
+    /// Transforms a direction, which unlike a point is not affected by translation.
+    pub fn transform_vector(&self, vector: &Vec3) -> Vec3 {
+        let m = &self.rows;
+        Vec3::new(
+            m[0][0] * vector.x + m[0][1] * vector.y + m[0][2] * vector.z,
+            m[1][0] * vector.x + m[1][1] * vector.y + m[1][2] * vector.z,
+            m[2][0] * vector.x + m[2][1] * vector.y + m[2][2] * vector.z,
+        )
+    }
+}
+
+impl Mul<&Matrix4> for &Matrix4 {
+    type Output = Matrix4;
+
+    fn mul(self, rhs: &Matrix4) -> Self::Output {
+        let mut rows = [[0.; 4]; 4];
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..4).map(|k| self.rows[i][k] * rhs.rows[k][j]).sum();
+            }
+        }
+        Matrix4::new(rows)
+    }
+}
+
+impl Mul<Matrix4> for Matrix4 {
+    type Output = Matrix4;
+
+    fn mul(self, rhs: Matrix4) -> Self::Output {
+        &self * &rhs
+    }
+}
+
+/// A rotation stored as a unit quaternion `w + xi + yj + zk`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Quaternion {
+    pub w: f64,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Quaternion {
+    pub const fn new(w: f64, x: f64, y: f64, z: f64) -> Self {
+        Self { w, x, y, z }
+    }
+
+    pub const fn identity() -> Self {
+        Self::new(1., 0., 0., 0.)
+    }
+
+    pub fn from_axis_angle(axis: &Vec3, degrees: f64) -> Self {
+        let axis = axis.unit();
+        let half_angle = degrees.to_radians() / 2.;
+        let sin = half_angle.sin();
+        Self::new(half_angle.cos(), axis.x * sin, axis.y * sin, axis.z * sin)
+    }
+
+    pub fn dot(&self, other: &Self) -> f64 {
+        self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn normalized(&self) -> Self {
+        let length = self.dot(self).sqrt();
+        Self::new(
+            self.w / length,
+            self.x / length,
+            self.y / length,
+            self.z / length,
+        )
+    }
+
+    /// Spherical linear interpolation, going the short way around from `self` at `t = 0` to
+    /// `other` at `t = 1`.
+    pub fn slerp(&self, other: &Self, t: f64) -> Self {
+        let mut cos = self.dot(other);
+        let mut other = other.clone();
+        if cos < 0. {
+            cos = -cos;
+            other = Self::new(-other.w, -other.x, -other.y, -other.z);
+        }
+        let (a, b) = if cos > 0.9995 {
+            // Nearly parallel, where linear interpolation is accurate and slerp is unstable.
+            (1. - t, t)
+        } else {
+            let angle = cos.acos();
+            let sin = angle.sin();
+            (((1. - t) * angle).sin() / sin, (t * angle).sin() / sin)
+        };
+        Self::new(
+            a * self.w + b * other.w,
+            a * self.x + b * other.x,
+            a * self.y + b * other.y,
+            a * self.z + b * other.z,
+        )
+        .normalized()
+    }
+
+    pub fn rotate(&self, vector: &Vec3) -> Vec3 {
+        let axis = Vec3::new(self.x, self.y, self.z);
+        let t = axis.cross(vector) * 2.;
+        vector + &t * self.w + axis.cross(&t)
+    }
+
+    pub fn to_matrix(&self) -> Matrix4 {
+        let Self { w, x, y, z } = self.normalized();
+        Matrix4::new([
+            [
+                1. - 2. * (y * y + z * z),
+                2. * (x * y - w * z),
+                2. * (x * z + w * y),
+                0.,
+            ],
+            [
+                2. * (x * y + w * z),
+                1. - 2. * (x * x + z * z),
+                2. * (y * z - w * x),
+                0.,
+            ],
+            [
+                2. * (x * z - w * y),
+                2. * (y * z + w * x),
+                1. - 2. * (x * x + y * y),
+                0.,
+            ],
+            [0., 0., 0., 1.],
+        ])
+    }
+}
+
+impl Mul<&Quaternion> for &Quaternion {
+    type Output = Quaternion;
+
+    /// The rotation by `rhs` followed by the rotation by `self`.
+    fn mul(self, rhs: &Quaternion) -> Self::Output {
+        Quaternion::new(
+            self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z,
+            self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+            self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+            self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+        )
+    }
+}
+
+/// An invertible affine transform, keeping the inverse matrix around since rays are
+/// transformed by the inverse and hits by the transform itself.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Transform {
+    pub matrix: Matrix4,
+    pub inverse: Matrix4,
+}
+
+impl Transform {
+    /// Returns `None` if the matrix is not invertible.
+    pub fn new(matrix: Matrix4) -> Option<Self> {
+        let inverse = matrix.inverse()?;
+        Some(Self { matrix, inverse })
+    }
+
+    pub fn identity() -> Self {
+        Self {
+            matrix: Matrix4::identity(),
+            inverse: Matrix4::identity(),
+        }
+    }
+
+    pub fn translation(offset: &Vec3) -> Self {
+        Self {
+            matrix: Matrix4::translation(offset),
+            inverse: Matrix4::translation(&-offset),
+        }
+    }
+
+    /// Panics if any of the factors is zero.
+    pub fn scaling(factors: &Vec3) -> Self {
+        assert!(
+            factors.x != 0. && factors.y != 0. && factors.z != 0.,
+            "scaling by zero can't be inverted"
+        );
+        Self {
+            matrix: Matrix4::scaling(factors),
+            inverse: Matrix4::scaling(&Vec3::new(1. / factors.x, 1. / factors.y, 1. / factors.z)),
+        }
+    }
+
+    pub fn rotation(axis: &Vec3, degrees: f64) -> Self {
+        Self::from_quaternion(&Quaternion::from_axis_angle(axis, degrees))
+    }
+
+    pub fn from_quaternion(rotation: &Quaternion) -> Self {
+        let matrix = rotation.to_matrix();
+        // The inverse of a rotation is its transpose.
+        let inverse = matrix.transpose();
+        Self { matrix, inverse }
+    }
+
+    /// The transform applying `self` first and `next` second.
+    pub fn then(&self, next: &Transform) -> Self {
+        Self {
+            matrix: &next.matrix * &self.matrix,
+            inverse: &self.inverse * &next.inverse,
+        }
+    }
+
+    pub fn transform_point(&self, point: &Point3) -> Point3 {
+        self.matrix.transform_point(point)
+    }
+
+    pub fn transform_vector(&self, vector: &Vec3) -> Vec3 {
+        self.matrix.transform_vector(vector)
+    }
+
+    /// Normals are transformed by the inverse transpose to stay perpendicular to the surface
+    /// under non-uniform scaling. The result is not normalized.
+    pub fn transform_normal(&self, normal: &Vec3) -> Vec3 {
+        self.inverse.transpose().transform_vector(normal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(actual: &Vec3, expected: &Vec3) {
+        assert!(
+            (actual - expected).length() < 1e-9,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn inverse_undoes_the_matrix() {
+        let matrix = Matrix4::translation(&Vec3::new(1., 2., 3.))
+            * Matrix4::rotation(&Vec3::new(1., 1., 0.), 30.)
+            * Matrix4::scaling(&Vec3::new(2., 3., 4.));
+        let point = Point3::new(-1., 0.5, 7.);
+        let inverse = matrix.inverse().unwrap();
+        assert_near(
+            &inverse.transform_point(&matrix.transform_point(&point)),
+            &point,
+        );
+        assert!(Matrix4::scaling(&Vec3::new(1., 0., 1.)).inverse().is_none());
+    }
+
+    #[test]
+    fn quaternion_rotation_matches_matrix() {
+        let rotation = Quaternion::from_axis_angle(&Vec3::new(0., 1., 0.), 90.);
+        let vector = Vec3::new(1., 0., 0.);
+        assert_near(&rotation.rotate(&vector), &Vec3::new(0., 0., -1.));
+        assert_near(
+            &rotation.to_matrix().transform_vector(&vector),
+            &Vec3::new(0., 0., -1.),
+        );
+
+        let halfway = Quaternion::identity().slerp(&rotation, 0.5);
+        let expected = Quaternion::from_axis_angle(&Vec3::new(0., 1., 0.), 45.);
+        assert!((halfway.dot(&expected) - 1.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn normals_stay_perpendicular_under_scaling() {
+        let transform = Transform::scaling(&Vec3::new(4., 1., 1.));
+        let tangent = transform.transform_vector(&Vec3::new(1., -1., 0.));
+        let normal = transform.transform_normal(&Vec3::new(1., 1., 0.));
+        assert!(tangent.dot(&normal).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,116 @@
+//! Homogeneous participating media filling the inside of objects. Unlike `ConstantMedium`,
+//! which is an object of its own, these are tracked by the integrator as rays cross the
+//! surfaces of the objects holding them, so media can be nested and surfaces can sit inside
+//! them: colored liquid in a glass, or objects in a fog-filled room.
+
+use crate::checkpoint::Fingerprint;
+use crate::color::Color;
+use crate::materials::{Isotropic, Material};
+use crate::random::random;
+use std::rc::Rc;
+
+#[derive(Debug)]
+pub struct Medium {
+    absorption: Color,        // Light absorbed per unit of distance, for every channel
+    scattering: f64,          // Probability of scattering per unit of distance
+    phase_function: Material, // Direction and color of scattered light
+}
+
+impl Medium {
+    pub fn new(absorption: Color, scattering: f64, phase_function: Material) -> Self {
+        Self {
+            absorption,
+            scattering,
+            phase_function,
+        }
+    }
+
+    /// A clear medium only absorbing light, like a colored liquid.
+    pub fn absorbing(absorption: Color) -> Self {
+        Self::new(absorption, 0., Isotropic::new(Color::one()).into())
+    }
+
+    pub fn phase_function(&self) -> &Material {
+        &self.phase_function
+    }
+
+    pub fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        fingerprint.add_vec3(&self.absorption);
+        fingerprint.add_f64(self.scattering);
+        self.phase_function.fingerprint(fingerprint);
+    }
+
+    /// Fraction of the light that isn't absorbed over `distance`, following Beer-Lambert's law.
+    pub fn transmittance(&self, distance: f64) -> Color {
+        // A channel without absorption transmits everything, even over an infinite distance.
+        let channel = |absorption: f64| {
+            if absorption == 0. {
+                1.
+            } else {
+                (-absorption * distance).exp()
+            }
+        };
+        Color::new(
+            channel(self.absorption.x),
+            channel(self.absorption.y),
+            channel(self.absorption.z),
+        )
+    }
+
+    /// Samples the distance at which light scatters, if it does before `max_distance`.
+    pub fn sample_scattering(&self, max_distance: f64) -> Option<f64> {
+        if self.scattering <= 0. {
+            return None;
+        }
+        let distance = -(1. - random()).ln() / self.scattering;
+        (distance < max_distance).then_some(distance)
+    }
+}
+
+/// The media a ray is in, innermost last. Objects entered are pushed and objects left are
+/// removed wherever they are, so overlapping media that aren't properly nested still work.
+#[derive(Clone, Default)]
+pub(crate) struct MediumStack(Vec<Rc<Medium>>);
+
+impl MediumStack {
+    pub fn new(outermost: Option<Rc<Medium>>) -> Self {
+        Self(outermost.into_iter().collect())
+    }
+
+    pub fn current(&self) -> Option<&Rc<Medium>> {
+        self.0.last()
+    }
+
+    pub fn enter(&mut self, medium: Rc<Medium>) {
+        self.0.push(medium)
+    }
+
+    pub fn leave(&mut self, medium: &Rc<Medium>) {
+        if let Some(index) = self.0.iter().rposition(|m| Rc::ptr_eq(m, medium)) {
+            self.0.remove(index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stack_tracks_the_innermost_medium() {
+        let room = Rc::new(Medium::absorbing(Color::zero()));
+        let glass = Rc::new(Medium::absorbing(Color::new(1., 0., 0.)));
+        let liquid = Rc::new(Medium::absorbing(Color::new(0., 1., 0.)));
+
+        let mut stack = MediumStack::new(Some(room.clone()));
+        stack.enter(glass.clone());
+        stack.enter(liquid.clone());
+        assert!(Rc::ptr_eq(stack.current().unwrap(), &liquid));
+        // Leaving the glass while still in the liquid, when they overlap.
+        stack.leave(&glass);
+        assert!(Rc::ptr_eq(stack.current().unwrap(), &liquid));
+        stack.leave(&liquid);
+        assert!(Rc::ptr_eq(stack.current().unwrap(), &room));
+        assert_eq!(liquid.transmittance(2.), Color::new(1., (-2f64).exp(), 1.));
+    }
+}
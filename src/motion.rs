@@ -0,0 +1,337 @@
+use crate::aabb::Aabb;
+use crate::checkpoint::Fingerprint;
+use crate::matrix::{Quaternion, Transform};
+use crate::vec3::{Point3, Vec3};
+use std::fmt;
+
+/// A position at a point in time of the camera shutter.
+#[derive(Clone, Debug)]
+pub struct Keyframe {
+    pub time: f64,
+    pub position: Point3,
+}
+
+impl Keyframe {
+    pub fn new(time: f64, position: Point3) -> Self {
+        Self { time, position }
+    }
+}
+
+/// A transform at a point in time of the camera shutter: the object is scaled, then rotated,
+/// then translated.
+#[derive(Clone, Debug)]
+pub struct TransformKeyframe {
+    pub time: f64,
+    pub scale: Vec3,
+    pub rotation: Quaternion,
+    pub translation: Vec3,
+}
+
+impl TransformKeyframe {
+    pub fn new(time: f64, scale: Vec3, rotation: Quaternion, translation: Vec3) -> Self {
+        Self {
+            time,
+            scale,
+            rotation,
+            translation,
+        }
+    }
+
+    fn transform(&self) -> Transform {
+        Transform::scaling(&self.scale)
+            .then(&Transform::from_quaternion(&self.rotation))
+            .then(&Transform::translation(&self.translation))
+    }
+}
+
+#[derive(Debug)]
+pub enum MotionError {
+    NoKeyframes,
+    NonPositiveScale,
+}
+
+impl fmt::Display for MotionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoKeyframes => write!(f, "keyframed motion needs a keyframe"),
+            Self::NonPositiveScale => write!(f, "scale factors must be positive"),
+        }
+    }
+}
+
+impl std::error::Error for MotionError {}
+
+/// How the position of an object changes while the shutter is open. The object is translated
+/// along the motion without turning: `TransformMotion` also animates its rotation and scale.
+#[derive(Clone, Debug)]
+pub enum Motion {
+    Static(Point3),
+    /// Moves at a constant speed, being at `from` at shutter time 0 and at `to` at time 1, and
+    /// resting there before and after them like keyframed motion.
+    Linear {
+        from: Point3,
+        to: Point3,
+    },
+    /// Moves along straight segments between keyframes sorted by time, and rests at the first
+    /// and the last keyframe before and after them.
+    Keyframed(Vec<Keyframe>),
+}
+
+impl Motion {
+    pub fn linear(from: Point3, to: Point3) -> Self {
+        Self::Linear { from, to }
+    }
+
+    pub fn keyframed(mut keyframes: Vec<Keyframe>) -> Result<Self, MotionError> {
+        if keyframes.is_empty() {
+            return Err(MotionError::NoKeyframes);
+        }
+        keyframes.sort_by(|l, r| l.time.total_cmp(&r.time));
+        Ok(Self::Keyframed(keyframes))
+    }
+
+    pub fn position_at(&self, shutter_time: f64) -> Point3 {
+        match self {
+            Self::Static(position) => position.clone(),
+            Self::Linear { from, to } => from + (to - from) * shutter_time.clamp(0., 1.),
+            Self::Keyframed(keyframes) => {
+                let (previous, next, t) = bracket(keyframes, |key| key.time, shutter_time);
+                &previous.position + (&next.position - &previous.position) * t
+            }
+        }
+    }
+
+    /// The box swept by `extent` around the moving position over the whole motion.
+    pub fn swept_box(&self, extent: &Vec3) -> Aabb {
+        let around = |position: &Point3| Aabb::new(&(position - extent), &(position + extent));
+        match self {
+            Self::Static(position) => around(position),
+            Self::Linear { from, to } => around(from).union(&around(to)),
+            Self::Keyframed(keyframes) => keyframes.iter().fold(Aabb::empty(), |bounds, key| {
+                bounds.union(&around(&key.position))
+            }),
+        }
+    }
+
+    pub fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        match self {
+            Self::Static(position) => {
+                fingerprint.add_bytes(b"static");
+                fingerprint.add_vec3(position);
+            }
+            Self::Linear { from, to } => {
+                fingerprint.add_bytes(b"linear");
+                fingerprint.add_vec3(from);
+                fingerprint.add_vec3(to);
+            }
+            Self::Keyframed(keyframes) => {
+                fingerprint.add_bytes(b"keyframed");
+                fingerprint.add_u64(keyframes.len() as u64);
+                for key in keyframes {
+                    fingerprint.add_f64(key.time);
+                    fingerprint.add_vec3(&key.position);
+                }
+            }
+        }
+    }
+}
+
+/// How the scale, rotation and translation of an object change while the shutter is open,
+/// along keyframes sorted by time. The rotation turns at a constant speed between keyframes,
+/// the scale and the translation change linearly, and the object rests at the first and the
+/// last keyframe before and after them.
+#[derive(Clone, Debug)]
+pub struct TransformMotion {
+    keyframes: Vec<TransformKeyframe>,
+}
+
+impl TransformMotion {
+    /// Mirroring isn't supported, since scale factors interpolated between opposite signs
+    /// would flatten the object on the way.
+    pub fn keyframed(mut keyframes: Vec<TransformKeyframe>) -> Result<Self, MotionError> {
+        if keyframes.is_empty() {
+            return Err(MotionError::NoKeyframes);
+        }
+        if keyframes.iter().any(|key| {
+            // Written so that NaN scales are rejected too.
+            !(key.scale.x > 0. && key.scale.y > 0. && key.scale.z > 0.)
+        }) {
+            return Err(MotionError::NonPositiveScale);
+        }
+        keyframes.sort_by(|l, r| l.time.total_cmp(&r.time));
+        Ok(Self { keyframes })
+    }
+
+    pub fn transform_at(&self, shutter_time: f64) -> Transform {
+        let (previous, next, t) = bracket(&self.keyframes, |key| key.time, shutter_time);
+        if t == 0. {
+            return previous.transform();
+        }
+        TransformKeyframe::new(
+            shutter_time,
+            &previous.scale + (&next.scale - &previous.scale) * t,
+            previous.rotation.slerp(&next.rotation, t),
+            &previous.translation + (&next.translation - &previous.translation) * t,
+        )
+        .transform()
+    }
+
+    /// A box containing `bounds` carried along the whole motion. It is tight while the
+    /// rotation doesn't change, since every point then moves along a straight line between
+    /// keyframes. While it turns, the object is bounded by the sphere around its origin it
+    /// can't leave whatever the rotation.
+    pub fn swept_box(&self, bounds: &Aabb) -> Aabb {
+        let mut swept = bounds.transformed(&self.keyframes[0].transform());
+        if bounds.is_empty() {
+            return swept;
+        }
+        let radius = bounds
+            .corners()
+            .iter()
+            .fold(0f64, |max, corner| max.max(corner.length()));
+        for pair in self.keyframes.windows(2) {
+            let (previous, next) = (&pair[0], &pair[1]);
+            swept = swept.union(&bounds.transformed(&next.transform()));
+            if previous.rotation.dot(&next.rotation).abs() < 1. {
+                let scale = [&previous.scale, &next.scale]
+                    .iter()
+                    .fold(0f64, |max, scale| {
+                        max.max(scale.x).max(scale.y).max(scale.z)
+                    });
+                let translations = Aabb::new(&previous.translation, &next.translation);
+                swept = swept.union(&translations.expanded(radius * scale));
+            }
+        }
+        swept
+    }
+
+    pub fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        fingerprint.add_u64(self.keyframes.len() as u64);
+        for key in &self.keyframes {
+            let rotation = &key.rotation;
+            fingerprint.add_f64(key.time);
+            fingerprint.add_vec3(&key.scale);
+            for value in [rotation.w, rotation.x, rotation.y, rotation.z] {
+                fingerprint.add_f64(value);
+            }
+            fingerprint.add_vec3(&key.translation);
+        }
+    }
+}
+
+// The keyframes around `shutter_time` among keyframes sorted by time, and how far between
+// them it is. Before the first and after the last keyframe, both are the same keyframe.
+fn bracket<K>(keyframes: &[K], time: impl Fn(&K) -> f64, shutter_time: f64) -> (&K, &K, f64) {
+    let next = keyframes.partition_point(|key| time(key) <= shutter_time);
+    if next == 0 {
+        return (&keyframes[0], &keyframes[0], 0.);
+    }
+    if next == keyframes.len() {
+        return (&keyframes[next - 1], &keyframes[next - 1], 0.);
+    }
+    let (previous, next) = (&keyframes[next - 1], &keyframes[next]);
+    let t = (shutter_time - time(previous)) / (time(next) - time(previous));
+    (previous, next, t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keyframed_motion_interpolates_between_keys() {
+        let motion = Motion::keyframed(vec![
+            Keyframe::new(1., Point3::new(2., 2., 0.)),
+            Keyframe::new(0., Point3::zero()),
+        ])
+        .unwrap();
+        assert_eq!(motion.position_at(-1.), Point3::zero());
+        assert_eq!(motion.position_at(0.25), Point3::new(0.5, 0.5, 0.));
+        assert_eq!(motion.position_at(3.), Point3::new(2., 2., 0.));
+
+        let bounds = motion.swept_box(&Vec3::one());
+        assert_eq!(bounds.min, Point3::new(-1., -1., -1.));
+        assert_eq!(bounds.max, Point3::new(3., 3., 1.));
+    }
+
+    #[test]
+    fn linear_motion_rests_outside_the_shutter_interval() {
+        let motion = Motion::linear(Point3::zero(), Point3::new(2., 0., 0.));
+        assert_eq!(motion.position_at(0.5), Point3::new(1., 0., 0.));
+        assert_eq!(motion.position_at(-1.), Point3::zero());
+        assert_eq!(motion.position_at(2.), Point3::new(2., 0., 0.));
+    }
+
+    #[test]
+    fn keyframed_motion_needs_keyframes() {
+        assert!(matches!(
+            Motion::keyframed(vec![]),
+            Err(MotionError::NoKeyframes)
+        ));
+        assert!(matches!(
+            TransformMotion::keyframed(vec![]),
+            Err(MotionError::NoKeyframes)
+        ));
+        let flat = TransformKeyframe::new(
+            0.,
+            Vec3::new(1., 0., 1.),
+            Quaternion::identity(),
+            Vec3::zero(),
+        );
+        assert!(matches!(
+            TransformMotion::keyframed(vec![flat]),
+            Err(MotionError::NonPositiveScale)
+        ));
+    }
+
+    #[test]
+    fn transform_motion_turns_and_stays_in_its_swept_box() {
+        let y = Vec3::new(0., 1., 0.);
+        let motion = TransformMotion::keyframed(vec![
+            TransformKeyframe::new(
+                1.,
+                Vec3::one() * 2.,
+                Quaternion::from_axis_angle(&y, 180.),
+                Vec3::new(4., 0., 0.),
+            ),
+            TransformKeyframe::new(0., Vec3::one(), Quaternion::identity(), Vec3::zero()),
+        ])
+        .unwrap();
+
+        // Halfway, a quarter turn with a scale of 1.5 and a translation of 2.
+        let point = motion
+            .transform_at(0.5)
+            .transform_point(&Point3::new(1., 0., 0.));
+        assert!((point - Point3::new(2., 0., -1.5)).length() < 1e-9);
+        let point = motion
+            .transform_at(-1.)
+            .transform_point(&Point3::new(1., 0., 0.));
+        assert!((point - Point3::new(1., 0., 0.)).length() < 1e-9);
+
+        let bounds = Aabb::new(&Point3::new(1., -1., -1.), &Point3::new(2., 1., 1.));
+        let swept = motion.swept_box(&bounds);
+        for step in 0..=100 {
+            let transform = motion.transform_at(step as f64 / 100.);
+            for corner in bounds.corners() {
+                let point = transform.transform_point(&corner);
+                assert!((0..3).all(|axis| swept.min[axis] <= point[axis] + 1e-9
+                    && point[axis] <= swept.max[axis] + 1e-9));
+            }
+        }
+
+        // Without turning, the box is exactly the one swept between keyframes.
+        let motion = TransformMotion::keyframed(vec![
+            TransformKeyframe::new(0., Vec3::one(), Quaternion::identity(), Vec3::zero()),
+            TransformKeyframe::new(
+                1.,
+                Vec3::one(),
+                Quaternion::identity(),
+                Vec3::new(4., 0., 0.),
+            ),
+        ])
+        .unwrap();
+        let swept = motion.swept_box(&bounds);
+        assert_eq!(swept.min, Point3::new(1., -1., -1.));
+        assert_eq!(swept.max, Point3::new(6., 1., 1.));
+    }
+}
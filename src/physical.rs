@@ -0,0 +1,145 @@
+//! A camera described the way photographers set one up: focal length and sensor size frame
+//! the image, the f-number sets the depth of field, and together with the shutter time and
+//! the ISO sensitivity it sets the exposure.
+//!
+//! Scene radiance is taken in units where an exposure value of 0 (f/1 for 1 s at ISO 100)
+//! renders it unchanged. Brighter camera settings scale it up and darker ones scale it down.
+
+use crate::camera_settings::CameraSettings;
+
+#[derive(Clone, Debug)]
+pub struct PhysicalCamera {
+    pub focal_length: f64,    // Lens focal length in millimeters
+    pub sensor_width: f64,    // Sensor width in millimeters
+    pub sensor_height: f64,   // Sensor height in millimeters
+    pub f_number: f64,        // Focal length over the aperture diameter
+    pub shutter_time: f64,    // Exposure time in seconds, for the exposure but not motion blur
+    pub iso: f64,             // Sensor sensitivity
+    pub units_per_meter: f64, // Size of a meter in scene units
+}
+
+impl PhysicalCamera {
+    /// A full frame (36 x 24 mm) camera exposing for 1/125 s at ISO 100, in a scene measured in
+    /// meters.
+    pub fn new(focal_length: f64, f_number: f64) -> Self {
+        Self {
+            focal_length,
+            sensor_width: 36.,
+            sensor_height: 24.,
+            f_number,
+            shutter_time: 1. / 125.,
+            iso: 100.,
+            units_per_meter: 1.,
+        }
+    }
+
+    pub fn with_sensor(mut self, width: f64, height: f64) -> Self {
+        self.sensor_width = width;
+        self.sensor_height = height;
+        self
+    }
+
+    pub fn with_exposure(mut self, shutter_time: f64, iso: f64) -> Self {
+        self.shutter_time = shutter_time;
+        self.iso = iso;
+        self
+    }
+
+    pub fn with_units_per_meter(mut self, units_per_meter: f64) -> Self {
+        self.units_per_meter = units_per_meter;
+        self
+    }
+
+    pub fn aspect_ratio(&self) -> f64 {
+        self.sensor_width / self.sensor_height
+    }
+
+    /// Vertical field of view in degrees, with the lens focused at infinity.
+    pub fn vfov(&self) -> f64 {
+        2. * (self.sensor_height / (2. * self.focal_length))
+            .atan()
+            .to_degrees()
+    }
+
+    /// Radius of the lens aperture in scene units.
+    pub fn aperture_radius(&self) -> f64 {
+        let diameter = self.focal_length / self.f_number;
+        diameter / 2. / 1000. * self.units_per_meter
+    }
+
+    /// The `defocus_angle` of `Camera::new` giving the aperture of this camera when focused at
+    /// `focus_distance` scene units.
+    pub fn defocus_angle(&self, focus_distance: f64) -> f64 {
+        2. * (self.aperture_radius() / focus_distance)
+            .atan()
+            .to_degrees()
+    }
+
+    /// Exposure value of the camera settings, normalized to ISO 100.
+    pub fn ev100(&self) -> f64 {
+        (self.f_number * self.f_number / self.shutter_time).log2() - (self.iso / 100.).log2()
+    }
+
+    /// Factor scaling the scene radiance into the rendered image.
+    pub fn exposure(&self) -> f64 {
+        (-self.ev100()).exp2()
+    }
+
+    /// The `settings` with the field of view, depth of field and exposure of this camera,
+    /// focused at `focus_distance` scene units. The image height follows the sensor aspect
+    /// ratio.
+    ///
+    /// The shutter time only sets the exposure. Motion blur still spans the shutter interval of
+    /// the `settings`, which is in the time of the object motion rather than in seconds.
+    pub fn settings(&self, settings: &CameraSettings, focus_distance: f64) -> CameraSettings {
+        settings
+            .clone()
+            .with_image_size(settings.image_width, self.aspect_ratio())
+            .with_vfov(self.vfov())
+            .with_defocus(self.defocus_angle(focus_distance), focus_distance)
+            .with_exposure(self.exposure())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normal_lens_on_full_frame() {
+        let camera = PhysicalCamera::new(50., 2.);
+        assert!((camera.vfov() - 26.99).abs() < 0.01);
+        assert_eq!(camera.aspect_ratio(), 1.5);
+        // A 25 mm aperture, so a 12.5 mm radius.
+        assert!((camera.aperture_radius() - 0.0125).abs() < 1e-12);
+        let angle = camera.defocus_angle(2.).to_radians();
+        assert!(((angle / 2.).tan() * 2. - 0.0125).abs() < 1e-12);
+    }
+
+    #[test]
+    fn exposure_halves_with_every_stop() {
+        let reference = PhysicalCamera::new(50., 1.).with_exposure(1., 100.);
+        assert_eq!(reference.ev100(), 0.);
+        assert_eq!(reference.exposure(), 1.);
+
+        let faster = reference.clone().with_exposure(0.5, 100.);
+        assert_eq!(faster.exposure(), 0.5);
+        let sensitive = reference.clone().with_exposure(0.5, 200.);
+        assert_eq!(sensitive.exposure(), 1.);
+        let stopped_down = PhysicalCamera::new(50., 2.).with_exposure(1., 100.);
+        assert_eq!(stopped_down.exposure(), 0.25);
+    }
+
+    #[test]
+    fn settings_take_the_lens_and_sensor() {
+        let physical = PhysicalCamera::new(50., 2.);
+        let settings = physical.settings(&CameraSettings::new().with_image_size(600, 1.), 2.);
+        assert_eq!(settings.image_width, 600);
+        assert_eq!(settings.aspect_ratio, 1.5);
+        assert_eq!(settings.vfov, physical.vfov());
+        assert_eq!(settings.defocus_angle, physical.defocus_angle(2.));
+        assert_eq!(settings.focus_distance, 2.);
+        assert_eq!(settings.exposure, physical.exposure());
+        assert!(settings.build().is_ok());
+    }
+}
@@ -0,0 +1,184 @@
+//! Real roots of polynomials up to the fourth degree, as needed for intersecting rays with
+//! quadric surfaces and tori. All solvers return the roots in ascending order.
+
+const EPSILON: f64 = 1e-9;
+
+fn is_zero(x: f64) -> bool {
+    x.abs() < EPSILON
+}
+
+/// Roots of `a x² + b x + c`, falling back to the linear equation when `a` is zero.
+pub fn solve_quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if a == 0. {
+        if b == 0. {
+            return Vec::new();
+        }
+        return vec![-c / b];
+    }
+    let discriminant = b * b - 4. * a * c;
+    if discriminant < 0. {
+        return Vec::new();
+    }
+    // Avoids the cancellation of the textbook formula when b is much larger than a and c.
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    if q == 0. {
+        return vec![0.];
+    }
+    let (x0, x1) = (q / a, c / q);
+    if x0 < x1 {
+        vec![x0, x1]
+    } else {
+        vec![x1, x0]
+    }
+}
+
+/// Roots of `a x³ + b x² + c x + d`, with Cardano's method.
+pub fn solve_cubic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    if a == 0. {
+        return solve_quadratic(b, c, d);
+    }
+    // Normal form x³ + Ax² + Bx + C = 0, and with x = y - A/3 the depressed y³ + 3py + 2q = 0.
+    let (a, b, c) = (b / a, c / a, d / a);
+    let square_a = a * a;
+    let p = (-square_a / 3. + b) / 3.;
+    let q = (2. / 27. * a * square_a - a * b / 3. + c) / 2.;
+    let cube_p = p * p * p;
+    let discriminant = q * q + cube_p;
+
+    let mut roots = if is_zero(discriminant) {
+        if is_zero(q) {
+            vec![0.]
+        } else {
+            let u = (-q).cbrt();
+            vec![2. * u, -u]
+        }
+    } else if discriminant < 0. {
+        // Three real roots.
+        let phi = (-q / (-cube_p).sqrt()).clamp(-1., 1.).acos() / 3.;
+        let t = 2. * (-p).sqrt();
+        vec![
+            t * phi.cos(),
+            -t * (phi + std::f64::consts::FRAC_PI_3).cos(),
+            -t * (phi - std::f64::consts::FRAC_PI_3).cos(),
+        ]
+    } else {
+        let sqrt_discriminant = discriminant.sqrt();
+        vec![(sqrt_discriminant - q).cbrt() - (sqrt_discriminant + q).cbrt()]
+    };
+
+    for root in roots.iter_mut() {
+        *root -= a / 3.;
+    }
+    finish(roots, |x| ((x + a) * x + b) * x + c)
+}
+
+/// Roots of `a x⁴ + b x³ + c x² + d x + e`, with Ferrari's method.
+pub fn solve_quartic(a: f64, b: f64, c: f64, d: f64, e: f64) -> Vec<f64> {
+    if a == 0. {
+        return solve_cubic(b, c, d, e);
+    }
+    // Normal form x⁴ + Ax³ + Bx² + Cx + D = 0, and with x = y - A/4 the depressed
+    // y⁴ + py² + qy + r = 0.
+    let (a, b, c, d) = (b / a, c / a, d / a, e / a);
+    let square_a = a * a;
+    let p = -3. / 8. * square_a + b;
+    let q = square_a * a / 8. - a * b / 2. + c;
+    let r = -3. / 256. * square_a * square_a + square_a * b / 16. - a * c / 4. + d;
+
+    let mut roots = if is_zero(r) {
+        // y(y³ + py + q) = 0
+        let mut roots = solve_cubic(1., 0., p, q);
+        roots.push(0.);
+        roots
+    } else {
+        // Any real root of the resolvent cubic splits the quartic into two quadratics.
+        let z = solve_cubic(1., -p / 2., -r, r * p / 2. - q * q / 8.)[0];
+        let u = z * z - r;
+        let v = 2. * z - p;
+        let u = if is_zero(u) {
+            0.
+        } else if u > 0. {
+            u.sqrt()
+        } else {
+            return Vec::new();
+        };
+        let v = if is_zero(v) {
+            0.
+        } else if v > 0. {
+            v.sqrt()
+        } else {
+            return Vec::new();
+        };
+        let v = if q < 0. { -v } else { v };
+        let mut roots = solve_quadratic(1., v, z - u);
+        roots.extend(solve_quadratic(1., -v, z + u));
+        roots
+    };
+
+    for root in roots.iter_mut() {
+        *root -= a / 4.;
+    }
+    finish(roots, |x| (((x + a) * x + b) * x + c) * x + d)
+}
+
+// Polishes the roots with a few Newton steps on the original polynomial, since the closed form
+// solutions lose a lot of precision, and sorts them.
+fn finish<F: Fn(f64) -> f64>(mut roots: Vec<f64>, polynomial: F) -> Vec<f64> {
+    for root in roots.iter_mut() {
+        for _ in 0..2 {
+            let h = 1e-7 * root.abs().max(1.);
+            let value = polynomial(*root);
+            let derivative = (polynomial(*root + h) - polynomial(*root - h)) / (2. * h);
+            if derivative == 0. || !derivative.is_finite() {
+                break;
+            }
+            let polished = *root - value / derivative;
+            if polynomial(polished).abs() < value.abs() {
+                *root = polished;
+            }
+        }
+    }
+    roots.sort_by(f64::total_cmp);
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_roots(actual: Vec<f64>, expected: &[f64]) {
+        assert_eq!(
+            actual.len(),
+            expected.len(),
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+        for (actual, expected) in actual.iter().zip(expected) {
+            assert!(
+                (actual - expected).abs() < 1e-9,
+                "{} != {}",
+                actual,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn solves_quadratics_and_cubics() {
+        assert_roots(solve_quadratic(1., -3., 2.), &[1., 2.]);
+        assert_roots(solve_quadratic(1., 0., 1.), &[]);
+        // (x + 1)(x - 2)(x - 5)
+        assert_roots(solve_cubic(2., -12., 6., 20.), &[-1., 2., 5.]);
+        assert_roots(solve_cubic(1., 0., 0., -8.), &[2.]);
+    }
+
+    #[test]
+    fn solves_quartics() {
+        // (x - 1)(x - 2)(x - 3)(x - 4)
+        assert_roots(solve_quartic(1., -10., 35., -50., 24.), &[1., 2., 3., 4.]);
+        // (x² + 1)(x - 3)(x + 0.5)
+        assert_roots(solve_quartic(1., -2.5, -0.5, -2.5, -1.5), &[-0.5, 3.]);
+        assert_roots(solve_quartic(1., 0., 1., 0., 1.), &[]);
+    }
+}
@@ -0,0 +1,77 @@
+use crate::sampling::SampleBuffer;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Settings for progressive rendering: the image is refined in passes, each one bringing every
+/// pixel up to twice the samples of the previous pass, until the time budget or the sample cap
+/// is exhausted.
+#[derive(Clone, Debug)]
+pub struct ProgressiveSettings {
+    pub time_limit: Option<Duration>, // Wall-clock budget, unlimited if not set
+    pub max_samples: u32,             // Samples per pixel at which rendering stops
+    pub first_pass_samples: u32,      // Samples per pixel taken by the first pass
+    pub snapshot_path: Option<PathBuf>, // Where intermediate images are written
+    pub snapshot_interval: Duration,  // Minimal time between two intermediate images
+}
+
+impl ProgressiveSettings {
+    pub fn new(max_samples: u32) -> Self {
+        Self {
+            time_limit: None,
+            max_samples: max_samples.max(1),
+            first_pass_samples: 1,
+            snapshot_path: None,
+            snapshot_interval: Duration::from_secs(30),
+        }
+    }
+
+    pub fn with_time_limit(mut self, time_limit: Duration) -> Self {
+        self.time_limit = Some(time_limit);
+        self
+    }
+
+    pub fn with_first_pass_samples(mut self, samples: u32) -> Self {
+        self.first_pass_samples = samples.clamp(1, self.max_samples);
+        self
+    }
+
+    pub fn with_snapshots<P: Into<PathBuf>>(mut self, path: P, interval: Duration) -> Self {
+        self.snapshot_path = Some(path.into());
+        self.snapshot_interval = interval;
+        self
+    }
+
+    /// Samples per pixel the pass following one that reached `samples` should reach.
+    pub fn next_pass_samples(&self, samples: u32) -> u32 {
+        if samples == 0 {
+            return self.first_pass_samples;
+        }
+        samples.saturating_mul(2).min(self.max_samples)
+    }
+
+    pub(crate) fn write_snapshot(&self, buffer: &SampleBuffer) {
+        if let Some(path) = &self.snapshot_path {
+            match buffer.to_image().save_ppm(path) {
+                Ok(()) => log::info!("Wrote intermediate image to {}", path.display()),
+                Err(error) => log::warn!("Failed to write {}: {}", path.display(), error),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passes_double_up_to_the_sample_cap() {
+        let settings = ProgressiveSettings::new(100).with_first_pass_samples(4);
+        let mut samples = 0;
+        let mut passes = Vec::new();
+        while samples < settings.max_samples {
+            samples = settings.next_pass_samples(samples);
+            passes.push(samples);
+        }
+        assert_eq!(passes, vec![4, 8, 16, 32, 64, 100]);
+    }
+}
@@ -0,0 +1,212 @@
+//! How the camera maps image positions to rays. Rays are computed in the camera frame, where
+//! x points to the right, y up and the camera looks down negative z.
+
+use crate::checkpoint::Fingerprint;
+use crate::vec3::Vec3;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// How angles from the view direction map to distances from the center of a fisheye image.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum FisheyeMapping {
+    /// Distance proportional to the angle, keeping angles measurable across the image.
+    Equidistant,
+    /// Distance proportional to the chord of the angle, keeping areas in proportion to the
+    /// solid angles they cover, like most real fisheye lenses.
+    Equisolid,
+}
+
+/// One of the six square 90° faces of a cubemap, named from the camera point of view.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum CubeFace {
+    Front,
+    Back,
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl CubeFace {
+    pub const ALL: [CubeFace; 6] = [
+        CubeFace::Front,
+        CubeFace::Back,
+        CubeFace::Left,
+        CubeFace::Right,
+        CubeFace::Up,
+        CubeFace::Down,
+    ];
+
+    // Direction in the camera frame through the point a, b of the face, both from -1 to 1 going
+    // right and up. The up and down faces are seen as if tilting the head from the front face.
+    fn direction(&self, a: f64, b: f64) -> Vec3 {
+        match self {
+            CubeFace::Front => Vec3::new(a, b, -1.),
+            CubeFace::Back => Vec3::new(-a, b, 1.),
+            CubeFace::Left => Vec3::new(-1., b, -a),
+            CubeFace::Right => Vec3::new(1., b, a),
+            CubeFace::Up => Vec3::new(a, 1., b),
+            CubeFace::Down => Vec3::new(a, -1., -b),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Projection {
+    /// Thin lens perspective over the vertical field of view of the camera.
+    #[default]
+    Perspective,
+    /// Parallel rays over a viewport `height` world units tall, for elevations and plans.
+    Orthographic { height: f64 },
+    /// A circular fisheye image inscribed in the image height, covering `fov` degrees across.
+    Fisheye { fov: f64, mapping: FisheyeMapping },
+    /// The full sphere of directions, longitude across the width and latitude down the height,
+    /// centered on the view direction. Meant for a 2:1 image.
+    Equirectangular,
+    /// One face of a cubemap around the camera. Meant for a square image.
+    CubemapFace(CubeFace),
+}
+
+impl Projection {
+    /// Origin offset and direction in the camera frame of the ray through the image position
+    /// `s`, `t`, both from 0 to 1 from the top left corner, of an image of the given aspect
+    /// ratio. Positions the projection doesn't cover, outside the circle of a fisheye image,
+    /// have no ray.
+    ///
+    /// Perspective rays depend on the focus plane and the lens, and are left to the camera.
+    pub fn camera_ray(&self, s: f64, t: f64, aspect_ratio: f64) -> Option<(Vec3, Vec3)> {
+        // Image position from the center, with y going up and 1 at the top edge.
+        let x = (2. * s - 1.) * aspect_ratio;
+        let y = 1. - 2. * t;
+        let forward = Vec3::new(0., 0., -1.);
+        match self {
+            Projection::Perspective => None,
+            Projection::Orthographic { height } => {
+                let origin = Vec3::new(x * height / 2., y * height / 2., 0.);
+                Some((origin, forward))
+            }
+            Projection::Fisheye { fov, mapping } => {
+                let radius = (x * x + y * y).sqrt();
+                if radius > 1. {
+                    return None;
+                }
+                let max_angle = fov.to_radians() / 2.;
+                let angle = match mapping {
+                    FisheyeMapping::Equidistant => radius * max_angle,
+                    FisheyeMapping::Equisolid => 2. * (radius * (max_angle / 2.).sin()).asin(),
+                };
+                let azimuth = y.atan2(x);
+                let direction = Vec3::new(
+                    angle.sin() * azimuth.cos(),
+                    angle.sin() * azimuth.sin(),
+                    -angle.cos(),
+                );
+                Some((Vec3::zero(), direction))
+            }
+            Projection::Equirectangular => {
+                let longitude = (s - 0.5) * 2. * PI;
+                let latitude = (0.5 - t) * PI;
+                let direction = Vec3::new(
+                    latitude.cos() * longitude.sin(),
+                    latitude.sin(),
+                    -latitude.cos() * longitude.cos(),
+                );
+                Some((Vec3::zero(), direction))
+            }
+            Projection::CubemapFace(face) => Some((Vec3::zero(), face.direction(2. * s - 1., y))),
+        }
+    }
+
+    pub fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        match self {
+            Projection::Perspective => fingerprint.add_bytes(b"perspective"),
+            Projection::Orthographic { height } => {
+                fingerprint.add_bytes(b"orthographic");
+                fingerprint.add_f64(*height);
+            }
+            Projection::Fisheye { fov, mapping } => {
+                fingerprint.add_bytes(b"fisheye");
+                fingerprint.add_f64(*fov);
+                fingerprint.add_bytes(&[*mapping as u8]);
+            }
+            Projection::Equirectangular => fingerprint.add_bytes(b"equirectangular"),
+            Projection::CubemapFace(face) => {
+                fingerprint.add_bytes(b"cubemap face");
+                fingerprint.add_bytes(&[*face as u8]);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(actual: &Vec3, expected: &Vec3) {
+        assert!(
+            (actual - expected).length() < 1e-9,
+            "{} is not {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn panoramas_look_forward_at_the_center() {
+        let forward = Vec3::new(0., 0., -1.);
+        for projection in [
+            Projection::Equirectangular,
+            Projection::CubemapFace(CubeFace::Front),
+            Projection::Fisheye {
+                fov: 180.,
+                mapping: FisheyeMapping::Equisolid,
+            },
+        ] {
+            let (origin, direction) = projection.camera_ray(0.5, 0.5, 1.).unwrap();
+            assert_near(&origin, &Vec3::zero());
+            assert_near(&direction.unit(), &forward);
+        }
+    }
+
+    #[test]
+    fn equirectangular_covers_every_direction() {
+        let projection = Projection::Equirectangular;
+        let (_, up) = projection.camera_ray(0.3, 0., 2.).unwrap();
+        assert_near(&up, &Vec3::new(0., 1., 0.));
+        let (_, right) = projection.camera_ray(0.75, 0.5, 2.).unwrap();
+        assert_near(&right, &Vec3::new(1., 0., 0.));
+        let (_, back) = projection.camera_ray(0., 0.5, 2.).unwrap();
+        assert_near(&back, &Vec3::new(0., 0., 1.));
+    }
+
+    #[test]
+    fn fisheye_edge_is_at_half_the_field_of_view() {
+        for mapping in [FisheyeMapping::Equidistant, FisheyeMapping::Equisolid] {
+            let projection = Projection::Fisheye { fov: 180., mapping };
+            let (_, direction) = projection.camera_ray(1., 0.5, 1.).unwrap();
+            assert_near(&direction, &Vec3::new(1., 0., 0.));
+            assert!(projection.camera_ray(0., 0., 1.).is_none());
+        }
+    }
+
+    #[test]
+    fn cube_faces_meet_at_their_edges() {
+        let right = Projection::CubemapFace(CubeFace::Right);
+        let front = Projection::CubemapFace(CubeFace::Front);
+        let up = Projection::CubemapFace(CubeFace::Up);
+        let (_, front_edge) = front.camera_ray(1., 0.5, 1.).unwrap();
+        let (_, right_edge) = right.camera_ray(0., 0.5, 1.).unwrap();
+        assert_near(&front_edge, &right_edge);
+        let (_, front_top) = front.camera_ray(0.5, 0., 1.).unwrap();
+        let (_, up_bottom) = up.camera_ray(0.5, 1., 1.).unwrap();
+        assert_near(&front_top, &up_bottom);
+    }
+
+    #[test]
+    fn orthographic_rays_are_parallel() {
+        let projection = Projection::Orthographic { height: 4. };
+        let (origin, direction) = projection.camera_ray(1., 0., 2.).unwrap();
+        assert_near(&origin, &Vec3::new(4., 2., 0.));
+        assert_near(&direction, &Vec3::new(0., 0., -1.));
+    }
+}
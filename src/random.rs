@@ -1,13 +1,50 @@
 use crate::vec3::Vec3;
-use rand::{thread_rng, Rng};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::cell::RefCell;
 use std::ops::Range;
 
+thread_local! {
+    static RNG: RefCell<ChaCha8Rng> = RefCell::new(ChaCha8Rng::from_entropy());
+}
+
+/// Everything needed to continue the random sequence of a thread exactly where it was.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RngState {
+    pub seed: [u8; 32],
+    pub stream: u64,
+    pub word_pos: u128,
+}
+
 pub fn random() -> f64 {
-    thread_rng().gen_range(0.0..=1.0)
+    RNG.with(|rng| rng.borrow_mut().gen_range(0.0..=1.0))
 }
 
 pub fn random_range(range: Range<f64>) -> f64 {
-    thread_rng().gen_range(range)
+    RNG.with(|rng| rng.borrow_mut().gen_range(range))
+}
+
+/// Makes the random sequence of the current thread reproducible.
+pub fn seed(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = ChaCha8Rng::seed_from_u64(seed));
+}
+
+pub fn rng_state() -> RngState {
+    RNG.with(|rng| {
+        let rng = rng.borrow();
+        RngState {
+            seed: rng.get_seed(),
+            stream: rng.get_stream(),
+            word_pos: rng.get_word_pos(),
+        }
+    })
+}
+
+pub fn set_rng_state(state: &RngState) {
+    let mut restored = ChaCha8Rng::from_seed(state.seed);
+    restored.set_stream(state.stream);
+    restored.set_word_pos(state.word_pos);
+    RNG.with(|rng| *rng.borrow_mut() = restored);
 }
 
 /// Returns the vector to a random point in the [-.5,-.5]-[+.5,+.5] unit square.
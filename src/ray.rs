@@ -3,11 +3,25 @@ use crate::vec3::{Point3, Vec3};
 pub struct Ray {
     pub origin: Point3,
     pub direction: Vec3,
+    pub shutter_time: f64, // Moment within the camera shutter interval the ray exists at
 }
 
 impl Ray {
     pub fn new(origin: Point3, direction: Vec3) -> Self {
-        Self { origin, direction }
+        Self::new_at(origin, direction, 0.)
+    }
+
+    pub fn new_at(origin: Point3, direction: Vec3, shutter_time: f64) -> Self {
+        Self {
+            origin,
+            direction,
+            shutter_time,
+        }
+    }
+
+    /// A new ray leaving a surface this ray hit, at the same shutter time.
+    pub fn scattered(&self, origin: Point3, direction: Vec3) -> Self {
+        Self::new_at(origin, direction, self.shutter_time)
     }
 
     pub fn position_at(&self, time: f64) -> Point3 {
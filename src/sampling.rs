@@ -0,0 +1,245 @@
+use crate::color::{luminance, Color};
+use crate::image::Image;
+use std::io::{self, Read, Write};
+
+// Luminance below which the noise target is treated as an absolute rather than relative error,
+// so that nearly black pixels don't demand an unbounded number of samples.
+const MIN_LUMINANCE: f64 = 0.01;
+
+/// Running statistics of the radiance samples taken for a single pixel.
+///
+/// The color is accumulated as a plain sum, while the luminance mean and variance are tracked
+/// with Welford's algorithm, which stays accurate for large sample counts.
+#[derive(Clone, Debug, Default)]
+pub struct PixelStats {
+    sum: Color,
+    count: u32,
+    luminance_mean: f64,
+    luminance_m2: f64,
+}
+
+impl PixelStats {
+    pub fn add(&mut self, sample: &Color) {
+        self.sum += sample;
+        self.count += 1;
+
+        let value = luminance(sample);
+        let delta = value - self.luminance_mean;
+        self.luminance_mean += delta / self.count as f64;
+        self.luminance_m2 += delta * (value - self.luminance_mean);
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn sum(&self) -> &Color {
+        &self.sum
+    }
+
+    pub fn mean(&self) -> Color {
+        if self.count == 0 {
+            return Color::zero();
+        }
+        &self.sum / self.count as f64
+    }
+
+    /// Unbiased sample variance of the pixel luminance.
+    pub fn variance(&self) -> f64 {
+        if self.count < 2 {
+            return 0.;
+        }
+        self.luminance_m2 / (self.count - 1) as f64
+    }
+
+    /// Estimated standard error of the mean relative to the pixel brightness.
+    pub fn relative_error(&self) -> f64 {
+        if self.count < 2 {
+            return f64::INFINITY;
+        }
+        let standard_error = (self.variance() / self.count as f64).sqrt();
+        standard_error / self.luminance_mean.max(MIN_LUMINANCE)
+    }
+
+    pub(crate) fn write_to<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        for value in [self.sum.x, self.sum.y, self.sum.z] {
+            out.write_all(&value.to_le_bytes())?;
+        }
+        out.write_all(&self.count.to_le_bytes())?;
+        out.write_all(&self.luminance_mean.to_le_bytes())?;
+        out.write_all(&self.luminance_m2.to_le_bytes())
+    }
+
+    pub(crate) fn read_from<R: Read>(input: &mut R) -> io::Result<Self> {
+        let mut f64_bytes = [0; 8];
+        let mut read_f64 = |input: &mut R| -> io::Result<f64> {
+            input.read_exact(&mut f64_bytes)?;
+            Ok(f64::from_le_bytes(f64_bytes))
+        };
+        let sum = Color::new(read_f64(input)?, read_f64(input)?, read_f64(input)?);
+        let mut u32_bytes = [0; 4];
+        input.read_exact(&mut u32_bytes)?;
+        Ok(Self {
+            sum,
+            count: u32::from_le_bytes(u32_bytes),
+            luminance_mean: read_f64(input)?,
+            luminance_m2: read_f64(input)?,
+        })
+    }
+}
+
+/// Settings for the "noise target" mode: keep sampling a pixel until its relative error drops
+/// below `noise_target` or it reaches `max_samples`.
+#[derive(Clone, Debug)]
+pub struct AdaptiveSampling {
+    pub min_samples: u32,   // Samples every pixel gets before its error is estimated
+    pub max_samples: u32,   // Upper bound of samples for a single pixel
+    pub noise_target: f64,  // Relative standard error at which a pixel counts as converged
+    pub batch_samples: u32, // Samples added to unconverged pixels in each pass
+}
+
+impl AdaptiveSampling {
+    pub fn new(min_samples: u32, max_samples: u32, noise_target: f64) -> Self {
+        let min_samples = min_samples.max(2);
+        Self {
+            min_samples,
+            max_samples: max_samples.max(min_samples),
+            noise_target,
+            batch_samples: 16,
+        }
+    }
+
+    pub fn with_batch_samples(mut self, batch_samples: u32) -> Self {
+        self.batch_samples = batch_samples.max(1);
+        self
+    }
+
+    pub fn is_converged(&self, stats: &PixelStats) -> bool {
+        stats.count() >= self.max_samples
+            || (stats.count() >= self.min_samples && stats.relative_error() <= self.noise_target)
+    }
+}
+
+/// Per-pixel sample statistics for a whole image.
+#[derive(Clone, Debug)]
+pub struct SampleBuffer {
+    width: usize,
+    height: usize,
+    pixels: Vec<PixelStats>,
+}
+
+impl SampleBuffer {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![PixelStats::default(); width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, i: usize, j: usize) -> &PixelStats {
+        &self.pixels[j * self.width + i]
+    }
+
+    pub fn get_mut(&mut self, i: usize, j: usize) -> &mut PixelStats {
+        &mut self.pixels[j * self.width + i]
+    }
+
+    pub fn pixels(&self) -> &[PixelStats] {
+        &self.pixels
+    }
+
+    pub(crate) fn from_pixels(width: usize, height: usize, pixels: Vec<PixelStats>) -> Self {
+        assert_eq!(pixels.len(), width * height);
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// The smallest number of samples taken by any pixel.
+    pub fn min_samples(&self) -> u32 {
+        self.pixels.iter().map(PixelStats::count).min().unwrap_or(0)
+    }
+
+    pub fn total_samples(&self) -> u64 {
+        self.pixels.iter().map(|stats| stats.count() as u64).sum()
+    }
+
+    /// The current estimate of the image: the mean of the samples of every pixel.
+    pub fn to_image(&self) -> Image {
+        let mut image = Image::new(self.width, self.height);
+        for j in 0..self.height {
+            for i in 0..self.width {
+                image.set(i, j, self.get(i, j).mean());
+            }
+        }
+        image
+    }
+
+    /// Visualizes where the sampling effort went: black pixels got the fewest samples, white
+    /// pixels the most.
+    pub fn heatmap(&self) -> Image {
+        let max_count = self.pixels.iter().map(PixelStats::count).max().unwrap_or(0);
+        let mut image = Image::new(self.width, self.height);
+        for j in 0..self.height {
+            for i in 0..self.width {
+                let t = self.get(i, j).count() as f64 / max_count.max(1) as f64;
+                image.set(i, j, heat_color(t));
+            }
+        }
+        image
+    }
+}
+
+// Maps `t` in [0, 1] onto a black - blue - red - yellow - white ramp.
+fn heat_color(t: f64) -> Color {
+    let stops = [
+        Color::new(0., 0., 0.),
+        Color::new(0., 0., 1.),
+        Color::new(1., 0., 0.),
+        Color::new(1., 1., 0.),
+        Color::new(1., 1., 1.),
+    ];
+    let scaled = t.clamp(0., 1.) * (stops.len() - 1) as f64;
+    let index = (scaled as usize).min(stops.len() - 2);
+    let fraction = scaled - index as f64;
+    &stops[index] * (1. - fraction) + &stops[index + 1] * fraction
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pixel_stats_track_mean_and_variance() {
+        let mut stats = PixelStats::default();
+        for value in [1., 2., 3., 4.] {
+            stats.add(&Color::new(value, value, value));
+        }
+        assert_eq!(stats.count(), 4);
+        assert!((stats.mean().x - 2.5).abs() < 1e-12);
+        assert!((stats.variance() - 5. / 3.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn constant_pixels_converge_after_min_samples() {
+        let sampling = AdaptiveSampling::new(4, 64, 0.01);
+        let mut stats = PixelStats::default();
+        for _ in 0..3 {
+            stats.add(&Color::one());
+        }
+        assert!(!sampling.is_converged(&stats));
+        stats.add(&Color::one());
+        assert!(sampling.is_converged(&stats));
+    }
+}
@@ -0,0 +1,265 @@
+//! Escape-time fractals with their distance estimators. They color themselves with orbit traps:
+//! `u` is how close the orbit of a point came to the origin, clamped to [0, 1], and `v` is the
+//! fraction of the iterations after which the orbit escaped. Textures mapping `u` and `v` to
+//! colors turn these into the usual fractal coloring.
+
+use crate::aabb::Aabb;
+use crate::checkpoint::Fingerprint;
+use crate::matrix::Quaternion;
+use crate::sdf::Sdf;
+use crate::vec3::{Point3, Vec3};
+
+// Result of iterating a fractal from one point.
+struct Orbit {
+    distance: f64,
+    trap: f64,       // Smallest distance of the orbit from the origin
+    iterations: u32, // Iterations before the orbit escaped
+}
+
+/// The Mandelbulb of the given power, around the origin with the Y axis as its pole. Power 8
+/// gives the classic shape, which fits within a radius of 1.2.
+pub struct Mandelbulb {
+    power: f64,
+    iterations: u32,
+}
+
+impl Mandelbulb {
+    pub fn new(power: f64, iterations: u32) -> Self {
+        Self {
+            power,
+            iterations: iterations.max(1),
+        }
+    }
+
+    fn orbit(&self, point: &Point3) -> Orbit {
+        let mut z = point.clone();
+        let mut derivative = 1.;
+        let mut radius = z.length();
+        let mut trap = radius;
+        let mut iterations = 0;
+        while iterations < self.iterations && radius <= 2. {
+            if radius == 0. {
+                // Zero stays zero, the point is deep inside.
+                return Orbit {
+                    distance: -1.,
+                    trap: 0.,
+                    iterations: self.iterations,
+                };
+            }
+            // Raises z to the power in spherical coordinates, and adds the starting point.
+            derivative = radius.powf(self.power - 1.) * self.power * derivative + 1.;
+            let theta = (z.y / radius).acos() * self.power;
+            let phi = z.z.atan2(z.x) * self.power;
+            z = &Vec3::new(
+                theta.sin() * phi.cos(),
+                theta.cos(),
+                theta.sin() * phi.sin(),
+            ) * radius.powf(self.power)
+                + point;
+            radius = z.length();
+            trap = trap.min(radius);
+            iterations += 1;
+        }
+        Orbit {
+            distance: 0.5 * radius.ln() * radius / derivative,
+            trap,
+            iterations,
+        }
+    }
+}
+
+impl Sdf for Mandelbulb {
+    fn distance(&self, point: &Point3) -> f64 {
+        self.orbit(point).distance
+    }
+
+    fn bounds(&self) -> Aabb {
+        Aabb::new(&Point3::new(-2., -2., -2.), &Point3::new(2., 2., 2.))
+    }
+
+    fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        fingerprint.add_bytes(b"mandelbulb");
+        fingerprint.add_f64(self.power);
+        fingerprint.add_u64(self.iterations as u64);
+    }
+
+    fn uv(&self, point: &Point3) -> (f64, f64) {
+        let orbit = self.orbit(point);
+        (
+            orbit.trap.clamp(0., 1.),
+            orbit.iterations as f64 / self.iterations as f64,
+        )
+    }
+}
+
+/// The Menger sponge filling the cube from -1 to 1 on every axis, with `iterations` levels of
+/// holes.
+pub struct MengerSponge {
+    iterations: u32,
+}
+
+impl MengerSponge {
+    pub fn new(iterations: u32) -> Self {
+        Self { iterations }
+    }
+
+    fn orbit(&self, point: &Point3) -> Orbit {
+        let q = Vec3::new(point.x.abs(), point.y.abs(), point.z.abs()) - Vec3::one();
+        let outside = Vec3::new(q.x.max(0.), q.y.max(0.), q.z.max(0.)).length();
+        let mut distance = outside + q.x.max(q.y).max(q.z).min(0.);
+        let mut trap = f64::INFINITY;
+        let mut carved_at = 0;
+
+        // Every level carves a cross, three infinite square bars, out of every cell.
+        let mut scale = 1.;
+        for level in 0..self.iterations {
+            let cell = Vec3::new(
+                (point.x * scale).rem_euclid(2.) - 1.,
+                (point.y * scale).rem_euclid(2.) - 1.,
+                (point.z * scale).rem_euclid(2.) - 1.,
+            );
+            scale *= 3.;
+            let r = Vec3::new(
+                (1. - 3. * cell.x.abs()).abs(),
+                (1. - 3. * cell.y.abs()).abs(),
+                (1. - 3. * cell.z.abs()).abs(),
+            );
+            let bar_x = r.y.max(r.z);
+            let bar_y = r.z.max(r.x);
+            let bar_z = r.x.max(r.y);
+            let cross = (bar_x.min(bar_y).min(bar_z) - 1.) / scale;
+            trap = trap.min(cell.length());
+            if cross > distance {
+                distance = cross;
+                carved_at = level + 1;
+            }
+        }
+        Orbit {
+            distance,
+            trap,
+            iterations: carved_at,
+        }
+    }
+}
+
+impl Sdf for MengerSponge {
+    fn distance(&self, point: &Point3) -> f64 {
+        self.orbit(point).distance
+    }
+
+    fn bounds(&self) -> Aabb {
+        Aabb::new(&Point3::new(-1., -1., -1.), &Point3::new(1., 1., 1.))
+    }
+
+    fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        fingerprint.add_bytes(b"menger sponge");
+        fingerprint.add_u64(self.iterations as u64);
+    }
+
+    // v is the level of the holes the point lies on, zero for the outer faces of the cube.
+    fn uv(&self, point: &Point3) -> (f64, f64) {
+        let orbit = self.orbit(point);
+        let levels = self.iterations.max(1) as f64;
+        (
+            (orbit.trap / 3f64.sqrt()).clamp(0., 1.),
+            orbit.iterations as f64 / levels,
+        )
+    }
+}
+
+/// The three-dimensional slice, at w = 0, of the quaternion Julia set z² + c.
+pub struct QuaternionJulia {
+    c: Quaternion,
+    iterations: u32,
+}
+
+impl QuaternionJulia {
+    pub fn new(c: Quaternion, iterations: u32) -> Self {
+        Self {
+            c,
+            iterations: iterations.max(1),
+        }
+    }
+
+    fn orbit(&self, point: &Point3) -> Orbit {
+        let mut z = Quaternion {
+            w: point.x,
+            x: point.y,
+            y: point.z,
+            z: 0.,
+        };
+        // Squared norms of z and of its derivative.
+        let mut norm = z.dot(&z);
+        let mut derivative = 1.;
+        let mut trap = norm;
+        let mut iterations = 0;
+        while iterations < self.iterations && norm <= 16. {
+            derivative *= 4. * norm;
+            z = Quaternion {
+                w: z.w * z.w - z.x * z.x - z.y * z.y - z.z * z.z + self.c.w,
+                x: 2. * z.w * z.x + self.c.x,
+                y: 2. * z.w * z.y + self.c.y,
+                z: 2. * z.w * z.z + self.c.z,
+            };
+            norm = z.dot(&z);
+            trap = trap.min(norm);
+            iterations += 1;
+        }
+        let distance = if norm == 0. || derivative == 0. {
+            -1.
+        } else {
+            0.25 * (norm / derivative).sqrt() * norm.ln()
+        };
+        Orbit {
+            distance,
+            trap: trap.sqrt(),
+            iterations,
+        }
+    }
+}
+
+impl Sdf for QuaternionJulia {
+    fn distance(&self, point: &Point3) -> f64 {
+        self.orbit(point).distance
+    }
+
+    fn bounds(&self) -> Aabb {
+        Aabb::new(&Point3::new(-2., -2., -2.), &Point3::new(2., 2., 2.))
+    }
+
+    fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        fingerprint.add_bytes(b"quaternion julia");
+        for value in [self.c.w, self.c.x, self.c.y, self.c.z] {
+            fingerprint.add_f64(value);
+        }
+        fingerprint.add_u64(self.iterations as u64);
+    }
+
+    fn uv(&self, point: &Point3) -> (f64, f64) {
+        let orbit = self.orbit(point);
+        (
+            orbit.trap.clamp(0., 1.),
+            orbit.iterations as f64 / self.iterations as f64,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fractal_distances_bound_the_surface() {
+        let bulb = Mandelbulb::new(8., 12);
+        assert!(bulb.distance(&Point3::zero()) < 0.);
+        let far = bulb.distance(&Point3::new(0., 3., 0.));
+        assert!(far > 0. && far < 2.);
+
+        let sponge = MengerSponge::new(3);
+        assert!(sponge.distance(&Point3::new(0.95, 0.95, 0.95)) < 0.);
+        // The center of the cube, and of every face, is carved out by the first level.
+        assert!(sponge.distance(&Point3::zero()) > 0.);
+        assert!((sponge.distance(&Point3::new(1., 0., 0.)) - 1. / 3.).abs() < 1e-9);
+        assert!((sponge.distance(&Point3::new(3., 0.9, 0.9)) - 2.).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,185 @@
+//! Signed distance fields: shapes described by a function giving, for any point, the distance
+//! to the closest point of the surface, negative inside. They are rendered by sphere tracing
+//! with `hit::sdf::SdfObject`.
+
+pub mod fractals;
+pub mod ops;
+pub mod shapes;
+
+use crate::aabb::Aabb;
+use crate::checkpoint::Fingerprint;
+use crate::vec3::{Point3, Vec3};
+use ops::{
+    Intersection, Repetition, Rounding, SmoothIntersection, SmoothSubtraction, SmoothUnion,
+    Subtraction, Translation, Twist, Union,
+};
+
+// Step of the finite differences approximating the gradient.
+const GRADIENT_STEP: f64 = 1e-5;
+
+pub trait Sdf {
+    /// Signed distance from the point to the surface. It may underestimate the distance, but
+    /// never overestimate it, or sphere tracing steps through the surface.
+    fn distance(&self, point: &Point3) -> f64;
+
+    /// A box containing the whole surface, infinite for unbounded shapes.
+    fn bounds(&self) -> Aabb;
+
+    /// Gradient of the distance, the outward normal on the surface. Estimated with central
+    /// differences unless a shape knows it analytically.
+    fn gradient(&self, point: &Point3) -> Vec3 {
+        let h = GRADIENT_STEP;
+        let dx = Vec3::new(h, 0., 0.);
+        let dy = Vec3::new(0., h, 0.);
+        let dz = Vec3::new(0., 0., h);
+        Vec3::new(
+            self.distance(&(point + &dx)) - self.distance(&(point - &dx)),
+            self.distance(&(point + &dy)) - self.distance(&(point - &dy)),
+            self.distance(&(point + &dz)) - self.distance(&(point - &dz)),
+        ) / (2. * h)
+    }
+
+    /// Surface coordinates of a point on the surface, which fractals use for their orbit traps.
+    fn uv(&self, _point: &Point3) -> (f64, f64) {
+        (0., 0.)
+    }
+
+    /// Adds what changes the shape to the fingerprint of the scene, its bounds by default.
+    /// Operators add their parameters and their operands.
+    fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        fingerprint.add_aabb(&self.bounds());
+    }
+
+    fn union<O: Sdf>(self, other: O) -> Union<Self, O>
+    where
+        Self: Sized,
+    {
+        Union(self, other)
+    }
+
+    fn intersection<O: Sdf>(self, other: O) -> Intersection<Self, O>
+    where
+        Self: Sized,
+    {
+        Intersection(self, other)
+    }
+
+    /// Everything in this shape that is not in `other`.
+    fn subtract<O: Sdf>(self, other: O) -> Subtraction<Self, O>
+    where
+        Self: Sized,
+    {
+        Subtraction(self, other)
+    }
+
+    /// Union blending the shapes together where they are closer than `k`.
+    fn smooth_union<O: Sdf>(self, other: O, k: f64) -> SmoothUnion<Self, O>
+    where
+        Self: Sized,
+    {
+        SmoothUnion(self, other, k)
+    }
+
+    fn smooth_intersection<O: Sdf>(self, other: O, k: f64) -> SmoothIntersection<Self, O>
+    where
+        Self: Sized,
+    {
+        SmoothIntersection(self, other, k)
+    }
+
+    fn smooth_subtract<O: Sdf>(self, other: O, k: f64) -> SmoothSubtraction<Self, O>
+    where
+        Self: Sized,
+    {
+        SmoothSubtraction(self, other, k)
+    }
+
+    fn translate(self, offset: Vec3) -> Translation<Self>
+    where
+        Self: Sized,
+    {
+        Translation(self, offset)
+    }
+
+    /// Rounds off the edges, growing the shape by `radius`.
+    fn round(self, radius: f64) -> Rounding<Self>
+    where
+        Self: Sized,
+    {
+        Rounding(self, radius)
+    }
+
+    /// Repeats the shape, which should fit into a cell around the origin, on a grid with the
+    /// given cell size. A zero size along an axis doesn't repeat along it.
+    fn repeat(self, period: Vec3) -> Repetition<Self>
+    where
+        Self: Sized,
+    {
+        Repetition::new(self, period, None)
+    }
+
+    /// Repeats the shape like `repeat`, but only `count` times in both directions from the
+    /// origin along every axis.
+    fn repeat_limited(self, period: Vec3, count: [u32; 3]) -> Repetition<Self>
+    where
+        Self: Sized,
+    {
+        Repetition::new(self, period, Some(count))
+    }
+
+    /// Twists the shape around the Y axis by `rate` degrees per unit of height. Twisting
+    /// distorts the distance, so the traced object needs a smaller step scale.
+    fn twist(self, rate: f64) -> Twist<Self>
+    where
+        Self: Sized,
+    {
+        Twist(self, rate)
+    }
+}
+
+impl Sdf for Box<dyn Sdf> {
+    fn distance(&self, point: &Point3) -> f64 {
+        self.as_ref().distance(point)
+    }
+
+    fn bounds(&self) -> Aabb {
+        self.as_ref().bounds()
+    }
+
+    fn gradient(&self, point: &Point3) -> Vec3 {
+        self.as_ref().gradient(point)
+    }
+
+    fn uv(&self, point: &Point3) -> (f64, f64) {
+        self.as_ref().uv(point)
+    }
+
+    fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        self.as_ref().fingerprint(fingerprint)
+    }
+}
+
+/// A distance field given by a user-supplied function. The function can't be looked into, so
+/// only its bounds go into the scene fingerprint: resumable renders changing it need a new
+/// `CheckpointSettings::with_scene_version`.
+pub struct SdfFn<F: Fn(&Point3) -> f64> {
+    function: F,
+    bounds: Aabb,
+}
+
+impl<F: Fn(&Point3) -> f64> SdfFn<F> {
+    /// The surface must lie within `bounds`, use `Aabb::infinite()` if it is unbounded.
+    pub fn new(bounds: Aabb, function: F) -> Self {
+        Self { function, bounds }
+    }
+}
+
+impl<F: Fn(&Point3) -> f64> Sdf for SdfFn<F> {
+    fn distance(&self, point: &Point3) -> f64 {
+        (self.function)(point)
+    }
+
+    fn bounds(&self) -> Aabb {
+        self.bounds.clone()
+    }
+}
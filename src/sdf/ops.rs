@@ -0,0 +1,442 @@
+//! Operators building new distance fields out of other ones, usually created through the
+//! combinator methods of `Sdf`.
+
+use crate::aabb::Aabb;
+use crate::checkpoint::Fingerprint;
+use crate::sdf::Sdf;
+use crate::vec3::{Point3, Vec3};
+
+// The operand whose surface is closest to the point, which gives the surface coordinates of a
+// union.
+fn closer<'a>(a: &'a dyn Sdf, b: &'a dyn Sdf, point: &Point3) -> &'a dyn Sdf {
+    if a.distance(point) <= b.distance(point) {
+        a
+    } else {
+        b
+    }
+}
+
+fn farther<'a>(a: &'a dyn Sdf, b: &'a dyn Sdf, point: &Point3) -> &'a dyn Sdf {
+    if a.distance(point) >= b.distance(point) {
+        a
+    } else {
+        b
+    }
+}
+
+pub struct Union<A: Sdf, B: Sdf>(pub A, pub B);
+
+impl<A: Sdf, B: Sdf> Sdf for Union<A, B> {
+    fn distance(&self, point: &Point3) -> f64 {
+        self.0.distance(point).min(self.1.distance(point))
+    }
+
+    fn bounds(&self) -> Aabb {
+        self.0.bounds().union(&self.1.bounds())
+    }
+
+    fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        fingerprint.add_bytes(b"union");
+        self.0.fingerprint(fingerprint);
+        self.1.fingerprint(fingerprint);
+    }
+
+    fn uv(&self, point: &Point3) -> (f64, f64) {
+        closer(&self.0, &self.1, point).uv(point)
+    }
+}
+
+pub struct Intersection<A: Sdf, B: Sdf>(pub A, pub B);
+
+impl<A: Sdf, B: Sdf> Sdf for Intersection<A, B> {
+    fn distance(&self, point: &Point3) -> f64 {
+        self.0.distance(point).max(self.1.distance(point))
+    }
+
+    fn bounds(&self) -> Aabb {
+        self.0.bounds().intersection(&self.1.bounds())
+    }
+
+    fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        fingerprint.add_bytes(b"intersection");
+        self.0.fingerprint(fingerprint);
+        self.1.fingerprint(fingerprint);
+    }
+
+    fn uv(&self, point: &Point3) -> (f64, f64) {
+        farther(&self.0, &self.1, point).uv(point)
+    }
+}
+
+pub struct Subtraction<A: Sdf, B: Sdf>(pub A, pub B);
+
+impl<A: Sdf, B: Sdf> Sdf for Subtraction<A, B> {
+    fn distance(&self, point: &Point3) -> f64 {
+        self.0.distance(point).max(-self.1.distance(point))
+    }
+
+    fn bounds(&self) -> Aabb {
+        self.0.bounds()
+    }
+
+    fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        fingerprint.add_bytes(b"subtraction");
+        self.0.fingerprint(fingerprint);
+        self.1.fingerprint(fingerprint);
+    }
+
+    fn uv(&self, point: &Point3) -> (f64, f64) {
+        self.0.uv(point)
+    }
+}
+
+// Polynomial smooth minimum, which is at most k / 4 below the actual minimum.
+fn smooth_min(a: f64, b: f64, k: f64) -> f64 {
+    if k <= 0. {
+        return a.min(b);
+    }
+    let h = (0.5 + 0.5 * (b - a) / k).clamp(0., 1.);
+    b * (1. - h) + a * h - k * h * (1. - h)
+}
+
+pub struct SmoothUnion<A: Sdf, B: Sdf>(pub A, pub B, pub f64);
+
+impl<A: Sdf, B: Sdf> Sdf for SmoothUnion<A, B> {
+    fn distance(&self, point: &Point3) -> f64 {
+        smooth_min(self.0.distance(point), self.1.distance(point), self.2)
+    }
+
+    fn bounds(&self) -> Aabb {
+        self.0
+            .bounds()
+            .union(&self.1.bounds())
+            .expanded(self.2 / 4.)
+    }
+
+    fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        fingerprint.add_bytes(b"smooth union");
+        self.0.fingerprint(fingerprint);
+        self.1.fingerprint(fingerprint);
+        fingerprint.add_f64(self.2);
+    }
+
+    fn uv(&self, point: &Point3) -> (f64, f64) {
+        closer(&self.0, &self.1, point).uv(point)
+    }
+}
+
+pub struct SmoothIntersection<A: Sdf, B: Sdf>(pub A, pub B, pub f64);
+
+impl<A: Sdf, B: Sdf> Sdf for SmoothIntersection<A, B> {
+    fn distance(&self, point: &Point3) -> f64 {
+        -smooth_min(-self.0.distance(point), -self.1.distance(point), self.2)
+    }
+
+    fn bounds(&self) -> Aabb {
+        self.0
+            .bounds()
+            .intersection(&self.1.bounds())
+            .expanded(self.2 / 4.)
+    }
+
+    fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        fingerprint.add_bytes(b"smooth intersection");
+        self.0.fingerprint(fingerprint);
+        self.1.fingerprint(fingerprint);
+        fingerprint.add_f64(self.2);
+    }
+
+    fn uv(&self, point: &Point3) -> (f64, f64) {
+        farther(&self.0, &self.1, point).uv(point)
+    }
+}
+
+pub struct SmoothSubtraction<A: Sdf, B: Sdf>(pub A, pub B, pub f64);
+
+impl<A: Sdf, B: Sdf> Sdf for SmoothSubtraction<A, B> {
+    fn distance(&self, point: &Point3) -> f64 {
+        -smooth_min(-self.0.distance(point), self.1.distance(point), self.2)
+    }
+
+    fn bounds(&self) -> Aabb {
+        self.0.bounds().expanded(self.2 / 4.)
+    }
+
+    fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        fingerprint.add_bytes(b"smooth subtraction");
+        self.0.fingerprint(fingerprint);
+        self.1.fingerprint(fingerprint);
+        fingerprint.add_f64(self.2);
+    }
+
+    fn uv(&self, point: &Point3) -> (f64, f64) {
+        self.0.uv(point)
+    }
+}
+
+pub struct Translation<S: Sdf>(pub S, pub Vec3);
+
+impl<S: Sdf> Sdf for Translation<S> {
+    fn distance(&self, point: &Point3) -> f64 {
+        self.0.distance(&(point - &self.1))
+    }
+
+    fn bounds(&self) -> Aabb {
+        let bounds = self.0.bounds();
+        Aabb {
+            min: &bounds.min + &self.1,
+            max: &bounds.max + &self.1,
+        }
+    }
+
+    fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        fingerprint.add_bytes(b"translation");
+        self.0.fingerprint(fingerprint);
+        fingerprint.add_vec3(&self.1);
+    }
+
+    fn gradient(&self, point: &Point3) -> Vec3 {
+        self.0.gradient(&(point - &self.1))
+    }
+
+    fn uv(&self, point: &Point3) -> (f64, f64) {
+        self.0.uv(&(point - &self.1))
+    }
+}
+
+pub struct Rounding<S: Sdf>(pub S, pub f64);
+
+impl<S: Sdf> Sdf for Rounding<S> {
+    fn distance(&self, point: &Point3) -> f64 {
+        self.0.distance(point) - self.1
+    }
+
+    fn bounds(&self) -> Aabb {
+        self.0.bounds().expanded(self.1)
+    }
+
+    fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        fingerprint.add_bytes(b"rounding");
+        self.0.fingerprint(fingerprint);
+        fingerprint.add_f64(self.1);
+    }
+
+    fn gradient(&self, point: &Point3) -> Vec3 {
+        self.0.gradient(point)
+    }
+
+    fn uv(&self, point: &Point3) -> (f64, f64) {
+        self.0.uv(point)
+    }
+}
+
+pub struct Repetition<S: Sdf> {
+    shape: S,
+    period: Vec3,
+    limit: Option<[u32; 3]>,
+}
+
+impl<S: Sdf> Repetition<S> {
+    pub fn new(shape: S, period: Vec3, limit: Option<[u32; 3]>) -> Self {
+        Self {
+            shape,
+            period,
+            limit,
+        }
+    }
+
+    // The point moved into the cell around the origin.
+    fn fold(&self, point: &Point3) -> Point3 {
+        let mut folded = point.clone();
+        for axis in 0..3 {
+            let period = self.period[axis];
+            if period <= 0. {
+                continue;
+            }
+            let mut cell = (point[axis] / period).round();
+            if let Some(limit) = self.limit {
+                let limit = limit[axis] as f64;
+                cell = cell.clamp(-limit, limit);
+            }
+            folded[axis] -= period * cell;
+        }
+        folded
+    }
+}
+
+impl<S: Sdf> Sdf for Repetition<S> {
+    fn distance(&self, point: &Point3) -> f64 {
+        self.shape.distance(&self.fold(point))
+    }
+
+    fn bounds(&self) -> Aabb {
+        let mut bounds = self.shape.bounds();
+        for axis in 0..3 {
+            let period = self.period[axis];
+            if period <= 0. {
+                continue;
+            }
+            match self.limit {
+                Some(limit) => {
+                    let reach = period * limit[axis] as f64;
+                    bounds.min[axis] -= reach;
+                    bounds.max[axis] += reach;
+                }
+                None => {
+                    bounds.min[axis] = f64::NEG_INFINITY;
+                    bounds.max[axis] = f64::INFINITY;
+                }
+            }
+        }
+        bounds
+    }
+
+    fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        fingerprint.add_bytes(b"repetition");
+        self.shape.fingerprint(fingerprint);
+        fingerprint.add_vec3(&self.period);
+        match self.limit {
+            Some(limit) => {
+                for count in limit {
+                    fingerprint.add_u64(count as u64);
+                }
+            }
+            None => fingerprint.add_bytes(b"unlimited"),
+        }
+    }
+
+    fn gradient(&self, point: &Point3) -> Vec3 {
+        self.shape.gradient(&self.fold(point))
+    }
+
+    fn uv(&self, point: &Point3) -> (f64, f64) {
+        self.shape.uv(&self.fold(point))
+    }
+}
+
+pub struct Twist<S: Sdf>(pub S, pub f64);
+
+impl<S: Sdf> Twist<S> {
+    fn untwist(&self, point: &Point3) -> Point3 {
+        let angle = -(self.1 * point.y).to_radians();
+        let (sin, cos) = angle.sin_cos();
+        Point3::new(
+            cos * point.x - sin * point.z,
+            point.y,
+            sin * point.x + cos * point.z,
+        )
+    }
+}
+
+impl<S: Sdf> Sdf for Twist<S> {
+    fn distance(&self, point: &Point3) -> f64 {
+        self.0.distance(&self.untwist(point))
+    }
+
+    // Twisting around the Y axis keeps the shape within the cylinder around the axis that
+    // contains its bounds.
+    fn bounds(&self) -> Aabb {
+        let bounds = self.0.bounds();
+        let x = bounds.min.x.abs().max(bounds.max.x.abs());
+        let z = bounds.min.z.abs().max(bounds.max.z.abs());
+        let radius = (x * x + z * z).sqrt();
+        Aabb::new(
+            &Point3::new(-radius, bounds.min.y, -radius),
+            &Point3::new(radius, bounds.max.y, radius),
+        )
+    }
+
+    fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        fingerprint.add_bytes(b"twist");
+        self.0.fingerprint(fingerprint);
+        fingerprint.add_f64(self.1);
+    }
+
+    fn uv(&self, point: &Point3) -> (f64, f64) {
+        self.0.uv(&self.untwist(point))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdf::shapes::{Cuboid, Sphere};
+
+    fn spheres() -> (Sphere, Sphere) {
+        (
+            Sphere::new(Point3::new(-1., 0., 0.), 1.5),
+            Sphere::new(Point3::new(1., 0., 0.), 1.5),
+        )
+    }
+
+    #[test]
+    fn smooth_union_tends_to_the_union() {
+        let points = [
+            Point3::zero(),
+            Point3::new(0., 2., 0.),
+            Point3::new(3., 1., -1.),
+        ];
+        for point in &points {
+            let (a, b) = spheres();
+            let union = a.distance(point).min(b.distance(point));
+            let sharp = SmoothUnion(a, b, 1e-9).distance(point);
+            assert!((sharp - union).abs() < 1e-9);
+
+            // Blending only ever adds material, by at most k / 4.
+            let (a, b) = spheres();
+            let smooth = SmoothUnion(a, b, 1.).distance(point);
+            assert!(smooth <= union && smooth >= union - 0.25);
+        }
+        // Where both surfaces are equally far, the blend is the deepest.
+        let (a, b) = spheres();
+        let point = Point3::new(0., 2., 0.);
+        let union = a.distance(&point);
+        assert!((SmoothUnion(a, b, 1.).distance(&point) - (union - 0.25)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn subtraction_carves_the_second_shape_out_of_the_first() {
+        let (a, b) = spheres();
+        let subtraction = Subtraction(a, b);
+        // Inside both, the surface of the second shape is the closest.
+        assert!((subtraction.distance(&Point3::new(0., 0., 0.)) - 0.5).abs() < 1e-9);
+        // Only inside the first, the point stays inside.
+        assert!((subtraction.distance(&Point3::new(-2., 0., 0.)) + 0.5).abs() < 1e-9);
+        // Outside the first, its surface is the closest.
+        assert!((subtraction.distance(&Point3::new(-4., 0., 0.)) - 1.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn repetition_is_periodic_up_to_its_limit() {
+        let sphere = || Sphere::new(Point3::zero(), 0.5);
+        let period = Vec3::new(2., 0., 3.);
+        let point = Point3::new(0.3, 0.7, -0.4);
+        let repeated = sphere().repeat(period.clone());
+        for cells in [-5., 1., 40.] {
+            let moved = &point + &period * cells;
+            assert!((repeated.distance(&moved) - sphere().distance(&point)).abs() < 1e-9);
+        }
+
+        // Past the last copy, the distance keeps growing instead of repeating.
+        let limited = sphere().repeat_limited(period.clone(), [1, 0, 1]);
+        let next = Point3::new(4., 0., 0.);
+        assert!((limited.distance(&next) - 1.5).abs() < 1e-9);
+        assert!((limited.distance(&Point3::new(2., 0., 0.)) + 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn twist_keeps_distances_on_its_axis() {
+        let cuboid = || Cuboid::new(Point3::zero(), Vec3::new(1., 2., 0.5));
+        let twisted = cuboid().twist(45.);
+        for y in [-3., -1., 0., 0.5, 2.5] {
+            let point = Point3::new(0., y, 0.);
+            assert!((twisted.distance(&point) - cuboid().distance(&point)).abs() < 1e-9);
+        }
+
+        // Off the axis, points are turned back by the rate times their height.
+        let point = Point3::new(0., 1., 0.9);
+        let (sin, cos) = 45f64.to_radians().sin_cos();
+        let turned = Point3::new(0.9 * sin, 1., 0.9 * cos);
+        assert!((twisted.distance(&point) - cuboid().distance(&turned)).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,153 @@
+use crate::aabb::Aabb;
+use crate::sdf::Sdf;
+use crate::vec3::{Point3, Vec3};
+
+pub struct Sphere {
+    pub center: Point3,
+    pub radius: f64,
+}
+
+impl Sphere {
+    pub fn new(center: Point3, radius: f64) -> Self {
+        Self { center, radius }
+    }
+}
+
+impl Sdf for Sphere {
+    fn distance(&self, point: &Point3) -> f64 {
+        (point - &self.center).length() - self.radius
+    }
+
+    fn bounds(&self) -> Aabb {
+        let extent = Vec3::new(self.radius, self.radius, self.radius);
+        Aabb::new(&(&self.center - &extent), &(&self.center + &extent))
+    }
+
+    fn gradient(&self, point: &Point3) -> Vec3 {
+        (point - &self.center).unit()
+    }
+}
+
+/// An axis-aligned box around `center`, extending `half_size` to every side.
+pub struct Cuboid {
+    pub center: Point3,
+    pub half_size: Vec3,
+}
+
+impl Cuboid {
+    pub fn new(center: Point3, half_size: Vec3) -> Self {
+        Self { center, half_size }
+    }
+}
+
+impl Sdf for Cuboid {
+    fn distance(&self, point: &Point3) -> f64 {
+        let p = point - &self.center;
+        let q = Vec3::new(
+            p.x.abs() - self.half_size.x,
+            p.y.abs() - self.half_size.y,
+            p.z.abs() - self.half_size.z,
+        );
+        let outside = Vec3::new(q.x.max(0.), q.y.max(0.), q.z.max(0.)).length();
+        let inside = q.x.max(q.y).max(q.z).min(0.);
+        outside + inside
+    }
+
+    fn bounds(&self) -> Aabb {
+        Aabb::new(
+            &(&self.center - &self.half_size),
+            &(&self.center + &self.half_size),
+        )
+    }
+}
+
+/// A torus around `center` in the XZ plane.
+pub struct Torus {
+    pub center: Point3,
+    pub major_radius: f64,
+    pub minor_radius: f64,
+}
+
+impl Torus {
+    pub fn new(center: Point3, major_radius: f64, minor_radius: f64) -> Self {
+        Self {
+            center,
+            major_radius,
+            minor_radius,
+        }
+    }
+}
+
+impl Sdf for Torus {
+    fn distance(&self, point: &Point3) -> f64 {
+        let p = point - &self.center;
+        let radial = (p.x * p.x + p.z * p.z).sqrt() - self.major_radius;
+        (radial * radial + p.y * p.y).sqrt() - self.minor_radius
+    }
+
+    fn bounds(&self) -> Aabb {
+        let outer = self.major_radius + self.minor_radius;
+        let extent = Vec3::new(outer, self.minor_radius, outer);
+        Aabb::new(&(&self.center - &extent), &(&self.center + &extent))
+    }
+}
+
+/// A line segment from `a` to `b` thickened by `radius`.
+pub struct Capsule {
+    pub a: Point3,
+    pub b: Point3,
+    pub radius: f64,
+}
+
+impl Capsule {
+    pub fn new(a: Point3, b: Point3, radius: f64) -> Self {
+        Self { a, b, radius }
+    }
+}
+
+impl Sdf for Capsule {
+    fn distance(&self, point: &Point3) -> f64 {
+        let pa = point - &self.a;
+        let ba = &self.b - &self.a;
+        let h = (pa.dot(&ba) / ba.length_squared()).clamp(0., 1.);
+        (pa - ba * h).length() - self.radius
+    }
+
+    fn bounds(&self) -> Aabb {
+        let extent = Vec3::new(self.radius, self.radius, self.radius);
+        Aabb::new(&(&self.a - &extent), &(&self.a + &extent))
+            .union(&Aabb::new(&(&self.b - &extent), &(&self.b + &extent)))
+    }
+}
+
+/// A capped cylinder around `center` along the Y axis.
+pub struct Cylinder {
+    pub center: Point3,
+    pub radius: f64,
+    pub half_height: f64,
+}
+
+impl Cylinder {
+    pub fn new(center: Point3, radius: f64, half_height: f64) -> Self {
+        Self {
+            center,
+            radius,
+            half_height,
+        }
+    }
+}
+
+impl Sdf for Cylinder {
+    fn distance(&self, point: &Point3) -> f64 {
+        let p = point - &self.center;
+        let radial = (p.x * p.x + p.z * p.z).sqrt() - self.radius;
+        let vertical = p.y.abs() - self.half_height;
+        let outside = (radial.max(0.).powi(2) + vertical.max(0.).powi(2)).sqrt();
+        outside + radial.max(vertical).min(0.)
+    }
+
+    fn bounds(&self) -> Aabb {
+        let extent = Vec3::new(self.radius, self.half_height, self.radius);
+        Aabb::new(&(&self.center - &extent), &(&self.center + &extent))
+    }
+}
@@ -0,0 +1,175 @@
+//! Stereo pairs: the scene seen by two eyes a little apart, combined in one image for 3D
+//! displays and VR headsets. Panoramas use omnidirectional stereo (ODS), where the eyes turn
+//! with every viewing direction around the center of the head.
+
+use crate::image::Image;
+use crate::projection::Projection;
+use crate::vec3::Vec3;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Eye {
+    Left,
+    Right,
+}
+
+/// How the two eye images are placed in the combined image.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StereoLayout {
+    SideBySide, // Left eye on the left, right eye on the right
+    OverUnder,  // Left eye on top, right eye below
+}
+
+#[derive(Clone, Debug)]
+pub struct Stereo {
+    pub interocular: f64, // Distance between the eyes
+    pub convergence: f64, // Distance at which the eyes converge, where objects have no parallax
+    pub layout: StereoLayout,
+}
+
+impl Stereo {
+    /// Eyes `interocular` apart looking in parallel, converging at infinity.
+    pub fn new(interocular: f64) -> Self {
+        Self {
+            interocular,
+            convergence: f64::INFINITY,
+            layout: StereoLayout::SideBySide,
+        }
+    }
+
+    pub fn with_convergence(mut self, convergence: f64) -> Self {
+        self.convergence = convergence;
+        self
+    }
+
+    pub fn with_layout(mut self, layout: StereoLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Offset of the eye along the camera right direction.
+    pub fn eye_offset(&self, eye: Eye) -> f64 {
+        match eye {
+            Eye::Left => -self.interocular / 2.,
+            Eye::Right => self.interocular / 2.,
+        }
+    }
+
+    /// Places the images of both eyes, which must have the same size, next to each other.
+    pub fn combine(&self, left: &Image, right: &Image) -> Image {
+        assert!(
+            left.width() == right.width() && left.height() == right.height(),
+            "both eye images must have the same size"
+        );
+        let (width, height) = (left.width(), left.height());
+        let (mut combined, right_corner) = match self.layout {
+            StereoLayout::SideBySide => (Image::new(2 * width, height), (width, 0)),
+            StereoLayout::OverUnder => (Image::new(width, 2 * height), (0, height)),
+        };
+        for j in 0..height {
+            for i in 0..width {
+                combined.set(i, j, left.get(i, j).clone());
+                combined.set(
+                    i + right_corner.0,
+                    j + right_corner.1,
+                    right.get(i, j).clone(),
+                );
+            }
+        }
+        combined
+    }
+}
+
+/// Moves a ray in the camera frame, from `projection` for a single eye, to an eye `offset`
+/// to the right, turning it to meet the original ray at the `convergence` distance.
+///
+/// Panoramas move the eye sideways of every direction, on a circle around the head. The circle
+/// shrinks towards the poles, so that looking straight up or down both eyes see the same.
+pub(crate) fn eye_ray(
+    projection: &Projection,
+    origin: Vec3,
+    direction: Vec3,
+    offset: f64,
+    convergence: f64,
+) -> (Vec3, Vec3) {
+    if offset == 0. {
+        return (origin, direction);
+    }
+    let sideways = match projection {
+        Projection::Equirectangular | Projection::CubemapFace(_) => {
+            Vec3::new(-direction.z, 0., direction.x) / direction.length()
+        }
+        _ => Vec3::new(1., 0., 0.),
+    };
+    let shift = sideways * offset;
+    let direction = match projection {
+        Projection::Orthographic { .. } => direction,
+        _ if convergence.is_finite() => direction.unit() * convergence - &shift,
+        _ => direction,
+    };
+    (origin + shift, direction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    #[test]
+    fn combined_images_place_the_left_eye_first() {
+        let mut left = Image::new(2, 1);
+        left.set(1, 0, Color::one());
+        let right = Image::new(2, 1);
+
+        let side_by_side = Stereo::new(0.06).combine(&left, &right);
+        assert_eq!((side_by_side.width(), side_by_side.height()), (4, 1));
+        assert_eq!(side_by_side.get(1, 0), &Color::one());
+        assert_eq!(side_by_side.get(3, 0), &Color::zero());
+
+        let over_under = Stereo::new(0.06)
+            .with_layout(StereoLayout::OverUnder)
+            .combine(&left, &right);
+        assert_eq!((over_under.width(), over_under.height()), (2, 2));
+        assert_eq!(over_under.get(1, 0), &Color::one());
+        assert_eq!(over_under.get(1, 1), &Color::zero());
+    }
+
+    #[test]
+    fn panorama_eyes_turn_with_the_view_direction() {
+        let projection = Projection::Equirectangular;
+        // Looking to the right, the right eye is behind the head.
+        let (origin, direction) = eye_ray(
+            &projection,
+            Vec3::zero(),
+            Vec3::new(1., 0., 0.),
+            0.03,
+            f64::INFINITY,
+        );
+        assert_eq!(origin, Vec3::new(0., 0., 0.03));
+        assert_eq!(direction, Vec3::new(1., 0., 0.));
+
+        // Looking straight up, both eyes are at the center.
+        let (origin, _) = eye_ray(
+            &projection,
+            Vec3::zero(),
+            Vec3::new(0., 1., 0.),
+            0.03,
+            f64::INFINITY,
+        );
+        assert_eq!(origin, Vec3::zero());
+    }
+
+    #[test]
+    fn eyes_converge_on_the_original_ray() {
+        let projection = Projection::Fisheye {
+            fov: 180.,
+            mapping: crate::projection::FisheyeMapping::Equidistant,
+        };
+        let direction = Vec3::new(0.6, 0., -0.8);
+        for offset in [-0.03, 0.03] {
+            let (origin, eye_direction) =
+                eye_ray(&projection, Vec3::zero(), direction.clone(), offset, 2.);
+            let meeting = origin + eye_direction;
+            assert!((meeting - &direction * 2.).length() < 1e-12);
+        }
+    }
+}
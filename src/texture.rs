@@ -0,0 +1,147 @@
+//! Colors varying over a surface, looked up from the surface coordinates and the position of
+//! the hit point.
+
+use crate::checkpoint::Fingerprint;
+use crate::color::Color;
+use crate::vec3::Point3;
+
+#[derive(Clone, Debug)]
+pub enum Texture {
+    Solid(Color),
+    Checker(Checker),
+    Ramp(ColorRamp),
+}
+
+impl Texture {
+    pub fn value(&self, u: f64, v: f64, point: &Point3) -> Color {
+        match self {
+            Self::Solid(color) => color.clone(),
+            Self::Checker(checker) => checker.value(u, v, point),
+            Self::Ramp(ramp) => ramp.value(u, v),
+        }
+    }
+
+    pub fn fingerprint(&self, fingerprint: &mut Fingerprint) {
+        match self {
+            Self::Solid(color) => {
+                fingerprint.add_bytes(b"solid");
+                fingerprint.add_vec3(color);
+            }
+            Self::Checker(checker) => {
+                fingerprint.add_bytes(b"checker");
+                checker.even.fingerprint(fingerprint);
+                checker.odd.fingerprint(fingerprint);
+                fingerprint.add_f64(checker.scale);
+            }
+            Self::Ramp(ramp) => {
+                fingerprint.add_bytes(b"ramp");
+                fingerprint.add_u64(ramp.colors.len() as u64);
+                for color in &ramp.colors {
+                    fingerprint.add_vec3(color);
+                }
+                fingerprint.add_bytes(&[ramp.along_v as u8]);
+            }
+        }
+    }
+}
+
+impl From<Color> for Texture {
+    fn from(value: Color) -> Self {
+        Self::Solid(value)
+    }
+}
+
+impl From<Checker> for Texture {
+    fn from(value: Checker) -> Self {
+        Self::Checker(value)
+    }
+}
+
+impl From<ColorRamp> for Texture {
+    fn from(value: ColorRamp) -> Self {
+        Self::Ramp(value)
+    }
+}
+
+/// A three-dimensional checker pattern alternating between two textures in cubes of `scale`.
+#[derive(Clone, Debug)]
+pub struct Checker {
+    even: Box<Texture>,
+    odd: Box<Texture>,
+    scale: f64,
+}
+
+impl Checker {
+    pub fn new<E: Into<Texture>, O: Into<Texture>>(even: E, odd: O, scale: f64) -> Self {
+        Self {
+            even: Box::new(even.into()),
+            odd: Box::new(odd.into()),
+            scale,
+        }
+    }
+
+    fn value(&self, u: f64, v: f64, point: &Point3) -> Color {
+        let cell = (point.x / self.scale).floor()
+            + (point.y / self.scale).floor()
+            + (point.z / self.scale).floor();
+        if cell.rem_euclid(2.) == 0. {
+            self.even.value(u, v, point)
+        } else {
+            self.odd.value(u, v, point)
+        }
+    }
+}
+
+/// Colors evenly spaced over [0, 1] and blended linearly, picked by `u`, or by `v` for a ramp
+/// created with `along_v`. This is how orbit traps of fractals get their colors.
+#[derive(Clone, Debug)]
+pub struct ColorRamp {
+    colors: Vec<Color>,
+    along_v: bool,
+}
+
+impl ColorRamp {
+    pub fn new(colors: Vec<Color>) -> Self {
+        assert!(!colors.is_empty(), "a color ramp needs at least one color");
+        Self {
+            colors,
+            along_v: false,
+        }
+    }
+
+    pub fn along_v(mut self) -> Self {
+        self.along_v = true;
+        self
+    }
+
+    fn value(&self, u: f64, v: f64) -> Color {
+        let t = if self.along_v { v } else { u };
+        let position = t.clamp(0., 1.) * (self.colors.len() - 1) as f64;
+        let index = (position.floor() as usize).min(self.colors.len() - 1);
+        let next = (index + 1).min(self.colors.len() - 1);
+        let blend = position - index as f64;
+        &self.colors[index] * (1. - blend) + &self.colors[next] * blend
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ramp_blends_between_neighbouring_colors() {
+        let ramp = ColorRamp::new(vec![
+            Color::zero(),
+            Color::new(1., 0., 0.),
+            Color::new(1., 1., 1.),
+        ]);
+        let origin = Point3::zero();
+        assert_eq!(ramp.value(-1., 0.), Color::zero());
+        assert_eq!(ramp.value(0.25, 0.), Color::new(0.5, 0., 0.));
+        assert_eq!(ramp.value(0.75, 0.), Color::new(1., 0.5, 0.5));
+        assert_eq!(
+            Texture::from(ramp).value(1., 0., &origin),
+            Color::new(1., 1., 1.)
+        );
+    }
+}
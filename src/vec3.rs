@@ -1,4 +1,5 @@
 use crate::random::{random, random_range};
+use serde::{Deserialize, Serialize};
 use std::fmt::Formatter;
 use std::{
     fmt::Display,
@@ -7,7 +8,7 @@ use std::{
     },
 };
 
-#[derive(Default, Clone, Debug, PartialOrd, PartialEq)]
+#[derive(Default, Clone, Debug, PartialOrd, PartialEq, Serialize, Deserialize)]
 pub struct Vec3 {
     pub x: f64,
     pub y: f64,
@@ -59,9 +60,9 @@ impl Vec3 {
     pub fn random_unit_on_hemisphere(normal: &Vec3) -> Self {
         let vec = Self::random_unit();
         if vec.dot(normal) > 0. {
-            return vec;
+            vec
         } else {
-            return -vec;
+            -vec
         }
     }
 
@@ -105,6 +106,18 @@ impl Vec3 {
         self / self.length()
     }
 
+    /// Two unit vectors that together with this unit vector form an orthonormal basis.
+    pub fn orthonormal_basis(&self) -> (Self, Self) {
+        let helper = if self.x.abs() > 0.9 {
+            Self::new(0., 1., 0.)
+        } else {
+            Self::new(1., 0., 0.)
+        };
+        let tangent = self.cross(&helper).unit();
+        let bitangent = self.cross(&tangent);
+        (tangent, bitangent)
+    }
+
     pub fn is_near_zero(&self) -> bool {
         let s = 1e-8;
         (self.x.abs() + self.y.abs() + self.z.abs()) < s
@@ -299,6 +312,14 @@ impl Div<f64> for &Vec3 {
     }
 }
 
+impl Div<f64> for Vec3 {
+    type Output = Vec3;
+
+    fn div(self, rhs: f64) -> Self::Output {
+        &self / rhs
+    }
+}
+
 impl DivAssign<f64> for Vec3 {
     fn div_assign(&mut self, rhs: f64) {
         self.x /= rhs;
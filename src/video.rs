@@ -0,0 +1,559 @@
+//! Animations written straight into a single file or stream instead of an image per frame:
+//! uncompressed YUV4MPEG2 to pipe into a video encoder, and animated GIF.
+
+use crate::color::to_rgb8;
+use crate::image::Image;
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+/// Destination of the frames of an animation, written one after the other. All the frames
+/// must have the size of the first one.
+pub trait FrameWriter {
+    fn write_frame(&mut self, frame: &Image) -> io::Result<()>;
+
+    /// Ends the stream once all the frames are written.
+    fn finish(&mut self) -> io::Result<()>;
+}
+
+fn check_frame_size(expected: (usize, usize), frame: &Image) -> io::Result<()> {
+    if (frame.width(), frame.height()) != expected {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "frame is {}x{} but the video is {}x{}",
+                frame.width(),
+                frame.height(),
+                expected.0,
+                expected.1
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Writes frames as an uncompressed YUV4MPEG2 stream, such as to the standard input of
+/// `ffmpeg -i - video.mp4`. Colors are converted to full range YCbCr with the BT.601 weights of
+/// JPEG, and the chroma is averaged over blocks of 2x2 pixels (4:2:0).
+pub struct Y4mWriter<W: Write> {
+    out: W,
+    frames_per_second: u32,
+    size: Option<(usize, usize)>, // Size of the frames, once the header is written
+}
+
+impl<W: Write> Y4mWriter<W> {
+    pub fn new(out: W, frames_per_second: u32) -> Self {
+        Self {
+            out,
+            frames_per_second,
+            size: None,
+        }
+    }
+}
+
+impl<W: Write> FrameWriter for Y4mWriter<W> {
+    fn write_frame(&mut self, frame: &Image) -> io::Result<()> {
+        let (width, height) = (frame.width(), frame.height());
+        match self.size {
+            Some(size) => check_frame_size(size, frame)?,
+            None => {
+                writeln!(
+                    self.out,
+                    "YUV4MPEG2 W{} H{} F{}:1 Ip A1:1 C420jpeg XCOLORRANGE=FULL",
+                    width, height, self.frames_per_second
+                )?;
+                self.size = Some((width, height));
+            }
+        }
+
+        let rgb: Vec<[f64; 3]> = frame
+            .pixels()
+            .iter()
+            .map(|color| to_rgb8(color).map(f64::from))
+            .collect();
+        let luma: Vec<u8> = rgb
+            .iter()
+            .map(|[r, g, b]| (0.299 * r + 0.587 * g + 0.114 * b).round() as u8)
+            .collect();
+
+        let (chroma_width, chroma_height) = (width.div_ceil(2), height.div_ceil(2));
+        let mut cb = Vec::with_capacity(chroma_width * chroma_height);
+        let mut cr = Vec::with_capacity(chroma_width * chroma_height);
+        for j in 0..chroma_height {
+            for i in 0..chroma_width {
+                let block: Vec<_> = (2 * j..(2 * j + 2).min(height))
+                    .flat_map(|y| (2 * i..(2 * i + 2).min(width)).map(move |x| y * width + x))
+                    .collect();
+                let mean = |channel: usize| {
+                    block.iter().map(|&k| rgb[k][channel]).sum::<f64>() / block.len() as f64
+                };
+                let (r, g, b) = (mean(0), mean(1), mean(2));
+                cb.push((128. - 0.168736 * r - 0.331264 * g + 0.5 * b).round() as u8);
+                cr.push((128. + 0.5 * r - 0.418688 * g - 0.081312 * b).round() as u8);
+            }
+        }
+
+        self.out.write_all(b"FRAME\n")?;
+        self.out.write_all(&luma)?;
+        self.out.write_all(&cb)?;
+        self.out.write_all(&cr)
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// Where the colors of the frames of a GIF come from.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum GifPalette {
+    /// Every frame gets its own palette of up to 256 colors chosen by median cut. Each frame
+    /// looks its best, but colors that barely change between frames can shimmer as the
+    /// palettes around them change.
+    #[default]
+    PerFrame,
+    /// The palette chosen for the first frame is used for all of them, as the global palette of
+    /// the file. Steady for animations keeping the same colors, like camera moves.
+    FirstFrame,
+    /// A palette of 1 to 256 colors given for all the frames.
+    Fixed(Vec<[u8; 3]>),
+}
+
+/// Writes frames as an animated GIF looping forever. The frames are reduced to the colors of
+/// a `GifPalette`, optionally dithered with Floyd-Steinberg error diffusion to hide the
+/// banding of smooth gradients.
+pub struct GifWriter<W: Write> {
+    out: W,
+    delay: u16,                   // Time every frame is shown, in hundredths of a second
+    dithering: bool,              // Whether to diffuse the quantization error
+    palette: GifPalette,          // How the colors of the frames are chosen
+    global: Option<Vec<[u8; 3]>>, // Palette shared by all the frames, once the header is written
+    size: Option<(usize, usize)>, // Size of the frames, once the header is written
+}
+
+impl<W: Write> GifWriter<W> {
+    /// GIF frame times are in hundredths of a second, so the frame rate is rounded to the
+    /// nearest one that can be stored.
+    pub fn new(out: W, frames_per_second: u32) -> Self {
+        let delay = (100. / frames_per_second.max(1) as f64).round() as u16;
+        Self {
+            out,
+            delay: delay.max(1),
+            dithering: true,
+            palette: GifPalette::PerFrame,
+            global: None,
+            size: None,
+        }
+    }
+
+    pub fn with_dithering(mut self, dithering: bool) -> Self {
+        self.dithering = dithering;
+        self
+    }
+
+    pub fn with_palette(mut self, palette: GifPalette) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    fn write_header(&mut self, width: usize, height: usize) -> io::Result<()> {
+        let (Ok(width), Ok(height)) = (u16::try_from(width), u16::try_from(height)) else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "GIF images are at most 65535 pixels wide and high",
+            ));
+        };
+        self.out.write_all(b"GIF89a")?;
+        // Logical screen, with a global palette of 256 colors if the frames share one.
+        self.out.write_all(&width.to_le_bytes())?;
+        self.out.write_all(&height.to_le_bytes())?;
+        match &self.global {
+            Some(palette) => {
+                self.out.write_all(&[0xf7, 0, 0])?;
+                write_palette(&mut self.out, palette)?;
+            }
+            None => self.out.write_all(&[0, 0, 0])?,
+        }
+        // Netscape extension looping the animation forever.
+        self.out.write_all(&[0x21, 0xff, 11])?;
+        self.out.write_all(b"NETSCAPE2.0")?;
+        self.out.write_all(&[3, 1, 0, 0, 0])
+    }
+}
+
+impl<W: Write> FrameWriter for GifWriter<W> {
+    fn write_frame(&mut self, frame: &Image) -> io::Result<()> {
+        let (width, height) = (frame.width(), frame.height());
+        let rgb: Vec<[u8; 3]> = frame.pixels().iter().map(to_rgb8).collect();
+        match self.size {
+            Some(size) => check_frame_size(size, frame)?,
+            None => {
+                self.global = match &self.palette {
+                    GifPalette::PerFrame => None,
+                    GifPalette::FirstFrame => Some(median_cut(&rgb, 256)),
+                    GifPalette::Fixed(palette) if (1..=256).contains(&palette.len()) => {
+                        Some(palette.clone())
+                    }
+                    GifPalette::Fixed(_) => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            "GIF palettes have 1 to 256 colors",
+                        ))
+                    }
+                };
+                self.write_header(width, height)?;
+                self.size = Some((width, height));
+            }
+        }
+
+        let local = self.global.is_none().then(|| median_cut(&rgb, 256));
+        let palette = local
+            .as_ref()
+            .or(self.global.as_ref())
+            .expect("frames have a global or a local palette");
+        let indices = if self.dithering {
+            dither(&rgb, width, palette)
+        } else {
+            rgb.iter().map(|color| nearest(palette, color)).collect()
+        };
+
+        // Graphic control extension with the frame time, leaving the frame in place.
+        self.out.write_all(&[0x21, 0xf9, 4, 0b100])?;
+        self.out.write_all(&self.delay.to_le_bytes())?;
+        self.out.write_all(&[0, 0])?;
+        // Image descriptor covering the whole screen, with a local palette of 256 colors
+        // unless the global one is used.
+        self.out.write_all(&[0x2c, 0, 0, 0, 0])?;
+        self.out.write_all(&(width as u16).to_le_bytes())?;
+        self.out.write_all(&(height as u16).to_le_bytes())?;
+        match &local {
+            Some(palette) => {
+                self.out.write_all(&[0x87])?;
+                write_palette(&mut self.out, palette)?;
+            }
+            None => self.out.write_all(&[0])?,
+        }
+
+        self.out.write_all(&[8])?;
+        for block in lzw_encode(&indices).chunks(255) {
+            self.out.write_all(&[block.len() as u8])?;
+            self.out.write_all(block)?;
+        }
+        self.out.write_all(&[0])
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.out.write_all(&[0x3b])?;
+        self.out.flush()
+    }
+}
+
+// Color table of 256 entries, padded with black.
+fn write_palette(out: &mut impl Write, palette: &[[u8; 3]]) -> io::Result<()> {
+    for k in 0..256 {
+        out.write_all(palette.get(k).unwrap_or(&[0, 0, 0]))?;
+    }
+    Ok(())
+}
+
+// A palette of at most `size` colors: the colors are split into boxes at the median of their
+// widest channel, starting with the box of the widest range, and every box gives the average
+// of its colors.
+fn median_cut(colors: &[[u8; 3]], size: usize) -> Vec<[u8; 3]> {
+    let range = |colors: &[[u8; 3]]| {
+        (0..3)
+            .map(|channel| {
+                let values = colors.iter().map(|color| color[channel]);
+                let (min, max) = values.fold((u8::MAX, u8::MIN), |(min, max), value| {
+                    (min.min(value), max.max(value))
+                });
+                (max.saturating_sub(min), channel)
+            })
+            .max()
+            .unwrap_or((0, 0))
+    };
+
+    // Every box keeps the width and the channel of its widest range, found once when it is
+    // made, so that a split only goes through the colors of the box being split.
+    let mut boxes = vec![(range(colors), colors.to_vec())];
+    while boxes.len() < size {
+        let Some((index, &((_, channel), _))) = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, ((width, _), _))| *width > 0)
+            .max_by_key(|(_, ((width, _), _))| *width)
+        else {
+            break;
+        };
+        let (_, mut colors) = boxes.swap_remove(index);
+        colors.sort_unstable_by_key(|color| color[channel]);
+        // Colors equal to the median all go to the same side.
+        let median = colors[colors.len() / 2][channel];
+        let split = match colors.partition_point(|color| color[channel] < median) {
+            0 => colors.partition_point(|color| color[channel] <= median),
+            split => split,
+        };
+        let upper = colors.split_off(split);
+        boxes.push((range(&colors), colors));
+        boxes.push((range(&upper), upper));
+    }
+
+    boxes
+        .iter()
+        .map(|(_, colors)| colors)
+        .filter(|colors| !colors.is_empty())
+        .map(|colors| {
+            let mean = |channel: usize| {
+                let sum: usize = colors.iter().map(|color| color[channel] as usize).sum();
+                ((sum + colors.len() / 2) / colors.len()) as u8
+            };
+            [mean(0), mean(1), mean(2)]
+        })
+        .collect()
+}
+
+fn nearest(palette: &[[u8; 3]], color: &[u8; 3]) -> u8 {
+    let distance = |entry: &[u8; 3]| {
+        (0..3)
+            .map(|channel| (entry[channel] as i32 - color[channel] as i32).pow(2))
+            .sum::<i32>()
+    };
+    (0..palette.len())
+        .min_by_key(|&k| distance(&palette[k]))
+        .unwrap_or(0) as u8
+}
+
+// Palette indices of the pixels, pushing the error of every pixel onto the pixels to its
+// right and below that are still to come, with the weights of Floyd and Steinberg.
+fn dither(colors: &[[u8; 3]], width: usize, palette: &[[u8; 3]]) -> Vec<u8> {
+    let mut errors = vec![[0.; 3]; colors.len()];
+    let mut indices = Vec::with_capacity(colors.len());
+    for (k, color) in colors.iter().enumerate() {
+        let wanted = [0, 1, 2].map(|c| (color[c] as f64 + errors[k][c]).clamp(0., 255.));
+        let index = nearest(palette, &wanted.map(|value| value.round() as u8));
+        indices.push(index);
+
+        let error = [0, 1, 2].map(|c| wanted[c] - palette[index as usize][c] as f64);
+        let (i, height) = (k % width, colors.len() / width);
+        let j = k / width;
+        let mut spread = |di: isize, dj: usize, weight: f64| {
+            let (i, j) = (i as isize + di, j + dj);
+            if i >= 0 && (i as usize) < width && j < height {
+                let target = &mut errors[j * width + i as usize];
+                for c in 0..3 {
+                    target[c] += error[c] * weight;
+                }
+            }
+        };
+        spread(1, 0, 7. / 16.);
+        spread(-1, 1, 3. / 16.);
+        spread(0, 1, 5. / 16.);
+        spread(1, 1, 1. / 16.);
+    }
+    indices
+}
+
+// LZW compression of 8-bit palette indices into GIF codes, packed from the least significant
+// bit. Codes start 9 bits wide and widen as the dictionary grows, up to 12 bits, after which
+// the dictionary is cleared.
+fn lzw_encode(indices: &[u8]) -> Vec<u8> {
+    const CLEAR: u16 = 256;
+    const END: u16 = 257;
+    const MAX_CODES: u16 = 4096;
+
+    let mut bytes = Vec::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    let mut write = |code: u16, width: u32| {
+        buffer |= (code as u32) << bits;
+        bits += width;
+        while bits >= 8 {
+            bytes.push(buffer as u8);
+            buffer >>= 8;
+            bits -= 8;
+        }
+    };
+
+    let mut dictionary: HashMap<(u16, u8), u16> = HashMap::new();
+    let (mut next_code, mut width) = (END + 1, 9);
+    write(CLEAR, width);
+    let mut prefix: Option<u16> = None;
+    for &index in indices {
+        let Some(code) = prefix else {
+            prefix = Some(index as u16);
+            continue;
+        };
+        if let Some(&longer) = dictionary.get(&(code, index)) {
+            prefix = Some(longer);
+            continue;
+        }
+        write(code, width);
+        if next_code == MAX_CODES {
+            write(CLEAR, width);
+            dictionary.clear();
+            (next_code, width) = (END + 1, 9);
+        } else {
+            dictionary.insert((code, index), next_code);
+            next_code += 1;
+            // The decoder adds every code one step later, so it widens one code after this.
+            if next_code > 1 << width && width < 12 {
+                width += 1;
+            }
+        }
+        prefix = Some(index as u16);
+    }
+    if let Some(code) = prefix {
+        write(code, width);
+    }
+    write(END, width);
+    if bits > 0 {
+        bytes.push(buffer as u8);
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    // A plain GIF LZW decoder, to check the encoder against.
+    fn lzw_decode(bytes: &[u8]) -> Vec<u8> {
+        let mut position = 0;
+        let mut read = |width: usize| -> u16 {
+            let mut code = 0;
+            for bit in 0..width {
+                let byte = bytes[(position + bit) / 8];
+                code |= (((byte >> ((position + bit) % 8)) & 1) as u16) << bit;
+            }
+            position += width;
+            code
+        };
+        let reset = || -> Vec<Vec<u8>> { (0..258).map(|k| vec![k as u8]).collect() };
+        let (mut table, mut width) = (reset(), 9);
+        let mut previous: Option<Vec<u8>> = None;
+        let mut output = Vec::new();
+        loop {
+            let code = read(width) as usize;
+            if code == 256 {
+                (table, width, previous) = (reset(), 9, None);
+                continue;
+            }
+            if code == 257 {
+                return output;
+            }
+            let entry = match (table.get(code), &previous) {
+                (Some(entry), _) => entry.clone(),
+                (None, Some(previous)) => [previous.clone(), vec![previous[0]]].concat(),
+                (None, None) => panic!("invalid code {}", code),
+            };
+            output.extend_from_slice(&entry);
+            if let Some(previous) = previous {
+                if table.len() < 4096 {
+                    table.push([previous, vec![entry[0]]].concat());
+                }
+                if table.len() == 1 << width && width < 12 {
+                    width += 1;
+                }
+            }
+            previous = Some(entry);
+        }
+    }
+
+    #[test]
+    fn lzw_round_trips() {
+        // Long enough to fill the dictionary and clear it, with repeats and noise.
+        let mut state = 1u32;
+        let indices: Vec<u8> = (0..200_000)
+            .map(|k| {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                if k % 3000 < 1500 {
+                    (k / 7 % 5) as u8
+                } else {
+                    (state >> 24) as u8
+                }
+            })
+            .collect();
+        assert_eq!(lzw_decode(&lzw_encode(&indices)), indices);
+        assert_eq!(lzw_decode(&lzw_encode(&[])), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn median_cut_keeps_distinct_colors() {
+        let colors = [[255, 0, 0], [0, 255, 0], [0, 0, 255], [255, 0, 0]];
+        let mut palette = median_cut(&colors, 256);
+        palette.sort();
+        assert_eq!(palette, vec![[0, 0, 255], [0, 255, 0], [255, 0, 0]]);
+        assert_eq!(median_cut(&colors, 2).len(), 2);
+    }
+
+    #[test]
+    fn dithering_keeps_the_average_color() {
+        // A mid gray between the two colors of the palette comes out as a mix of both.
+        let colors = vec![[100, 100, 100]; 64 * 64];
+        let palette = [[0, 0, 0], [200, 200, 200]];
+        let indices = dither(&colors, 64, &palette);
+        let mean = indices
+            .iter()
+            .map(|&k| palette[k as usize][0] as f64)
+            .sum::<f64>()
+            / indices.len() as f64;
+        assert!((mean - 100.).abs() < 2., "{}", mean);
+    }
+
+    #[test]
+    fn y4m_frames_have_a_header_and_three_planes() {
+        let mut frame = Image::new(3, 3);
+        frame.set(0, 0, Color::one());
+        let mut bytes = Vec::new();
+        let mut video = Y4mWriter::new(&mut bytes, 24);
+        video.write_frame(&frame).unwrap();
+        video.write_frame(&frame).unwrap();
+        assert!(video.write_frame(&Image::new(2, 2)).is_err());
+        video.finish().unwrap();
+
+        let header = b"YUV4MPEG2 W3 H3 F24:1 Ip A1:1 C420jpeg XCOLORRANGE=FULL\n";
+        assert!(bytes.starts_with(header));
+        let frame_size = "FRAME\n".len() + 9 + 2 * 4;
+        assert_eq!(bytes.len(), header.len() + 2 * frame_size);
+        let planes = &bytes[header.len() + 6..];
+        assert_eq!(planes[0], 255);
+        assert_eq!(planes[1], 0);
+        // Gray has no chroma.
+        assert_eq!(planes[9], 128);
+    }
+
+    #[test]
+    fn gifs_are_framed_by_header_and_trailer() {
+        let mut bytes = Vec::new();
+        let mut video = GifWriter::new(&mut bytes, 25);
+        video.write_frame(&Image::new(4, 2)).unwrap();
+        video.finish().unwrap();
+        assert!(bytes.starts_with(b"GIF89a\x04\x00\x02\x00"));
+        assert_eq!(bytes.last(), Some(&0x3b));
+        // 4 hundredths of a second per frame.
+        let control = bytes.windows(2).position(|w| w == [0x21, 0xf9]).unwrap();
+        assert_eq!(bytes[control + 4..control + 6], [4, 0]);
+    }
+
+    #[test]
+    fn shared_palettes_are_written_once() {
+        let mut frame = Image::new(4, 2);
+        frame.set(0, 0, Color::one());
+        let palette = GifPalette::Fixed(vec![[0, 0, 0], [255, 255, 255]]);
+        let mut bytes = Vec::new();
+        let mut video = GifWriter::new(&mut bytes, 25).with_palette(palette);
+        video.write_frame(&frame).unwrap();
+        video.write_frame(&frame).unwrap();
+        video.finish().unwrap();
+        // A global palette, and image descriptors without a local one.
+        assert_eq!(bytes[10], 0xf7);
+        assert_eq!(bytes[13..19], [0, 0, 0, 255, 255, 255]);
+        let descriptors: Vec<usize> = (0..bytes.len() - 10)
+            .filter(|&k| bytes[k] == 0x2c && bytes[k + 5..k + 9] == [4, 0, 2, 0])
+            .collect();
+        assert_eq!(descriptors.len(), 2);
+        assert!(descriptors.iter().all(|&k| bytes[k + 9] == 0));
+
+        let mut video = GifWriter::new(Vec::new(), 25).with_palette(GifPalette::Fixed(vec![]));
+        assert!(video.write_frame(&frame).is_err());
+    }
+}
@@ -0,0 +1,228 @@
+//! Dense grids of voxels describing heterogeneous volumes, such as the output of smoke and
+//! fire simulations.
+//!
+//! Grids are stored in a simple binary format: the magic bytes `RTVOXEL1`, the number of voxels
+//! along X, Y and Z and the number of channels as little-endian `u32`s, then the voxels as
+//! little-endian `f32`s, with X varying fastest and Z slowest. The first channel is the density,
+//! the optional second one the temperature in kelvin.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 8] = b"RTVOXEL1";
+
+// Voxels reserved up front when reading a grid: the header can't be trusted with more before
+// the data is actually there.
+const MAX_PREALLOCATION: usize = 1 << 20;
+
+#[derive(Clone, Debug)]
+pub struct VoxelGrid {
+    size: [usize; 3],
+    density: Vec<f32>,
+    temperature: Option<Vec<f32>>,
+    max_density: f64,
+}
+
+impl VoxelGrid {
+    /// A grid of `size[0] * size[1] * size[2]` densities, with X varying fastest. There must be
+    /// at least one voxel along each axis.
+    pub fn new(size: [usize; 3], density: Vec<f32>) -> Self {
+        assert!(!size.contains(&0), "the grid is empty");
+        assert_eq!(
+            Some(density.len()),
+            voxel_count(size),
+            "the densities don't match the grid size"
+        );
+        let max_density = density.iter().fold(0f64, |max, &d| max.max(d as f64));
+        Self {
+            size,
+            density,
+            temperature: None,
+            max_density,
+        }
+    }
+
+    /// Adds temperatures in kelvin, laid out like the densities.
+    pub fn with_temperature(mut self, temperature: Vec<f32>) -> Self {
+        assert_eq!(
+            temperature.len(),
+            self.density.len(),
+            "the temperatures don't match the grid size"
+        );
+        self.temperature = Some(temperature);
+        self
+    }
+
+    pub fn size(&self) -> [usize; 3] {
+        self.size
+    }
+
+    /// The highest density in the grid, which bounds the interpolated density everywhere.
+    pub fn max_density(&self) -> f64 {
+        self.max_density
+    }
+
+    pub fn has_temperature(&self) -> bool {
+        self.temperature.is_some()
+    }
+
+    /// Trilinearly interpolated density at a position given in [0, 1] over every axis of the
+    /// grid, zero outside of it.
+    pub fn density_at(&self, position: [f64; 3]) -> f64 {
+        self.interpolate(&self.density, position)
+    }
+
+    /// Interpolated temperature like `density_at`, zero if the grid has no temperatures.
+    pub fn temperature_at(&self, position: [f64; 3]) -> f64 {
+        match &self.temperature {
+            Some(temperature) => self.interpolate(temperature, position),
+            None => 0.,
+        }
+    }
+
+    fn interpolate(&self, values: &[f32], position: [f64; 3]) -> f64 {
+        if position.iter().any(|p| !(0. ..=1.).contains(p)) {
+            return 0.;
+        }
+        // Voxel values sit at the center of their cells.
+        let mut lower = [0; 3];
+        let mut upper = [0; 3];
+        let mut blend = [0.; 3];
+        for axis in 0..3 {
+            let last = self.size[axis] - 1;
+            let x = (position[axis] * self.size[axis] as f64 - 0.5).clamp(0., last as f64);
+            lower[axis] = (x.floor() as usize).min(last);
+            upper[axis] = (lower[axis] + 1).min(last);
+            blend[axis] = x - lower[axis] as f64;
+        }
+        let value = |x: usize, y: usize, z: usize| -> f64 {
+            values[(z * self.size[1] + y) * self.size[0] + x] as f64
+        };
+        let lerp = |a: f64, b: f64, t: f64| a + (b - a) * t;
+        let along_x =
+            |y: usize, z: usize| lerp(value(lower[0], y, z), value(upper[0], y, z), blend[0]);
+        let along_y = |z: usize| lerp(along_x(lower[1], z), along_x(upper[1], z), blend[1]);
+        lerp(along_y(lower[2]), along_y(upper[2]), blend[2])
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::read_from(&mut BufReader::new(File::open(path)?))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.write_to(&mut out)?;
+        out.flush()
+    }
+
+    pub fn write_to<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        out.write_all(MAGIC)?;
+        let channels = if self.temperature.is_some() { 2 } else { 1 };
+        for value in [self.size[0], self.size[1], self.size[2], channels] {
+            out.write_all(&(value as u32).to_le_bytes())?;
+        }
+        for (index, density) in self.density.iter().enumerate() {
+            out.write_all(&density.to_le_bytes())?;
+            if let Some(temperature) = &self.temperature {
+                out.write_all(&temperature[index].to_le_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn read_from<R: Read>(input: &mut R) -> io::Result<Self> {
+        let mut magic = [0; 8];
+        input.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(io::ErrorKind::InvalidData.into());
+        }
+
+        let mut bytes = [0; 4];
+        let mut read_u32 = |input: &mut R| -> io::Result<u32> {
+            input.read_exact(&mut bytes)?;
+            Ok(u32::from_le_bytes(bytes))
+        };
+        let size = [
+            read_u32(input)? as usize,
+            read_u32(input)? as usize,
+            read_u32(input)? as usize,
+        ];
+        let channels = read_u32(input)?;
+        if size.contains(&0) || !(1..=2).contains(&channels) {
+            return Err(io::ErrorKind::InvalidData.into());
+        }
+        let count = voxel_count(size).ok_or(io::Error::from(io::ErrorKind::InvalidData))?;
+
+        let read_f32 = |input: &mut R| -> io::Result<f32> {
+            let mut bytes = [0; 4];
+            // The header promised more voxels than the stream holds.
+            input
+                .read_exact(&mut bytes)
+                .map_err(|error| match error.kind() {
+                    io::ErrorKind::UnexpectedEof => io::ErrorKind::InvalidData.into(),
+                    _ => error,
+                })?;
+            Ok(f32::from_le_bytes(bytes))
+        };
+        // The vectors grow past the preallocation as the voxels arrive.
+        let capacity = count.min(MAX_PREALLOCATION);
+        let mut density = Vec::with_capacity(capacity);
+        let mut temperature = Vec::with_capacity(if channels == 2 { capacity } else { 0 });
+        for _ in 0..count {
+            density.push(read_f32(input)?);
+            if channels == 2 {
+                temperature.push(read_f32(input)?);
+            }
+        }
+        let grid = Self::new(size, density);
+        Ok(if channels == 2 {
+            grid.with_temperature(temperature)
+        } else {
+            grid
+        })
+    }
+}
+
+// Number of voxels in a grid of the given size, if it fits in a `usize`.
+fn voxel_count(size: [usize; 3]) -> Option<usize> {
+    size[0].checked_mul(size[1])?.checked_mul(size[2])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_round_trips_and_interpolates() {
+        let grid = VoxelGrid::new([2, 1, 1], vec![0., 1.]).with_temperature(vec![1000., 3000.]);
+        let mut bytes = Vec::new();
+        grid.write_to(&mut bytes).unwrap();
+        let grid = VoxelGrid::read_from(&mut bytes.as_slice()).unwrap();
+
+        assert_eq!(grid.size(), [2, 1, 1]);
+        assert_eq!(grid.max_density(), 1.);
+        assert_eq!(grid.density_at([0.1, 0.5, 0.5]), 0.);
+        assert_eq!(grid.density_at([0.5, 0.5, 0.5]), 0.5);
+        assert_eq!(grid.temperature_at([0.5, 0.5, 0.5]), 2000.);
+        assert_eq!(grid.density_at([1.5, 0.5, 0.5]), 0.);
+        assert!(VoxelGrid::read_from(&mut &bytes[..20]).is_err());
+    }
+
+    #[test]
+    fn truncated_grids_are_invalid() {
+        let mut bytes = MAGIC.to_vec();
+        for value in [1u32 << 20, 1 << 20, 2, 1] {
+            bytes.extend(value.to_le_bytes());
+        }
+        bytes.extend(1f32.to_le_bytes());
+        let error = VoxelGrid::read_from(&mut bytes.as_slice()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    #[should_panic(expected = "the grid is empty")]
+    fn empty_grids_are_rejected() {
+        VoxelGrid::new([0, 1, 1], vec![]);
+    }
+}